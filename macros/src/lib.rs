@@ -39,9 +39,13 @@ mod derive_custom_model;
 ///
 /// The `my_service_method_executor` function can be called from other services to ensure transactionality,
 /// while `my_service_method` can be called from controllers without exposing database details.
+///
+/// With `#[executor(transaction)]`, `my_service_method` runs the executor in an immediate transaction instead, which is
+/// committed when it returns `Ok` and rolled back otherwise. The method must return a `Result` with an `AppError`, and
+/// everything it calls must use `tx`, i.e. the executors of other services, or it waits for the lock it holds.
 #[proc_macro_attribute]
-pub fn executor(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    service_executor::executor_impl(_attr, item)
+pub fn executor(attr: TokenStream, item: TokenStream) -> TokenStream {
+    service_executor::executor_impl(attr, item)
 }
 
 #[proc_macro_derive(DeriveCustomModel, attributes(custom_model))]
@@ -2,7 +2,12 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, parse_quote, FnArg, Ident, ItemFn};
 
-pub(crate) fn executor_impl(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub(crate) fn executor_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let transaction = match parse_macro_input!(attr as Option<Ident>) {
+        Some(ident) if ident == "transaction" => true,
+        Some(ident) => return syn::Error::new(ident.span(), "expected `transaction`").to_compile_error().into(),
+        None => false
+    };
     let input_fn = parse_macro_input!(item as ItemFn);
     let original_sig = &input_fn.sig;
     let original_block = &input_fn.block;
@@ -60,11 +65,27 @@ pub(crate) fn executor_impl(_attr: TokenStream, item: TokenStream) -> TokenStrea
         }
     });
 
-    // Generate the new original function body
-    let new_body = quote! {
-        {
-            let mut tx = self.db_pool.acquire().await.unwrap();
-            self.#executor_name(&mut tx, #(#call_params),*).await
+    // Generate the new original function body. Transactions take the write lock right away, so the reads they make
+    // before writing cannot be changed by another connection in between.
+    let new_body = if transaction {
+        quote! {
+            {
+                let mut tx = self.db_pool.begin_with("BEGIN IMMEDIATE").await.map_err(crate::util::db::to_app_error)?;
+                let result = self.#executor_name(&mut tx, #(#call_params),*).await;
+
+                if result.is_ok() {
+                    tx.commit().await.map_err(crate::util::db::to_app_error)?;
+                }
+
+                result
+            }
+        }
+    } else {
+        quote! {
+            {
+                let mut tx = self.db_pool.acquire().await.unwrap();
+                self.#executor_name(&mut tx, #(#call_params),*).await
+            }
         }
     };
 
@@ -0,0 +1,15 @@
+CREATE TABLE "Session" (
+	"id"	INTEGER,
+	"user_id"	INTEGER NOT NULL,
+	"refresh_token_hash"	TEXT NOT NULL UNIQUE,
+	"previous_refresh_token_hash"	TEXT,
+	"created_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"last_refreshed_at"	TEXT,
+	"revoked_at"	TEXT,
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id") ON DELETE CASCADE,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_Session_user_id" ON "Session" ("user_id");
+CREATE INDEX "idx_Session_previous_refresh_token_hash" ON "Session" ("previous_refresh_token_hash");
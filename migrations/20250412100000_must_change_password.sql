@@ -0,0 +1,6 @@
+ALTER TABLE "AppUser" ADD COLUMN "must_change_password" INTEGER NOT NULL DEFAULT 0;
+
+-- The seeded super admin password hash is public, so it must be replaced on first sign in
+UPDATE "AppUser"
+SET "must_change_password" = 1
+WHERE "username" = 'super.admin' AND "password" = '$2a$12$iQzXMMzW77eEvjRw5GZ57u3i4gSTlUE2AMk9vsDe2wcq7p8SGBF7m';
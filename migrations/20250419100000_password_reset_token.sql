@@ -0,0 +1,12 @@
+CREATE TABLE "PasswordResetToken" (
+	"id"	INTEGER,
+	"user_id"	INTEGER NOT NULL,
+	"token_hash"	TEXT NOT NULL UNIQUE,
+	"created_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"used_at"	TEXT,
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id") ON DELETE CASCADE,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_PasswordResetToken_user_id" ON "PasswordResetToken" ("user_id");
@@ -0,0 +1,15 @@
+ALTER TABLE "AppUser" ADD COLUMN "totp_secret" TEXT;
+ALTER TABLE "AppUser" ADD COLUMN "totp_enabled" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "AppUser" ADD COLUMN "totp_last_used_step" INTEGER;
+
+CREATE TABLE "RecoveryCode" (
+	"id"	INTEGER,
+	"user_id"	INTEGER NOT NULL,
+	"code_hash"	TEXT NOT NULL,
+	"created_at"	TEXT NOT NULL,
+	"used_at"	TEXT,
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id") ON DELETE CASCADE,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_RecoveryCode_user_id" ON "RecoveryCode" ("user_id");
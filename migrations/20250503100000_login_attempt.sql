@@ -0,0 +1,22 @@
+CREATE TABLE "LoginAttempt" (
+	"id"	INTEGER,
+	"username"	TEXT NOT NULL,
+	"ip_address"	TEXT NOT NULL,
+	"success"	INTEGER NOT NULL,
+	"failure_reason"	TEXT,
+	"attempted_at"	TEXT NOT NULL,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_LoginAttempt_username" ON "LoginAttempt" ("username");
+CREATE INDEX "idx_LoginAttempt_ip_address" ON "LoginAttempt" ("ip_address");
+
+-- Consecutive failures per username and per client ip. Rows are removed on successful sign in or admin unlock.
+CREATE TABLE "LoginThrottle" (
+	"kind"	TEXT NOT NULL,
+	"value"	TEXT NOT NULL,
+	"failures"	INTEGER NOT NULL,
+	"last_failure_at"	TEXT NOT NULL,
+	"blocked_until"	TEXT NOT NULL,
+	PRIMARY KEY("kind", "value")
+);
@@ -0,0 +1,20 @@
+CREATE TABLE "ApiKey" (
+	"id"	INTEGER,
+	"company_id"	INTEGER NOT NULL,
+	"created_by"	INTEGER NOT NULL,
+	"name"	TEXT NOT NULL,
+	"key_prefix"	TEXT NOT NULL,
+	"key_hash"	TEXT NOT NULL UNIQUE,
+	"user"	INTEGER NOT NULL DEFAULT 0,
+	"payroll"	INTEGER NOT NULL DEFAULT 0,
+	"company"	INTEGER NOT NULL DEFAULT 0,
+	"created_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"last_used_at"	TEXT,
+	"revoked_at"	TEXT,
+	FOREIGN KEY("company_id") REFERENCES "Company"("id"),
+	FOREIGN KEY("created_by") REFERENCES "AppUser"("id"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_ApiKey_company_id" ON "ApiKey" ("company_id");
@@ -0,0 +1,14 @@
+ALTER TABLE "AppUser" ADD COLUMN "oidc_subject" TEXT;
+
+CREATE UNIQUE INDEX "idx_AppUser_oidc_subject" ON "AppUser" ("oidc_subject");
+
+CREATE TABLE "OidcLoginState" (
+	"id"	INTEGER,
+	"state_hash"	TEXT NOT NULL UNIQUE,
+	"nonce"	TEXT NOT NULL,
+	"code_verifier"	TEXT NOT NULL,
+	"created_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"used_at"	TEXT,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
@@ -0,0 +1,31 @@
+CREATE TABLE "Impersonation" (
+	"id"	INTEGER,
+	"actor_user_id"	INTEGER NOT NULL,
+	"user_id"	INTEGER NOT NULL,
+	"session_id"	INTEGER NOT NULL UNIQUE,
+	"reason"	TEXT NOT NULL,
+	"started_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"ended_at"	TEXT,
+	FOREIGN KEY("actor_user_id") REFERENCES "AppUser"("id"),
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id"),
+	FOREIGN KEY("session_id") REFERENCES "Session"("id"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_Impersonation_actor_user_id" ON "Impersonation" ("actor_user_id");
+CREATE INDEX "idx_Impersonation_user_id" ON "Impersonation" ("user_id");
+
+CREATE TABLE "ImpersonationRequest" (
+	"id"	INTEGER,
+	"impersonation_id"	INTEGER NOT NULL,
+	"actor_user_id"	INTEGER NOT NULL,
+	"user_id"	INTEGER NOT NULL,
+	"method"	TEXT NOT NULL,
+	"path"	TEXT NOT NULL,
+	"requested_at"	TEXT NOT NULL,
+	FOREIGN KEY("impersonation_id") REFERENCES "Impersonation"("id"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_ImpersonationRequest_impersonation_id" ON "ImpersonationRequest" ("impersonation_id");
@@ -0,0 +1,23 @@
+-- pending: invited, waiting for the user to choose a password. active: can sign in
+ALTER TABLE "AppUser" ADD COLUMN "status" TEXT NOT NULL DEFAULT 'active';
+
+CREATE TABLE "Invitation" (
+	"id"	INTEGER,
+	"user_id"	INTEGER NOT NULL,
+	"company_id"	INTEGER NOT NULL,
+	"invited_by"	INTEGER NOT NULL,
+	"email"	TEXT NOT NULL,
+	"token_hash"	TEXT NOT NULL UNIQUE,
+	"created_at"	TEXT NOT NULL,
+	"last_sent_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"accepted_at"	TEXT,
+	"revoked_at"	TEXT,
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id") ON DELETE CASCADE,
+	FOREIGN KEY("company_id") REFERENCES "Company"("id"),
+	FOREIGN KEY("invited_by") REFERENCES "AppUser"("id"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_Invitation_user_id" ON "Invitation" ("user_id");
+CREATE INDEX "idx_Invitation_company_id" ON "Invitation" ("company_id");
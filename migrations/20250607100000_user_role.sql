@@ -0,0 +1,9 @@
+ALTER TABLE "AppUser" ADD COLUMN "role" TEXT NOT NULL DEFAULT 'User';
+
+-- Roles were only kept as permission bitmasks, so they are recovered from them
+UPDATE "AppUser"
+SET "role" = CASE
+	WHEN EXISTS (SELECT 1 FROM "Permission" WHERE "user_id" = "AppUser"."id" AND "user" = 15 AND "payroll" = 15 AND "company" = 15) THEN 'SuperAdmin'
+	WHEN EXISTS (SELECT 1 FROM "Permission" WHERE "user_id" = "AppUser"."id" AND "user" = 240 AND "payroll" = 240 AND "company" = 224) THEN 'Admin'
+	ELSE 'User'
+END;
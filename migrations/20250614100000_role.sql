@@ -0,0 +1,58 @@
+-- Bitmasks use the layout of `Scope`: bits 0-3 are Any, 4-7 SelfCompany and 8-11 Owned, for the Create, Read,
+-- Update and Delete operations in that order
+CREATE TABLE "Role" (
+	"id"	INTEGER,
+	"name"	TEXT NOT NULL UNIQUE,
+	"user"	INTEGER NOT NULL DEFAULT 0,
+	"payroll"	INTEGER NOT NULL DEFAULT 0,
+	"company"	INTEGER NOT NULL DEFAULT 0,
+	"built_in"	INTEGER NOT NULL DEFAULT 0,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+INSERT INTO "Role" ("id", "name", "user", "payroll", "company", "built_in") VALUES (1, 'SuperAdmin', 15, 15, 15, 1);
+INSERT INTO "Role" ("id", "name", "user", "payroll", "company", "built_in") VALUES (2, 'Admin', 240, 240, 224, 1);
+INSERT INTO "Role" ("id", "name", "user", "payroll", "company", "built_in") VALUES (3, 'User', 512, 512, 512, 1);
+
+-- SQLite cannot add a column with a foreign key and a default, so the reference is checked by the application
+ALTER TABLE "AppUser" ADD COLUMN "role_id" INTEGER NOT NULL DEFAULT 3;
+
+-- Users without permissions, or with permissions customised after their role was inferred, keep them in a custom
+-- role for each distinct set of bitmasks
+CREATE TEMP TABLE "UserPermission" AS
+SELECT
+	"AppUser"."id" AS "user_id",
+	COALESCE("Permission"."user", 0) AS "user",
+	COALESCE("Permission"."payroll", 0) AS "payroll",
+	COALESCE("Permission"."company", 0) AS "company"
+FROM "AppUser"
+LEFT JOIN "Permission" ON "Permission"."user_id" = "AppUser"."id";
+
+INSERT INTO "Role" ("name", "user", "payroll", "company")
+SELECT DISTINCT 'Custom ' || "user" || '-' || "payroll" || '-' || "company", "user", "payroll", "company"
+FROM "UserPermission"
+WHERE NOT EXISTS (
+	SELECT 1 FROM "Role"
+	WHERE "Role"."user" = "UserPermission"."user"
+		AND "Role"."payroll" = "UserPermission"."payroll"
+		AND "Role"."company" = "UserPermission"."company"
+);
+
+-- Every set of bitmasks has exactly one role now
+UPDATE "AppUser"
+SET "role_id" = (
+	SELECT "Role"."id"
+	FROM "Role"
+	JOIN "UserPermission" ON "UserPermission"."user" = "Role"."user"
+		AND "UserPermission"."payroll" = "Role"."payroll"
+		AND "UserPermission"."company" = "Role"."company"
+	WHERE "UserPermission"."user_id" = "AppUser"."id"
+);
+
+DROP TABLE "UserPermission";
+
+CREATE INDEX "idx_AppUser_role_id" ON "AppUser" ("role_id");
+
+-- Permissions are now the ones of the role of the user
+ALTER TABLE "AppUser" DROP COLUMN "role";
+DROP TABLE "Permission";
@@ -0,0 +1,19 @@
+CREATE TABLE "CompanyGrant" (
+	"id"	INTEGER,
+	"user_id"	INTEGER NOT NULL,
+	"company_id"	INTEGER NOT NULL,
+	"granted_by"	INTEGER NOT NULL,
+	"user"	INTEGER NOT NULL DEFAULT 0,
+	"payroll"	INTEGER NOT NULL DEFAULT 0,
+	"company"	INTEGER NOT NULL DEFAULT 0,
+	"created_at"	TEXT NOT NULL,
+	"expires_at"	TEXT NOT NULL,
+	"revoked_at"	TEXT,
+	FOREIGN KEY("user_id") REFERENCES "AppUser"("id"),
+	FOREIGN KEY("company_id") REFERENCES "Company"("id"),
+	FOREIGN KEY("granted_by") REFERENCES "AppUser"("id"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_CompanyGrant_user_id_company_id" ON "CompanyGrant" ("user_id", "company_id");
+CREATE INDEX "idx_CompanyGrant_company_id" ON "CompanyGrant" ("company_id");
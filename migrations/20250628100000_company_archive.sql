@@ -0,0 +1 @@
+ALTER TABLE "Company" ADD COLUMN "archived_at" TEXT;
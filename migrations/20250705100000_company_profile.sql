@@ -0,0 +1,14 @@
+ALTER TABLE "Company" ADD COLUMN "legal_name" TEXT;
+-- Spanish CIF or NIF, stored in uppercase without separators
+ALTER TABLE "Company" ADD COLUMN "tax_id" TEXT;
+ALTER TABLE "Company" ADD COLUMN "address" TEXT;
+ALTER TABLE "Company" ADD COLUMN "postal_code" TEXT;
+ALTER TABLE "Company" ADD COLUMN "city" TEXT;
+ALTER TABLE "Company" ADD COLUMN "province" TEXT;
+ALTER TABLE "Company" ADD COLUMN "country" TEXT;
+ALTER TABLE "Company" ADD COLUMN "contact_email" TEXT;
+-- Key of the logo in the payroll bucket
+ALTER TABLE "Company" ADD COLUMN "logo_object_key" TEXT;
+ALTER TABLE "Company" ADD COLUMN "logo_content_type" TEXT;
+
+CREATE UNIQUE INDEX "idx_Company_tax_id" ON "Company" ("tax_id");
@@ -0,0 +1,3 @@
+ALTER TABLE "Company" ADD COLUMN "locale" TEXT NOT NULL DEFAULT 'es-ES';
+-- IANA time zone name
+ALTER TABLE "Company" ADD COLUMN "time_zone" TEXT NOT NULL DEFAULT 'Europe/Madrid';
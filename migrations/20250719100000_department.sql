@@ -0,0 +1,25 @@
+-- Departments form a tree inside a company. `manager_user_id` is the user that the SelfDepartment scope applies to
+CREATE TABLE "Department" (
+	"id"	INTEGER,
+	"company_id"	INTEGER NOT NULL,
+	"parent_id"	INTEGER,
+	"name"	TEXT NOT NULL,
+	"cost_center"	TEXT,
+	"manager_user_id"	INTEGER,
+	FOREIGN KEY("company_id") REFERENCES "Company"("id"),
+	FOREIGN KEY("parent_id") REFERENCES "Department"("id"),
+	FOREIGN KEY("manager_user_id") REFERENCES "AppUser"("id"),
+	UNIQUE("company_id", "name"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+
+CREATE INDEX "idx_Department_parent_id" ON "Department" ("parent_id");
+CREATE INDEX "idx_Department_manager_user_id" ON "Department" ("manager_user_id");
+
+ALTER TABLE "AppUser" ADD COLUMN "department_id" INTEGER REFERENCES "Department"("id");
+
+CREATE INDEX "idx_AppUser_department_id" ON "AppUser" ("department_id");
+
+-- Bits 12-15 are the SelfDepartment scope. Department managers read the payrolls of their departments, and their
+-- own user, payrolls and company like the User role
+INSERT OR IGNORE INTO "Role" ("name", "user", "payroll", "company", "built_in") VALUES ('DepartmentManager', 512, 8704, 512, 1);
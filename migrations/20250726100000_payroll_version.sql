@@ -0,0 +1,17 @@
+-- Files replaced on a payroll are kept as its prior versions. `Payroll.version` is the number of the current file
+ALTER TABLE "Payroll" ADD COLUMN "version" INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE "PayrollVersion" (
+	"id"	INTEGER,
+	"payroll_id"	INTEGER NOT NULL,
+	"version"	INTEGER NOT NULL,
+	"object_key"	TEXT NOT NULL UNIQUE,
+	"filename"	TEXT NOT NULL,
+	"content_type"	TEXT NOT NULL,
+	"file_size"	INTEGER NOT NULL,
+	"uploaded_at"	TEXT NOT NULL,
+	"replaced_at"	TEXT NOT NULL,
+	FOREIGN KEY("payroll_id") REFERENCES "Payroll"("id") ON DELETE CASCADE,
+	UNIQUE("payroll_id", "version"),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
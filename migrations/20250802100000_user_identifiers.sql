@@ -0,0 +1,6 @@
+-- Identifiers of the employee in the documents of the company, such as the payslips of the payroll provider
+ALTER TABLE "AppUser" ADD COLUMN "national_id" TEXT;
+ALTER TABLE "AppUser" ADD COLUMN "employee_number" TEXT;
+
+CREATE UNIQUE INDEX "idx_AppUser_company_id_national_id" ON "AppUser" ("company_id", "national_id");
+CREATE UNIQUE INDEX "idx_AppUser_company_id_employee_number" ON "AppUser" ("company_id", "employee_number");
@@ -0,0 +1,4 @@
+-- SHA-256 of the stored file, hex encoded, used as the ETag of its downloads. Files uploaded before it was added have none
+ALTER TABLE "Payroll" ADD COLUMN "content_hash" TEXT;
+
+ALTER TABLE "PayrollVersion" ADD COLUMN "content_hash" TEXT;
@@ -0,0 +1,14 @@
+use actix_web::{http::header, web, Responder};
+
+use crate::{auth::keys, util::json_response::json_response};
+
+/// Routes are registered at the root, outside the api scope, where clients expect them
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/.well-known/jwks.json", web::get().to(get_jwks));
+}
+
+pub async fn get_jwks() -> impl Responder {
+    json_response(&Ok(keys::get().jwks()))
+        .customize()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+}
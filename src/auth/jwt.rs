@@ -1,33 +1,98 @@
-use std::future::{ready, Ready};
 use actix_web::{http, Error as ActixWebError, FromRequest};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use futures_util::future::LocalBoxFuture;
 use serde::{Deserialize, Serialize};
 
+use crate::{error::error::AppErrorType, service};
+
+use super::keys;
+
+const API_KEY_HEADER: &str = "X-Api-Key";
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Claims {
     pub sub: i64,
+    pub sid: i64,
+    pub scope: TokenScope,
     pub exp: usize,
+    /// Real user behind an impersonation token. `sub` is the impersonated user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaims>,
+    /// Set when the request was authenticated with an API key instead of a token
+    #[serde(skip)]
+    pub api_key: Option<ApiKeyClaims>
 }
 
-pub fn generate_token(user_id: i64) -> String {
+impl Claims {
+    /// Tokens that can act on resources. Restricted tokens must be explicitly allowed by the routes that handle them.
+    pub const ACCEPTED_SCOPES: &'static [TokenScope] = &[TokenScope::Full, TokenScope::ApiKey, TokenScope::Impersonation];
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ActorClaims {
+    pub sub: i64
+}
+
+#[derive(Debug)]
+pub struct ApiKeyClaims {
+    pub id: i64,
+    pub company_id: i64
+}
+
+/// What a token can be used for. Only `Full` and `Impersonation` tokens and API keys are accepted by default,
+/// restricted tokens must be explicitly allowed by the routes that handle them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    Full,
+    PasswordChange,
+    /// Password or single sign-on verified, waiting for the second factor code
+    MfaPending,
+    /// Password or single sign-on verified, but the role of the user requires a second factor that is not enrolled yet
+    MfaEnrollment,
+    /// Claims of an API key. They are never encoded in a token
+    ApiKey,
+    /// Issued to an admin acting as another user. It cannot be refreshed and it is never allowed to change passwords
+    Impersonation
+}
+
+/// Generates a short-lived access token bound to the given session, signed with the active key.
+/// Revoking the session invalidates the token even before it expires.
+pub fn generate_token(user_id: i64, session_id: i64, scope: TokenScope) -> String {
+    let config = crate::config::get();
+
     let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
+        .checked_add_signed(chrono::Duration::seconds(config.auth.access_token_ttl))
         .expect("valid timestamp")
         .timestamp();
 
     let claims = Claims {
         sub: user_id,
+        sid: session_id,
+        scope,
         exp: expiration as usize,
+        act: None,
+        api_key: None
     };
 
-    let config = crate::config::get();
+    keys::get().encode(&claims)
+        .expect("Failed to encode token")
+}
+
+/// Generates a token to act as `user_id` on behalf of `actor_user_id`, valid until the impersonation expires
+pub fn generate_impersonation_token(actor_user_id: i64, user_id: i64, session_id: i64, expires_at: &chrono::DateTime<chrono::Utc>) -> String {
+    let claims = Claims {
+        sub: user_id,
+        sid: session_id,
+        scope: TokenScope::Impersonation,
+        exp: expires_at.timestamp() as usize,
+        act: Some(ActorClaims {
+            sub: actor_user_id
+        }),
+        api_key: None
+    };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(&config.auth.secret),
-    )
-    .expect("Failed to encode token")
+    keys::get().encode(&claims)
+        .expect("Failed to encode token")
 }
 
 // async fn validate_token(req: &ServiceRequest) -> Result<TokenData<Claims>, Error> {
@@ -47,25 +112,131 @@ pub fn generate_token(user_id: i64) -> String {
 //     .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid token"))
 // }
 
-impl FromRequest for Claims {
-    type Error = ActixWebError;
-    type Future = Ready<Result<Self, Self::Error>>;
-
-    fn from_request(req: &actix_web::HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
-        let auth_header = req.headers().get(http::header::AUTHORIZATION);
-        let token = match auth_header.and_then(|h| h.to_str().ok()) {
-            Some(header) => header.trim_start_matches("Bearer ").trim(),
-            None => return ready(Err(actix_web::error::ErrorUnauthorized("Missing Authorization header"))),
-        };
-
-        let config = crate::config::get();
-
-        ready(decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(&config.auth.secret),
-            &Validation::default()
-        )
-        .map(|data| data.claims)
-        .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid token")))
+/// Declares an extractor for the claims of tokens with any of the given scopes, for routes that act on the actor
+/// itself. Routes that act on resources take an extractor of `authorized_claims!` instead, so `Claims` cannot be
+/// extracted on its own.
+macro_rules! scoped_claims {
+    ($(#[$meta:meta])* $name:ident, [$($scope:ident),+]) => {
+        $(#[$meta])*
+        pub struct $name(pub Claims);
+
+        impl FromRequest for $name {
+            type Error = ActixWebError;
+            type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+            fn from_request(req: &actix_web::HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+                let claims = extract_claims(req, &[$(TokenScope::$scope),+]);
+
+                Box::pin(async move {
+                    claims.await.map($name)
+                })
+            }
+        }
+    };
+}
+
+scoped_claims!(
+    /// Claims of any actor that can act on resources
+    AuthenticatedClaims, [Full, ApiKey, Impersonation]
+);
+
+scoped_claims!(
+    /// Claims of a user session, for routes that cannot be used with API keys
+    SessionClaims, [Full]
+);
+
+scoped_claims!(
+    /// Claims of an impersonation token
+    ImpersonationClaims, [Impersonation]
+);
+
+scoped_claims!(
+    /// Claims of a token that is allowed to change the password
+    PasswordChangeClaims, [Full, PasswordChange]
+);
+
+scoped_claims!(
+    /// Claims of a token waiting for the second factor code
+    MfaPendingClaims, [MfaPending]
+);
+
+scoped_claims!(
+    /// Claims of a token that is allowed to enroll a second factor
+    MfaEnrollmentClaims, [Full, MfaEnrollment]
+);
+
+pub fn extract_claims(req: &actix_web::HttpRequest, accepted_scopes: &'static [TokenScope]) -> LocalBoxFuture<'static, Result<Claims, ActixWebError>> {
+    if let Some(api_key) = api_key_from_request(req) {
+        return extract_api_key_claims(api_key, accepted_scopes);
     }
+
+    let auth_header = req.headers().get(http::header::AUTHORIZATION);
+    let token = match auth_header.and_then(|h| h.to_str().ok()) {
+        Some(header) => header.trim_start_matches("Bearer ").trim(),
+        None => return Box::pin(async { Err(actix_web::error::ErrorUnauthorized("Missing Authorization header")) }),
+    };
+
+    let claims = keys::get().decode::<Claims>(token)
+        .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid token"));
+
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+
+    Box::pin(async move {
+        let claims = claims?;
+
+        if !accepted_scopes.contains(&claims.scope) {
+            return Err(actix_web::error::ErrorForbidden("Token is not valid for this operation"));
+        }
+
+        // The impersonation claim is only trusted on impersonation tokens
+        if (claims.scope == TokenScope::Impersonation) != claims.act.is_some() {
+            return Err(actix_web::error::ErrorUnauthorized("Invalid token"));
+        }
+
+        match service::get().session().is_session_active(claims.sid).await {
+            Ok(true) => (),
+            Ok(false) => return Err(actix_web::error::ErrorUnauthorized("Session has been revoked")),
+            Err(_) => return Err(actix_web::error::ErrorInternalServerError("Failed to validate session"))
+        }
+
+        if let Some(actor) = &claims.act {
+            let recorded = service::get().impersonation().record_request(actor.sub, claims.sub, claims.sid, &method, &path).await;
+
+            if let Err(error) = recorded {
+                return match error.error_type() {
+                    AppErrorType::Unauthorized => Err(actix_web::error::ErrorUnauthorized("The impersonation has ended")),
+                    _ => Err(actix_web::error::ErrorInternalServerError("Failed to record the request"))
+                };
+            }
+        }
+
+        Ok(claims)
+    })
+}
+
+/// API keys are sent either in the `X-Api-Key` header or as `Authorization: ApiKey <key>`
+fn api_key_from_request(req: &actix_web::HttpRequest) -> Option<String> {
+    if let Some(api_key) = req.headers().get(API_KEY_HEADER).and_then(|h| h.to_str().ok()) {
+        return Some(api_key.trim().to_string());
+    }
+
+    req.headers().get(http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|header| header.strip_prefix("ApiKey "))
+        .map(|api_key| api_key.trim().to_string())
+}
+
+fn extract_api_key_claims(api_key: String, accepted_scopes: &'static [TokenScope]) -> LocalBoxFuture<'static, Result<Claims, ActixWebError>> {
+    if !accepted_scopes.contains(&TokenScope::ApiKey) {
+        return Box::pin(async { Err(actix_web::error::ErrorForbidden("API keys are not valid for this operation")) });
+    }
+
+    Box::pin(async move {
+        match service::get().api_key().authenticate(&api_key).await {
+            Ok(Some(claims)) => Ok(claims),
+            Ok(None) => Err(actix_web::error::ErrorUnauthorized("Invalid API key")),
+            Err(_) => Err(actix_web::error::ErrorInternalServerError("Failed to validate API key"))
+        }
+    })
 }
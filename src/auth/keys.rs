@@ -0,0 +1,186 @@
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::{pkcs8::DecodePublicKey, VerifyingKey};
+use jsonwebtoken::{decode, decode_header, encode, errors::{Error as JwtError, ErrorKind}, jwk::{AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType}, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rsa::{pkcs1::DecodeRsaPublicKey, traits::PublicKeyParts, RsaPublicKey};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::config::{AuthConfig, SigningKeyConfig};
+
+/// Keys used to sign and verify the tokens.
+///
+/// Tokens signed with an asymmetric key carry its `kid` in the header, and are verified with that key and its
+/// algorithm only. Tokens without a `kid` are HS256 tokens signed with the configured secret. Rotating keys is done
+/// by adding a new key, making it the active one and keeping the previous one configured without its private key
+/// until the tokens it signed expire.
+pub struct KeyStore {
+    hmac_key: Option<(EncodingKey, DecodingKey)>,
+    keys: HashMap<String, AsymmetricKey>,
+    active_key_id: Option<String>
+}
+
+struct AsymmetricKey {
+    algorithm: Algorithm,
+    encoding_key: Option<EncodingKey>,
+    decoding_key: DecodingKey,
+    jwk: Jwk
+}
+
+impl KeyStore {
+    /// Loads the configured keys. Fails if a key file cannot be read or parsed, or if there is no key to sign with.
+    pub fn from_config(config: &AuthConfig) -> Result<KeyStore, String> {
+        let hmac_key = config.secret.as_ref()
+            .map(|secret| (EncodingKey::from_secret(secret), DecodingKey::from_secret(secret)));
+
+        let mut keys = HashMap::new();
+        for key_config in &config.signing_keys {
+            let key = AsymmetricKey::load(key_config)
+                .map_err(|e| format!("Key {}: {}", key_config.kid, e))?;
+
+            if keys.insert(key_config.kid.clone(), key).is_some() {
+                return Err(format!("Duplicated key id: {}", key_config.kid));
+            }
+        }
+
+        match &config.active_key_id {
+            Some(active_key_id) => match keys.get(active_key_id) {
+                Some(key) if key.encoding_key.is_some() => (),
+                Some(_) => return Err(format!("Active key {} has no private key", active_key_id)),
+                None => return Err(format!("Active key {} is not configured", active_key_id))
+            },
+            None if hmac_key.is_none() => return Err(String::from("There is no active key nor secret to sign tokens")),
+            None => ()
+        };
+
+        Ok(KeyStore {
+            hmac_key,
+            keys,
+            active_key_id: config.active_key_id.clone()
+        })
+    }
+
+    /// Signs the claims with the active key
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let (header, encoding_key) = match &self.active_key_id {
+            Some(active_key_id) => {
+                let key = &self.keys[active_key_id];
+                let mut header = Header::new(key.algorithm);
+                header.kid = Some(active_key_id.clone());
+
+                (header, key.encoding_key.as_ref().expect("active key has a private key"))
+            },
+            None => {
+                let (encoding_key, _) = self.hmac_key.as_ref().expect("secret is configured");
+
+                (Header::new(Algorithm::HS256), encoding_key)
+            }
+        };
+
+        encode(&header, claims, encoding_key)
+    }
+
+    /// Verifies the token with the key it was signed with and returns its claims
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, JwtError> {
+        let header = decode_header(token)?;
+
+        let (algorithm, decoding_key) = match &header.kid {
+            Some(kid) => match self.keys.get(kid) {
+                Some(key) => (key.algorithm, &key.decoding_key),
+                None => return Err(ErrorKind::InvalidToken.into())
+            },
+            None => match &self.hmac_key {
+                Some((_, decoding_key)) => (Algorithm::HS256, decoding_key),
+                None => return Err(ErrorKind::InvalidToken.into())
+            }
+        };
+
+        decode::<T>(token, decoding_key, &Validation::new(algorithm))
+            .map(|data| data.claims)
+    }
+
+    /// Public keys, so other services can verify our tokens. The HS256 secret is never exposed.
+    pub fn jwks(&self) -> JwkSet {
+        let mut keys: Vec<Jwk> = self.keys.values()
+            .map(|key| key.jwk.clone())
+            .collect();
+        keys.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+
+        JwkSet {
+            keys
+        }
+    }
+}
+
+impl AsymmetricKey {
+    fn load(config: &SigningKeyConfig) -> Result<AsymmetricKey, String> {
+        let public_pem = std::fs::read_to_string(&config.public_key_path)
+            .map_err(|e| format!("Cannot read {}: {}", config.public_key_path, e))?;
+        let private_pem = match &config.private_key_path {
+            Some(path) => Some(std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?),
+            None => None
+        };
+
+        let (decoding_key, encoding_key, algorithm_parameters) = match config.algorithm {
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 |
+            Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 => {
+                let public_key = RsaPublicKey::from_public_key_pem(&public_pem)
+                    .or_else(|_| RsaPublicKey::from_pkcs1_pem(&public_pem))
+                    .map_err(|e| format!("Invalid RSA public key: {}", e))?;
+
+                (
+                    DecodingKey::from_rsa_pem(public_pem.as_bytes()).map_err(|e| e.to_string())?,
+                    private_pem.map(|pem| EncodingKey::from_rsa_pem(pem.as_bytes())).transpose().map_err(|e| e.to_string())?,
+                    AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+                        e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be())
+                    })
+                )
+            },
+            Algorithm::EdDSA => {
+                let public_key = VerifyingKey::from_public_key_pem(&public_pem)
+                    .map_err(|e| format!("Invalid Ed25519 public key: {}", e))?;
+
+                (
+                    DecodingKey::from_ed_pem(public_pem.as_bytes()).map_err(|e| e.to_string())?,
+                    private_pem.map(|pem| EncodingKey::from_ed_pem(pem.as_bytes())).transpose().map_err(|e| e.to_string())?,
+                    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: URL_SAFE_NO_PAD.encode(public_key.as_bytes())
+                    })
+                )
+            },
+            other => return Err(format!("Unsupported algorithm: {:?}", other))
+        };
+
+        Ok(AsymmetricKey {
+            algorithm: config.algorithm,
+            encoding_key,
+            decoding_key,
+            jwk: Jwk {
+                common: CommonParameters {
+                    public_key_use: Some(PublicKeyUse::Signature),
+                    key_algorithm: Some(KeyAlgorithm::from_str(&format!("{:?}", config.algorithm)).map_err(|e| e.to_string())?),
+                    key_id: Some(config.kid.clone()),
+                    ..Default::default()
+                },
+                algorithm: algorithm_parameters
+            }
+        })
+    }
+}
+
+static INSTANCE: OnceLock<KeyStore> = OnceLock::new();
+
+pub fn init(key_store: KeyStore) {
+    match INSTANCE.set(key_store) {
+        Ok(_) => (),
+        Err(_) => panic!("KeyStore already initialized"),
+    }
+}
+
+pub fn get() -> &'static KeyStore {
+    INSTANCE.get().expect("KeyStore not initialized")
+}
@@ -1 +1,5 @@
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod totp;
+pub mod keys;
+pub mod jwks_controller;
+pub mod policy;
\ No newline at end of file
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crate::{entities::{company::company_policy::COMPANY_POLICY, payroll::payroll_policy::PAYROLL_POLICY, permission::permission::{Operation, ResourceKind, ScopeKind}, user::user_policy::USER_POLICY}, error::error::{AppError, AppErrorType}, service};
+
+use super::jwt::{Claims, TokenScope};
+
+/// Scopes that can allow each operation on the resources of an entity. Every entity declares its own.
+pub struct Policy {
+    pub create: &'static [ScopeKind],
+    pub read: &'static [ScopeKind],
+    pub update: &'static [ScopeKind],
+    pub delete: &'static [ScopeKind]
+}
+
+impl Policy {
+    pub fn scopes(&self, operation: Operation) -> &'static [ScopeKind] {
+        match operation {
+            Operation::Create => self.create,
+            Operation::Read => self.read,
+            Operation::Update => self.update,
+            Operation::Delete => self.delete
+        }
+    }
+}
+
+impl ResourceKind {
+    pub fn policy(&self) -> &'static Policy {
+        match self {
+            ResourceKind::User => &USER_POLICY,
+            ResourceKind::Payroll => &PAYROLL_POLICY,
+            ResourceKind::Company => &COMPANY_POLICY
+        }
+    }
+}
+
+/// What an extractor of `authorized_claims!` authorizes. By default the operation is allowed by any scope of the
+/// policy of the entity, with any token accepted by `Claims`.
+#[derive(Clone, Copy)]
+pub struct Action {
+    pub operation: Operation,
+    pub scopes: &'static [ScopeKind],
+    pub tokens: &'static [TokenScope],
+    /// The actor must hold every permission of the user that owns the resource, unless it is themselves. API keys
+    /// always must, even for their creator.
+    pub outranks_owner: bool,
+    /// `SelfCompany` scopes only match for members of the company of the resource, never through a grant
+    pub members_only: bool
+}
+
+impl Action {
+    pub const fn new(operation: Operation) -> Action {
+        Action {
+            operation,
+            scopes: &ScopeKind::ALL,
+            tokens: Claims::ACCEPTED_SCOPES,
+            outranks_owner: false,
+            members_only: false
+        }
+    }
+
+    /// Only takes the given scopes into account, for actions stricter than the policy of the entity
+    pub const fn scopes(self, scopes: &'static [ScopeKind]) -> Action {
+        Action { scopes, ..self }
+    }
+
+    pub const fn tokens(self, tokens: &'static [TokenScope]) -> Action {
+        Action { tokens, ..self }
+    }
+
+    pub const fn outranking_owner(self) -> Action {
+        Action { outranks_owner: true, ..self }
+    }
+
+    pub const fn members_only(self) -> Action {
+        Action { members_only: true, ..self }
+    }
+}
+
+/// What an operation is authorized on. Collections are used to list and create resources, optionally narrowed to
+/// the company or user they belong to.
+#[derive(Clone, Copy, Debug)]
+pub enum Resource {
+    User(i64),
+    /// Users of a company, or of every company
+    Users(Option<i64>),
+    Payroll(i64),
+    /// Payrolls of a user, or of every user
+    Payrolls(Option<i64>),
+    /// Payrolls of the users of a department
+    DepartmentPayrolls(i64),
+    /// Payrolls of the users of a company
+    CompanyPayrolls(i64),
+    Company(i64),
+    Companies,
+    /// Roles are shared by every company, so they are authorized as the users of the company of the actor
+    Roles,
+    /// The actor, for routes that act on them without their id
+    CurrentUser
+}
+
+impl Resource {
+    /// The resource with the id, or the whole collection without it
+    pub fn from_kind(kind: ResourceKind, id: Option<i64>) -> Resource {
+        match (kind, id) {
+            (ResourceKind::User, Some(user_id)) => Resource::User(user_id),
+            (ResourceKind::User, None) => Resource::Users(None),
+            (ResourceKind::Payroll, Some(payroll_id)) => Resource::Payroll(payroll_id),
+            (ResourceKind::Payroll, None) => Resource::Payrolls(None),
+            (ResourceKind::Company, Some(company_id)) => Resource::Company(company_id),
+            (ResourceKind::Company, None) => Resource::Companies
+        }
+    }
+
+    pub fn kind(&self) -> ResourceKind {
+        match self {
+            Resource::User(_) | Resource::Users(_) | Resource::Roles | Resource::CurrentUser => ResourceKind::User,
+            Resource::Payroll(_) | Resource::Payrolls(_) | Resource::DepartmentPayrolls(_) | Resource::CompanyPayrolls(_) => ResourceKind::Payroll,
+            Resource::Company(_) | Resource::Companies => ResourceKind::Company
+        }
+    }
+}
+
+/// Result of every scope of the actor for an operation on a resource, resolved to the user that owns it, its company
+/// and its department
+pub struct Authorization {
+    pub owner_user_id: Option<i64>,
+    pub company_id: Option<i64>,
+    pub department_id: Option<i64>,
+    pub checks: Vec<ScopeCheck>
+}
+
+pub struct ScopeCheck {
+    pub scope: ScopeKind,
+    /// Whether the policy of the entity lets this scope allow the operation
+    pub in_policy: bool,
+    /// Whether the permission of the actor includes the scope
+    pub held: bool,
+    pub same_company: Option<bool>,
+    pub held_through_grant: Option<bool>,
+    /// Whether the actor manages the department of the resource or one of its parents
+    pub manages_department: Option<bool>,
+    pub owns_resource: Option<bool>,
+    pub matched: bool
+}
+
+impl Authorization {
+    pub fn allowed(&self) -> bool {
+        self.checks.iter().any(|check| check.matched)
+    }
+
+    /// Only takes the scopes of the action into account
+    pub fn allowed_for(&self, action: &Action) -> bool {
+        self.checks.iter().any(|check| {
+            let through_grant = check.scope == ScopeKind::SelfCompany && !(check.held && check.same_company == Some(true));
+
+            check.matched && action.scopes.contains(&check.scope) && !(action.members_only && through_grant)
+        })
+    }
+}
+
+pub fn permission_denied() -> AppError {
+    AppError::new(
+        String::from("You do not have permission to access the requested resource"),
+        AppErrorType::Forbidden,
+        None
+    )
+}
+
+/// Id in the path of the route, e.g. `path_id(req, "payroll_id")`
+pub fn path_id(req: &actix_web::HttpRequest, name: &str) -> Option<i64> {
+    req.match_info().get(name).and_then(|id| id.parse().ok())
+}
+
+/// Optional id in the query string. Invalid ids are ignored here, the query extractor of the handler rejects them.
+pub fn query_id(req: &actix_web::HttpRequest, name: &str) -> Option<i64> {
+    actix_web::web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .ok()
+        .and_then(|query| query.get(name).and_then(|id| id.parse().ok()))
+}
+
+fn invalid_resource() -> AppError {
+    AppError::new(
+        String::from("The requested resource is not valid"),
+        AppErrorType::BadRequest,
+        None
+    )
+}
+
+/// Authorizes the action on the resource of a request for the claims of an `authorized_claims!` extractor
+pub async fn authorize_request(claims: &Claims, action: &Action, resource: Option<Resource>) -> Result<(), actix_web::Error> {
+    let resource = resource.ok_or_else(invalid_resource)?;
+
+    if !service::get().permission().authorize_action(claims, action, resource).await? {
+        return Err(permission_denied().into());
+    }
+
+    Ok(())
+}
+
+/// Declares an extractor for the claims of an actor authorized to perform an action on the resource built from the
+/// request. Handlers that take it cannot run without the authorization. The action defaults to the operation allowed
+/// by the policy of the entity, and can be narrowed for stricter ones.
+///
+/// Resources named in the body of the request are built from it with a `json` or `multipart` closure. The extractor
+/// then also holds the body, and for multipart requests the rest of the payload, e.g. the file that follows.
+#[macro_export]
+macro_rules! authorized_claims {
+    ($(#[$meta:meta])* $name:ident, action: $action:expr, |$req:ident| $resource:expr) => {
+        $(#[$meta])*
+        pub struct $name(pub $crate::auth::jwt::Claims);
+
+        impl actix_web::FromRequest for $name {
+            type Error = actix_web::Error;
+            type Future = futures_util::future::LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+            fn from_request(req: &actix_web::HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+                let action: $crate::auth::policy::Action = $action;
+                let claims = $crate::auth::jwt::extract_claims(req, action.tokens);
+                let resource: Option<$crate::auth::policy::Resource> = (|$req: &actix_web::HttpRequest| $resource)(req);
+
+                Box::pin(async move {
+                    let claims = claims.await?;
+                    $crate::auth::policy::authorize_request(&claims, &action, resource).await?;
+
+                    Ok($name(claims))
+                })
+            }
+        }
+    };
+    ($(#[$meta:meta])* $name:ident, action: $action:expr, json |$body:ident: $body_type:ty| $resource:expr) => {
+        $(#[$meta])*
+        pub struct $name(pub $crate::auth::jwt::Claims, pub $body_type);
+
+        impl actix_web::FromRequest for $name {
+            type Error = actix_web::Error;
+            type Future = futures_util::future::LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+            fn from_request(req: &actix_web::HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+                let action: $crate::auth::policy::Action = $action;
+                let claims = $crate::auth::jwt::extract_claims(req, action.tokens);
+                let body = <actix_web::web::Json<$body_type> as actix_web::FromRequest>::from_request(req, payload);
+
+                Box::pin(async move {
+                    let claims = claims.await?;
+                    let body = body.await?.into_inner();
+
+                    let resource: Option<$crate::auth::policy::Resource> = (|$body: &$body_type| $resource)(&body);
+                    $crate::auth::policy::authorize_request(&claims, &action, resource).await?;
+
+                    Ok($name(claims, body))
+                })
+            }
+        }
+    };
+    ($(#[$meta:meta])* $name:ident, action: $action:expr, multipart |$body:ident: $body_type:ty| $resource:expr) => {
+        $(#[$meta])*
+        pub struct $name(pub $crate::auth::jwt::Claims, pub $body_type, pub actix_multipart::Multipart);
+
+        impl actix_web::FromRequest for $name {
+            type Error = actix_web::Error;
+            type Future = futures_util::future::LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+            fn from_request(req: &actix_web::HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+                let action: $crate::auth::policy::Action = $action;
+                let claims = $crate::auth::jwt::extract_claims(req, action.tokens);
+                let mut multipart = actix_multipart::Multipart::new(req.headers(), payload.take());
+
+                Box::pin(async move {
+                    let claims = claims.await?;
+                    let body: $body_type = $crate::util::multipart::extract_body(&mut multipart).await?;
+
+                    let resource: Option<$crate::auth::policy::Resource> = (|$body: &$body_type| $resource)(&body);
+                    $crate::auth::policy::authorize_request(&claims, &action, resource).await?;
+
+                    Ok($name(claims, body, multipart))
+                })
+            }
+        }
+    };
+    ($(#[$meta:meta])* $name:ident, $operation:expr, |$req:ident| $resource:expr) => {
+        $crate::authorized_claims!($(#[$meta])* $name, action: $crate::auth::policy::Action::new($operation), |$req| $resource);
+    };
+    ($(#[$meta:meta])* $name:ident, $operation:expr, $scopes:expr, |$req:ident| $resource:expr) => {
+        $crate::authorized_claims!($(#[$meta])* $name, action: $crate::auth::policy::Action::new($operation).scopes($scopes), |$req| $resource);
+    };
+}
@@ -0,0 +1,172 @@
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha1::Sha1;
+
+/// RFC 6238 parameters. They are the defaults assumed by authenticator apps, so they are not configurable.
+const DIGITS: u32 = 6;
+const PERIOD: i64 = 30;
+const SECRET_LENGTH: usize = 20;
+/// Accepted clock drift, in periods, between the server and the authenticator app
+const ALLOWED_DRIFT: i64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generates a new random TOTP secret, base32 encoded as authenticator apps expect it.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut secret);
+
+    base32_encode(&secret)
+}
+
+/// Builds the `otpauth://` URI used to enroll the secret in an authenticator app (usually shown as a QR code).
+pub fn otpauth_uri(issuer: &str, account_name: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account_name),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        PERIOD
+    )
+}
+
+/// Checks a code against the secret at the given unix time.
+///
+/// Returns the time step the code belongs to, so callers can reject codes of steps that were already used.
+pub fn verify_code(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let secret = base32_decode(secret)?;
+    let code = code.trim();
+
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let code: u32 = code.parse().ok()?;
+    let current_step = unix_time / PERIOD;
+
+    (current_step - ALLOWED_DRIFT..=current_step + ALLOWED_DRIFT)
+        .find(|step| *step >= 0 && code_at_step(&secret, *step as u64) == code)
+}
+
+fn code_at_step(secret: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226, section 5.3)
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = ((hash[offset] & 0x7f) as u32) << 24
+        | (hash[offset + 1] as u32) << 16
+        | (hash[offset + 2] as u32) << 8
+        | hash[offset + 3] as u32;
+
+    binary % 10u32.pow(DIGITS)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            encoded.push(BASE32_ALPHABET[((buffer >> (bits - 5)) & 0x1f) as usize] as char);
+            bits -= 5;
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in encoded.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let value = BASE32_ALPHABET.iter().position(|a| *a as char == c.to_ascii_uppercase())? as u32;
+
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            decoded.push(((buffer >> (bits - 8)) & 0xff) as u8);
+            bits -= 8;
+        }
+    }
+
+    Some(decoded)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-1 seed of the RFC 6238 test vectors, base32 encoded
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    /// Unix time and code of the SHA-1 test vectors of RFC 6238, appendix B. The RFC uses 8 digits, these are the
+    /// last 6 of them.
+    const RFC_VECTORS: [(i64, &str); 6] = [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130")
+    ];
+
+    #[test]
+    fn matches_the_rfc_6238_test_vectors() {
+        let secret = base32_decode(RFC_SECRET).unwrap();
+        assert_eq!(secret, b"12345678901234567890");
+
+        for (unix_time, code) in RFC_VECTORS {
+            assert_eq!(format!("{:06}", code_at_step(&secret, (unix_time / PERIOD) as u64)), code);
+            assert_eq!(verify_code(RFC_SECRET, code, unix_time), Some(unix_time / PERIOD));
+        }
+    }
+
+    #[test]
+    fn accepts_codes_within_the_allowed_drift() {
+        let (unix_time, code) = RFC_VECTORS[3];
+
+        assert!(verify_code(RFC_SECRET, code, unix_time + PERIOD).is_some());
+        assert!(verify_code(RFC_SECRET, code, unix_time - PERIOD).is_some());
+        assert!(verify_code(RFC_SECRET, code, unix_time + 2 * PERIOD).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let (unix_time, _) = RFC_VECTORS[3];
+
+        assert!(verify_code(RFC_SECRET, "05924", unix_time).is_none());
+        assert!(verify_code(RFC_SECRET, "0059245", unix_time).is_none());
+        assert!(verify_code(RFC_SECRET, "00592a", unix_time).is_none());
+    }
+
+    #[test]
+    fn encodes_secrets_as_base32() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(base32_decode(&generate_secret()).map(|secret| secret.len()), Some(SECRET_LENGTH));
+    }
+}
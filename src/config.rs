@@ -1,27 +1,34 @@
-use std::sync::OnceLock;
+use std::{str::FromStr, sync::OnceLock};
+
+use jsonwebtoken::Algorithm;
 
 pub struct Config {
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
     pub file: FileConfig,
-    pub bucket: BucketConfig
+    pub bucket: BucketConfig,
+    pub mail: MailConfig,
+    /// Single sign-on is disabled when not set
+    pub oidc: Option<OidcConfig>
 }
 
 impl Config {
-    fn new(database: DatabaseConfig, auth: AuthConfig, file: FileConfig, bucket: BucketConfig) -> Config {
+    fn new(database: DatabaseConfig, auth: AuthConfig, file: FileConfig, bucket: BucketConfig, mail: MailConfig, oidc: Option<OidcConfig>) -> Config {
         Config {
             database,
             auth,
             file,
-            bucket
+            bucket,
+            mail,
+            oidc
         }
     }
 }
 
 static INSTANCE: OnceLock<Config> = OnceLock::new();
 
-pub fn initialize(database: DatabaseConfig, auth: AuthConfig, file: FileConfig, bucket: BucketConfig) {
-    match INSTANCE.set(Config::new(database, auth, file, bucket)) {
+pub fn initialize(database: DatabaseConfig, auth: AuthConfig, file: FileConfig, bucket: BucketConfig, mail: MailConfig, oidc: Option<OidcConfig>) {
+    match INSTANCE.set(Config::new(database, auth, file, bucket, mail, oidc)) {
         Ok(_) => (),
         Err(_) => panic!("Config already initialized"),
     };
@@ -36,7 +43,63 @@ pub struct DatabaseConfig {
 }
 
 pub struct AuthConfig {
-    pub secret: Vec<u8>
+    /// HS256 secret. Tokens without a `kid` are signed and verified with it
+    pub secret: Option<Vec<u8>>,
+    /// Asymmetric keys used to sign and verify tokens. Keys without a private key are only used for verification
+    pub signing_keys: Vec<SigningKeyConfig>,
+    /// Key used to sign new tokens. If not set, tokens are signed with the HS256 secret
+    pub active_key_id: Option<String>,
+    pub access_token_ttl: i64,
+    pub refresh_token_ttl: i64,
+    pub password_reset_token_ttl: i64,
+    pub invitation_token_ttl: i64,
+    /// Lifetime of impersonation tokens in seconds. They cannot be refreshed
+    pub impersonation_ttl: i64,
+    pub mfa_issuer: String,
+    /// Names of the roles whose permissions require a second factor. Users holding any of them, whatever their role,
+    /// cannot use the application until they enroll one
+    pub mfa_required_roles: Vec<String>,
+    /// Encrypts the TOTP secrets at rest. It is not used to sign tokens, so signing keys can be rotated without users
+    /// enrolling again
+    pub mfa_encryption_key: Vec<u8>,
+    /// Consecutive failed sign ins allowed for a username before it is locked
+    pub login_max_failures_per_username: i64,
+    /// Consecutive failed sign ins allowed for a client ip before it is locked
+    pub login_max_failures_per_ip: i64,
+    /// Seconds a username or ip stays locked. It also caps the backoff between failed attempts
+    pub login_lockout_duration: i64,
+    /// Use the `Forwarded`/`X-Forwarded-For` headers to get the client ip. Only enable it behind a trusted proxy
+    pub trust_forwarded_headers: bool
+}
+
+pub struct SigningKeyConfig {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub public_key_path: String,
+    pub private_key_path: Option<String>
+}
+
+impl FromStr for SigningKeyConfig {
+    type Err = String;
+
+    /// Parses a `kid:algorithm:public_key_path[:private_key_path]` key definition
+    fn from_str(definition: &str) -> Result<SigningKeyConfig, String> {
+        let parts: Vec<&str> = definition.split(':').map(|part| part.trim()).collect();
+
+        if parts.len() < 3 || parts.len() > 4 || parts.iter().any(|part| part.is_empty()) {
+            return Err(format!("Invalid key definition: {}", definition));
+        }
+
+        let algorithm = Algorithm::from_str(parts[1])
+            .map_err(|_| format!("Invalid algorithm: {}", parts[1]))?;
+
+        Ok(SigningKeyConfig {
+            kid: parts[0].to_string(),
+            algorithm,
+            public_key_path: parts[2].to_string(),
+            private_key_path: parts.get(3).map(|path| path.to_string())
+        })
+    }
 }
 
 impl AuthConfig {
@@ -72,3 +135,64 @@ pub struct BucketConfig {
     pub secret_key: String,
     pub payroll_base_bucket_name: String,
 }
+
+pub struct MailConfig {
+    pub from: String,
+    pub transport: MailTransportConfig,
+    /// URL sent in password reset emails. The `{token}` placeholder is replaced with the reset token.
+    pub password_reset_url: String,
+    /// URL sent in invitation emails. The `{token}` placeholder is replaced with the invitation token.
+    pub invitation_url: String
+}
+
+pub enum MailTransportConfig {
+    Smtp(SmtpConfig),
+    /// Writes every email as an .eml file in the given directory instead of sending it
+    Outbox(String)
+}
+
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub tls: SmtpTls
+}
+
+pub enum SmtpTls {
+    None,
+    StartTls,
+    Tls
+}
+
+impl FromStr for SmtpTls {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<SmtpTls, String> {
+        match value {
+            "none" => Ok(SmtpTls::None),
+            "starttls" => Ok(SmtpTls::StartTls),
+            "tls" => Ok(SmtpTls::Tls),
+            _ => Err(format!("Invalid smtp tls mode: {}", value))
+        }
+    }
+}
+
+pub struct OidcConfig {
+    /// The discovery document is read from `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    /// Not needed by public clients, which only rely on PKCE
+    pub client_secret: Option<String>,
+    /// Page that receives the authorization code and posts it to the callback route
+    pub redirect_url: String,
+    pub scopes: String,
+    /// Users signing in for the first time are created when set, otherwise they must already exist
+    pub provisioning: Option<OidcProvisioningConfig>
+}
+
+pub struct OidcProvisioningConfig {
+    pub company_id: i64,
+    /// Name of the role given to provisioned users
+    pub role: String
+}
@@ -0,0 +1,104 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+
+use crate::{entities::permission::permission::Permission, error::error::{AppError, AppErrorType}};
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateApiKeyDb",
+    fields(company_id, created_by, name, key_prefix, key_hash, user, payroll, company, created_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveApiKeyDb",
+    fields(id, company_id, created_by, name, key_prefix, user, payroll, company, created_at, expires_at, last_used_at, revoked_at)
+))]
+#[custom_model(model(
+    name = "RetrieveApiKeyDto",
+    fields(id, company_id, created_by, name, key_prefix, user, payroll, company, created_at, expires_at, last_used_at, revoked_at),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct ApiKey {
+    id: i64,
+    company_id: i64,
+    created_by: i64,
+    name: String,
+    key_prefix: String,
+    key_hash: String,
+    user: i16,
+    payroll: i16,
+    company: i16,
+    created_at: String,
+    expires_at: String,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>
+}
+
+impl ApiKey {
+    /// Prefix of every raw key, so leaked keys are easy to spot
+    pub const KEY_PREFIX: &'static str = "pmk_";
+    /// Characters of the raw key kept in clear, to tell keys apart
+    pub const VISIBLE_PREFIX_LENGTH: usize = 12;
+    pub const MAX_EXPIRATION_DAYS: i64 = 365;
+
+    pub fn check_name(name: &str) -> Result<(), AppError> {
+        if name.is_empty() || name.len() > 50 {
+            return Err(AppError::new(
+                String::from("The name must be between 1 and 50 characters long"),
+                AppErrorType::BadRequest,
+                None
+            ))
+        }
+
+        Ok(())
+    }
+
+    pub fn check_expires_in_days(expires_in_days: i64) -> Result<(), AppError> {
+        if !(1..=Self::MAX_EXPIRATION_DAYS).contains(&expires_in_days) {
+            return Err(AppError::new(
+                String::from("API keys must expire in between 1 and $1 days"),
+                AppErrorType::BadRequest,
+                Some(vec![Self::MAX_EXPIRATION_DAYS.to_string()])
+            ))
+        }
+
+        Ok(())
+    }
+
+    /// API keys act on behalf of a company, so they cannot hold permissions over other companies or over resources
+    /// owned by a user
+    pub fn check_permission(permission: &Permission) -> Result<(), AppError> {
+        if !permission.is_company_scoped() {
+            return Err(AppError::new(
+                String::from("API keys can only be granted permissions over their company"),
+                AppErrorType::BadRequest,
+                None
+            ))
+        }
+
+        Ok(())
+    }
+}
+
+impl RetrieveApiKeyDb {
+    pub fn to_retrieve_api_key_dto(self) -> RetrieveApiKeyDto {
+        RetrieveApiKeyDto {
+            id: self.id,
+            company_id: self.company_id,
+            created_by: self.created_by,
+            name: self.name,
+            key_prefix: self.key_prefix,
+            user: self.user,
+            payroll: self.payroll,
+            company: self.company,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_used_at: self.last_used_at,
+            revoked_at: self.revoked_at
+        }
+    }
+
+    pub fn permission(&self) -> Permission {
+        Permission::new(self.created_by, self.user, self.payroll, self.company)
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::{web, Responder};
+
+use crate::{service, util::json_response::json_response};
+
+use super::{custom_dto::api_key_dto::CreateApiKeyDto, api_key_policy::ManageApiKeysClaims};
+
+/// Routes are nested in the `/companies` scope. API keys cannot be used to manage API keys.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/{company_id}/api-keys")
+            .route("", web::post().to(create_api_key))
+            .route("", web::get().to(get_api_keys))
+            .route("/{api_key_id}", web::delete().to(revoke_api_key))
+    );
+}
+
+pub async fn create_api_key(company_id: web::Path<i64>, api_key: web::Json<CreateApiKeyDto>, claims: ManageApiKeysClaims) -> impl Responder {
+    let ManageApiKeysClaims(claims) = claims;
+    let company_id = company_id.into_inner();
+
+    let created = service::get().api_key().create_api_key(&claims, company_id, api_key.into_inner()).await;
+
+    json_response(&created)
+}
+
+pub async fn get_api_keys(company_id: web::Path<i64>, _claims: ManageApiKeysClaims) -> impl Responder {
+    let company_id = company_id.into_inner();
+
+    let api_keys = service::get().api_key().get_api_keys(company_id).await;
+
+    json_response(&api_keys)
+}
+
+pub async fn revoke_api_key(path: web::Path<(i64, i64)>, _claims: ManageApiKeysClaims) -> impl Responder {
+    let (company_id, api_key_id) = path.into_inner();
+
+    let revoked = service::get().api_key().revoke_api_key(company_id, api_key_id).await;
+
+    json_response(&revoked)
+}
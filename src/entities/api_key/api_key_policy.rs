@@ -0,0 +1,9 @@
+use crate::{auth::{jwt::TokenScope::Full, policy::{path_id, Action, Resource}}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, SelfCompany}}};
+
+authorized_claims!(
+    /// API keys belong to their company, so managing them requires updating it. Grants do not allow it, and neither do
+    /// API keys. The permissions of a key are checked against the ones of the actor when it is created.
+    ManageApiKeysClaims,
+    action: Action::new(Operation::Update).scopes(&[Any, SelfCompany]).tokens(&[Full]).members_only(),
+    |req| path_id(req, "company_id").map(Resource::Company)
+);
@@ -0,0 +1,183 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::api_key::{CreateApiKeyDb, RetrieveApiKeyDb};
+
+pub struct ApiKeyRepository {}
+
+impl ApiKeyRepository {
+    pub fn new() -> ApiKeyRepository {
+        ApiKeyRepository {
+
+        }
+    }
+
+    pub async fn create_api_key(&self, tx: &mut SqliteConnection, api_key: &CreateApiKeyDb) -> Result<RetrieveApiKeyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveApiKeyDb,
+            r#"
+            INSERT INTO ApiKey (company_id, created_by, name, key_prefix, key_hash, user, payroll, company, created_at, expires_at)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING
+                id as "id!: i64",
+                company_id,
+                created_by,
+                name,
+                key_prefix,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                last_used_at,
+                revoked_at
+            "#,
+            api_key.company_id,
+            api_key.created_by,
+            api_key.name,
+            api_key.key_prefix,
+            api_key.key_hash,
+            api_key.user,
+            api_key.payroll,
+            api_key.company,
+            api_key.created_at,
+            api_key.expires_at
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_api_keys_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<Vec<RetrieveApiKeyDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveApiKeyDb,
+            r#"
+            SELECT
+                id as "id!: i64",
+                company_id,
+                created_by,
+                name,
+                key_prefix,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                last_used_at,
+                revoked_at
+            FROM ApiKey
+            WHERE company_id = $1
+            ORDER BY id
+            "#,
+            company_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_api_key_by_id(&self, tx: &mut SqliteConnection, id: i64) -> Result<Option<RetrieveApiKeyDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveApiKeyDb,
+            r#"
+            SELECT
+                id as "id!: i64",
+                company_id,
+                created_by,
+                name,
+                key_prefix,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                last_used_at,
+                revoked_at
+            FROM ApiKey
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_active_api_key_by_hash(&self, tx: &mut SqliteConnection, key_hash: &str, now: &str) -> Result<Option<RetrieveApiKeyDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveApiKeyDb,
+            r#"
+            SELECT
+                id as "id!: i64",
+                company_id,
+                created_by,
+                name,
+                key_prefix,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                last_used_at,
+                revoked_at
+            FROM ApiKey
+            WHERE key_hash = $1 AND revoked_at IS NULL AND expires_at > $2
+                AND EXISTS (SELECT 1 FROM AppUser WHERE AppUser.id = ApiKey.created_by AND AppUser.status = 'active')
+            "#,
+            key_hash,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_last_used(&self, tx: &mut SqliteConnection, id: i64, now: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE ApiKey
+            SET last_used_at = $1
+            WHERE id = $2
+            "#,
+            now,
+            id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the key does not belong to the company or was already revoked.
+    pub async fn revoke_api_key(&self, tx: &mut SqliteConnection, id: i64, company_id: i64, now: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE ApiKey
+            SET revoked_at = $1
+            WHERE id = $2 AND company_id = $3 AND revoked_at IS NULL
+            "#,
+            now,
+            id,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_api_keys_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM ApiKey
+            WHERE company_id = $1
+            "#,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
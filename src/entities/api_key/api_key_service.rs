@@ -0,0 +1,145 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{auth::{jwt::{ApiKeyClaims, Claims, TokenScope}, policy::permission_denied}, entities::permission::permission::Permission, error::error::{AppError, AppErrorType}, service, util::crypto::{generate_secret_token, hash_secret_token}};
+
+use super::{api_key::{ApiKey, CreateApiKeyDb, RetrieveApiKeyDto}, api_key_repository::ApiKeyRepository, custom_dto::api_key_dto::{CreateApiKeyDto, CreatedApiKeyDto}};
+
+pub struct ApiKeyService {
+    db_pool: SqlitePool,
+    api_key_repository: ApiKeyRepository
+}
+
+impl ApiKeyService {
+    pub fn new(db_pool: SqlitePool, api_key_repository: ApiKeyRepository) -> ApiKeyService {
+        ApiKeyService {
+            db_pool,
+            api_key_repository
+        }
+    }
+
+    /// Creates a key for the company and returns it along with the raw key, which is not stored. Creating a key
+    /// requires holding every permission granted to it.
+    #[executor]
+    pub async fn create_api_key(&self, actor: &Claims, company_id: i64, api_key: CreateApiKeyDto) -> Result<CreatedApiKeyDto, AppError> {
+        let created_by = actor.sub;
+        let permission = Self::requested_permission(created_by, &api_key);
+
+        ApiKey::check_name(&api_key.name)?;
+        ApiKey::check_expires_in_days(api_key.expires_in_days)?;
+        ApiKey::check_permission(&permission)?;
+
+        if !service::get().permission().can_grant_executor(tx, actor, company_id, &permission).await? {
+            return Err(permission_denied());
+        }
+
+        if !service::get().company().company_exists_by_id_executor(tx, company_id).await? {
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![company_id.to_string()])
+            ))
+        }
+
+        let key = format!("{}{}", ApiKey::KEY_PREFIX, generate_secret_token());
+
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::days(api_key.expires_in_days))
+            .expect("valid timestamp");
+
+        let created = self.api_key_repository.create_api_key(tx, &CreateApiKeyDb {
+            company_id,
+            created_by,
+            name: api_key.name,
+            key_prefix: key[..ApiKey::VISIBLE_PREFIX_LENGTH].to_string(),
+            key_hash: hash_secret_token(&key),
+            user: api_key.user,
+            payroll: api_key.payroll,
+            company: api_key.company,
+            created_at: now.naive_utc().to_string(),
+            expires_at: expires_at.naive_utc().to_string()
+        }).await?;
+
+        Ok(CreatedApiKeyDto {
+            key,
+            api_key: created.to_retrieve_api_key_dto()
+        })
+    }
+
+    #[executor]
+    pub async fn get_api_keys(&self, company_id: i64) -> Result<Vec<RetrieveApiKeyDto>, AppError> {
+        let api_keys = self.api_key_repository.get_api_keys_by_company_id(tx, company_id).await?;
+
+        Ok(api_keys.into_iter().map(|api_key| api_key.to_retrieve_api_key_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn revoke_api_key(&self, company_id: i64, api_key_id: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        if !self.api_key_repository.revoke_api_key(tx, api_key_id, company_id, &now).await? {
+            return Err(AppError::new(
+                String::from(r#"Active API key with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![api_key_id.to_string()])
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Removes every key of the company, revoked or not. Only used when the company is deleted.
+    #[executor]
+    pub async fn delete_company_api_keys(&self, company_id: i64) -> Result<(), AppError> {
+        self.api_key_repository.delete_api_keys_by_company_id(tx, company_id).await
+    }
+
+    /// Returns the claims of the key if it is valid, and records its usage.
+    ///
+    /// `sub` is the creator of the key, only to record who acts. Permission checks use the permissions and company of
+    /// the key, and never treat the key as its creator.
+    /// API keys have no session, so `sid` is always 0.
+    #[executor]
+    pub async fn authenticate(&self, key: &str) -> Result<Option<Claims>, AppError> {
+        let now = chrono::Utc::now().naive_utc();
+
+        let api_key = match self.api_key_repository.get_active_api_key_by_hash(tx, &hash_secret_token(key), &now.to_string()).await? {
+            Some(api_key) => api_key,
+            None => return Ok(None)
+        };
+
+        self.api_key_repository.update_last_used(tx, api_key.id, &now.to_string()).await?;
+
+        let expires_at = chrono::NaiveDateTime::parse_from_str(&api_key.expires_at, "%Y-%m-%d %H:%M:%S%.f")
+            .map_err(AppError::internal_from_generic)?;
+
+        Ok(Some(Claims {
+            sub: api_key.created_by,
+            sid: 0,
+            scope: TokenScope::ApiKey,
+            exp: expires_at.and_utc().timestamp() as usize,
+            act: None,
+            api_key: Some(ApiKeyClaims {
+                id: api_key.id,
+                company_id: api_key.company_id
+            })
+        }))
+    }
+
+    #[executor]
+    pub async fn get_api_key_permission(&self, api_key_id: i64) -> Result<Permission, AppError> {
+        match self.api_key_repository.get_api_key_by_id(tx, api_key_id).await? {
+            Some(api_key) => Ok(api_key.permission()),
+            None => Err(AppError::new(
+                String::from("API key has not permission"),
+                AppErrorType::Forbidden,
+                None
+            ))
+        }
+    }
+
+    pub fn requested_permission(created_by: i64, api_key: &CreateApiKeyDto) -> Permission {
+        Permission::new(created_by, api_key.user, api_key.payroll, api_key.company)
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::api_key::api_key::RetrieveApiKeyDto;
+
+/// Permissions use the `Permission` bitmasks, and may only include `SelfCompany` scopes
+#[derive(Deserialize)]
+pub struct CreateApiKeyDto {
+    pub name: String,
+    pub user: i16,
+    pub payroll: i16,
+    pub company: i16,
+    pub expires_in_days: i64
+}
+
+/// The raw key is only returned once, when the key is created
+#[derive(Serialize)]
+pub struct CreatedApiKeyDto {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: RetrieveApiKeyDto
+}
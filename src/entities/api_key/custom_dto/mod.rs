@@ -0,0 +1 @@
+pub mod api_key_dto;
\ No newline at end of file
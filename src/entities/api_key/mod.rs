@@ -0,0 +1,6 @@
+pub mod api_key;
+pub mod api_key_service;
+pub mod api_key_repository;
+pub mod api_key_controller;
+pub mod api_key_policy;
+pub mod custom_dto;
\ No newline at end of file
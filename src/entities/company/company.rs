@@ -1,12 +1,14 @@
 use macros::DeriveCustomModel;
 use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
 
-use crate::error::error::{AppError, AppErrorType};
+use crate::{entities::user::user::User, error::error::{AppError, AppErrorType}, util::tax_id::{is_valid_spanish_tax_id, normalize_tax_id}};
 
 #[derive(DeriveCustomModel)]
 #[custom_model(model(
     name = "RetrieveCompanyDb",
-    fields(id, name)
+    fields(id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone),
+    extra_derives(FromRow)
 ))]
 #[custom_model(model(
     name = "CreateCompanyDb",
@@ -14,7 +16,7 @@ use crate::error::error::{AppError, AppErrorType};
 ))]
 #[custom_model(model(
     name = "RetrieveCompanyDto",
-    fields(id, name),
+    fields(id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_content_type, locale, time_zone),
     extra_derives(Serialize)
 ))]
 #[custom_model(model(
@@ -22,19 +24,53 @@ use crate::error::error::{AppError, AppErrorType};
     fields(name),
     extra_derives(Deserialize)
 ))]
+#[custom_model(model(
+    name = "UpdateCompanyProfileDb",
+    fields(legal_name, tax_id, address, postal_code, city, province, country, contact_email)
+))]
+#[custom_model(model(
+    name = "UpdateCompanyProfileDto",
+    fields(legal_name, tax_id, address, postal_code, city, province, country, contact_email),
+    extra_derives(Deserialize)
+))]
+#[custom_model(model(
+    name = "UpdateCompanySettingsDb",
+    fields(locale, time_zone)
+))]
+#[custom_model(model(
+    name = "UpdateCompanySettingsDto",
+    fields(locale, time_zone),
+    extra_derives(Deserialize)
+))]
 #[allow(dead_code)]
 pub struct Company {
     id: i64,
-    name: String
+    name: String,
+    /// Archived companies are hidden from listings and cannot get new users
+    archived_at: Option<String>,
+    legal_name: Option<String>,
+    /// Spanish CIF or NIF
+    tax_id: Option<String>,
+    address: Option<String>,
+    postal_code: Option<String>,
+    city: Option<String>,
+    province: Option<String>,
+    /// ISO 3166-1 alpha-2 code
+    country: Option<String>,
+    contact_email: Option<String>,
+    logo_object_key: Option<String>,
+    /// Only set when the company has a logo
+    logo_content_type: Option<String>,
+    /// Default locale of the users of the company, e.g. `es-ES`
+    locale: String,
+    /// IANA time zone, e.g. `Europe/Madrid`
+    time_zone: String
 }
 
 impl Company {
-    pub fn new(id: i64, name: String) -> Company {
-        Company {
-            id,
-            name
-        }
-    }
+    /// Logos are stored in the payroll bucket under this prefix
+    pub const LOGO_OBJECT_PREFIX: &str = "company-logos";
+    pub const MAX_LOGO_SIZE: i64 = 1024 * 1024;
 
     pub fn check_name(name: &str) -> Result<(), AppError> {
         if name.len() == 0 || name.len() > 50 {
@@ -47,13 +83,114 @@ impl Company {
 
         Ok(())
     }
+
+    pub fn check_tax_id(tax_id: &Option<String>) -> Result<(), AppError> {
+        if let Some(tax_id) = tax_id {
+            if !is_valid_spanish_tax_id(tax_id) {
+                return Err(AppError::new(
+                    String::from("Invalid tax id: $1. It must be a valid CIF or NIF"),
+                    AppErrorType::BadRequest,
+                    Some(vec![tax_id.to_string()])
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the length of an optional text field of the profile
+    pub fn check_profile_field(field: &str, value: &Option<String>, max_length: usize) -> Result<(), AppError> {
+        if let Some(value) = value {
+            if value.len() == 0 || value.len() > max_length {
+                return Err(AppError::new(
+                    String::from("The $1 must be between 1 and $2 characters long"),
+                    AppErrorType::BadRequest,
+                    Some(vec![field.to_string(), max_length.to_string()])
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// ISO 3166-1 alpha-2 code, e.g. `ES`
+    pub fn check_country(country: &Option<String>) -> Result<(), AppError> {
+        if let Some(country) = country {
+            if country.len() != 2 || !country.bytes().all(|b| b.is_ascii_uppercase()) {
+                return Err(AppError::new(
+                    String::from("Invalid country: $1. It must be an ISO 3166-1 alpha-2 code"),
+                    AppErrorType::BadRequest,
+                    Some(vec![country.to_string()])
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Language code with an optional region, e.g. `es` or `es-ES`
+    pub fn check_locale(locale: &str) -> Result<(), AppError> {
+        let regex = regex::Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap();
+        if !regex.is_match(locale) {
+            return Err(AppError::new(
+                String::from("Invalid locale: $1"),
+                AppErrorType::BadRequest,
+                Some(vec![locale.to_string()])
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Only the format of the name is checked, e.g. `UTC` or `Europe/Madrid`
+    pub fn check_time_zone(time_zone: &str) -> Result<(), AppError> {
+        let regex = regex::Regex::new(r"^[A-Za-z][A-Za-z0-9_+-]*(/[A-Za-z0-9_+-]+){0,2}$").unwrap();
+        if time_zone.len() > 64 || !regex.is_match(time_zone) {
+            return Err(AppError::new(
+                String::from("Invalid time zone: $1"),
+                AppErrorType::BadRequest,
+                Some(vec![time_zone.to_string()])
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Only PNG and JPEG logos are accepted. The type is taken from the content, not from the file name.
+    pub fn logo_content_type(header: &[u8]) -> Result<&'static str, AppError> {
+        if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Ok("image/png");
+        }
+
+        if header.starts_with(b"\xff\xd8\xff") {
+            return Ok("image/jpeg");
+        }
+
+        Err(AppError::new(
+            String::from("The logo must be a PNG or JPEG image"),
+            AppErrorType::BadRequest,
+            None
+        ))
+    }
 }
 
 impl RetrieveCompanyDb {
     pub fn to_retrieve_company_dto(self) -> Result<RetrieveCompanyDto, AppError> {
         Ok(RetrieveCompanyDto {
             id: self.id,
-            name: self.name
+            name: self.name,
+            archived_at: self.archived_at,
+            legal_name: self.legal_name,
+            tax_id: self.tax_id,
+            address: self.address,
+            postal_code: self.postal_code,
+            city: self.city,
+            province: self.province,
+            country: self.country,
+            contact_email: self.contact_email,
+            logo_content_type: self.logo_content_type,
+            locale: self.locale,
+            time_zone: self.time_zone
         })
     }
 }
@@ -66,4 +203,51 @@ impl CreateCompanyDb {
             name: company.name
         })
     }
+}
+
+impl UpdateCompanyProfileDb {
+    /// Missing fields are cleared. Blank values are treated as missing.
+    pub fn from_update_company_profile_dto(profile: UpdateCompanyProfileDto) -> Result<UpdateCompanyProfileDb, AppError> {
+        let trimmed = |value: Option<String>| value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        let tax_id = trimmed(profile.tax_id).map(|tax_id| normalize_tax_id(&tax_id));
+        let contact_email = trimmed(profile.contact_email).map(|email| email.to_lowercase());
+        let country = trimmed(profile.country).map(|country| country.to_uppercase());
+
+        let profile = UpdateCompanyProfileDb {
+            legal_name: trimmed(profile.legal_name),
+            tax_id,
+            address: trimmed(profile.address),
+            postal_code: trimmed(profile.postal_code),
+            city: trimmed(profile.city),
+            province: trimmed(profile.province),
+            country,
+            contact_email
+        };
+
+        Company::check_profile_field("legal name", &profile.legal_name, 100)?;
+        Company::check_tax_id(&profile.tax_id)?;
+        Company::check_profile_field("address", &profile.address, 100)?;
+        Company::check_profile_field("postal code", &profile.postal_code, 10)?;
+        Company::check_profile_field("city", &profile.city, 50)?;
+        Company::check_profile_field("province", &profile.province, 50)?;
+        Company::check_country(&profile.country)?;
+        User::check_email(&profile.contact_email)?;
+
+        Ok(profile)
+    }
+}
+
+impl UpdateCompanySettingsDb {
+    pub fn from_update_company_settings_dto(settings: UpdateCompanySettingsDto) -> Result<UpdateCompanySettingsDb, AppError> {
+        Company::check_locale(&settings.locale)?;
+        Company::check_time_zone(&settings.time_zone)?;
+
+        Ok(UpdateCompanySettingsDb {
+            locale: settings.locale,
+            time_zone: settings.time_zone
+        })
+    }
 }
\ No newline at end of file
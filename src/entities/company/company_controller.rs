@@ -1,29 +1,114 @@
-use actix_web::{web, Responder};
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
 
-use crate::{auth::jwt::Claims, check_permission, service, util::json_response::json_response};
+use crate::{entities::{api_key::api_key_controller, company_grant::company_grant_controller, department::department_controller, invitation::invitation_controller}, service, util::{json_response::json_response, multipart::extract_file}};
 
-use super::{company::CreateCompanyDto, custom_models::company_filter::CompanyFilterDto};
+use super::{company::{CreateCompanyDto, UpdateCompanyProfileDto, UpdateCompanySettingsDto}, company_policy::{CreateCompanyClaims, DeleteCompanyClaims, ListCompaniesClaims, ReadCompanyClaims, UpdateCompanyClaims}, custom_dto::company_dto::UpdateCompanyDto, custom_models::company_filter::CompanyFilterDto};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/companies")
             .route("", web::post().to(create_company))
             .route("", web::get().to(get_companies))
+            .route("/{company_id}", web::get().to(get_company))
+            .route("/{company_id}", web::patch().to(update_company))
+            .route("/{company_id}", web::delete().to(delete_company))
+            .route("/{company_id}/profile", web::put().to(update_company_profile))
+            .route("/{company_id}/settings", web::put().to(update_company_settings))
+            .route("/{company_id}/logo", web::put().to(upload_logo))
+            .route("/{company_id}/logo", web::get().to(download_logo))
+            .route("/{company_id}/logo", web::delete().to(delete_logo))
+            .route("/{company_id}/archive", web::post().to(archive_company))
+            .route("/{company_id}/unarchive", web::post().to(unarchive_company))
+            .configure(api_key_controller::config)
+            .configure(invitation_controller::config)
+            .configure(company_grant_controller::config)
+            .configure(department_controller::config)
     );
 }
 
-pub async fn create_company(company: web::Json<CreateCompanyDto>, claims: Claims) -> impl Responder {
-    check_permission!(service::get().permission().create_company(claims.sub).await);
-
+pub async fn create_company(company: web::Json<CreateCompanyDto>, _claims: CreateCompanyClaims) -> impl Responder {
     let company = service::get().company().create_company(company.into_inner()).await;
 
     json_response(&company)
 }
 
-pub async fn get_companies(filters: web::Query<CompanyFilterDto>, claims: Claims) -> impl Responder {
-    check_permission!(service::get().permission().retrieve_companies(claims.sub).await);
-
+pub async fn get_companies(filters: web::Query<CompanyFilterDto>, _claims: ListCompaniesClaims) -> impl Responder {
     let companies = service::get().company().get_companies(filters.into_inner()).await;
 
     json_response(&companies)
 }
+
+pub async fn get_company(company_id: web::Path<i64>, _claims: ReadCompanyClaims) -> impl Responder {
+    let company = service::get().company().get_company(company_id.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn update_company(company_id: web::Path<i64>, update: web::Json<UpdateCompanyDto>, _claims: UpdateCompanyClaims) -> impl Responder {
+    let company = service::get().company().update_company(company_id.into_inner(), update.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn update_company_profile(company_id: web::Path<i64>, profile: web::Json<UpdateCompanyProfileDto>, _claims: UpdateCompanyClaims) -> impl Responder {
+    let company = service::get().company().update_company_profile(company_id.into_inner(), profile.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn update_company_settings(company_id: web::Path<i64>, settings: web::Json<UpdateCompanySettingsDto>, _claims: UpdateCompanyClaims) -> impl Responder {
+    let company = service::get().company().update_company_settings(company_id.into_inner(), settings.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn upload_logo(company_id: web::Path<i64>, mut payload: Multipart, _claims: UpdateCompanyClaims) -> impl Responder {
+    let file_info = match extract_file(&mut payload).await {
+        Ok(file_info) => file_info,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let company = service::get().company().upload_logo(company_id.into_inner(), &file_info.file_path, file_info.file_size).await;
+
+    json_response(&company)
+}
+
+pub async fn download_logo(company_id: web::Path<i64>, _claims: ReadCompanyClaims) -> impl Responder {
+    let logo = match service::get().company().download_logo(company_id.into_inner()).await {
+        Ok(logo) => logo,
+        Err(err) => return err.error_response()
+    };
+
+    let mut builder = HttpResponse::Ok();
+
+    builder
+        .content_type(logo.content_type)
+        .append_header(("Content-Length", logo.file_size.to_string()));
+
+    builder.streaming(logo.stream)
+}
+
+pub async fn delete_logo(company_id: web::Path<i64>, _claims: UpdateCompanyClaims) -> impl Responder {
+    let company = service::get().company().delete_logo(company_id.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn archive_company(company_id: web::Path<i64>, _claims: DeleteCompanyClaims) -> impl Responder {
+    let company = service::get().company().archive_company(company_id.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn unarchive_company(company_id: web::Path<i64>, _claims: DeleteCompanyClaims) -> impl Responder {
+    let company = service::get().company().unarchive_company(company_id.into_inner()).await;
+
+    json_response(&company)
+}
+
+pub async fn delete_company(company_id: web::Path<i64>, _claims: DeleteCompanyClaims) -> impl Responder {
+    let deleted = service::get().company().delete_company(company_id.into_inner()).await;
+
+    json_response(&deleted)
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use crate::{auth::policy::{path_id, Policy, Resource}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, SelfCompany}}};
+
+/// Only users that can act on any company may create companies
+pub const COMPANY_POLICY: Policy = Policy {
+    create: &[Any],
+    read: &[Any, SelfCompany],
+    update: &[Any, SelfCompany],
+    delete: &[Any, SelfCompany]
+};
+
+authorized_claims!(
+    CreateCompanyClaims, Operation::Create, |_req| Some(Resource::Companies)
+);
+
+authorized_claims!(
+    ListCompaniesClaims, Operation::Read, |_req| Some(Resource::Companies)
+);
+
+authorized_claims!(
+    ReadCompanyClaims, Operation::Read, |req| path_id(req, "company_id").map(Resource::Company)
+);
+
+authorized_claims!(
+    UpdateCompanyClaims, Operation::Update, |req| path_id(req, "company_id").map(Resource::Company)
+);
+
+authorized_claims!(
+    /// Archiving is a soft deletion, so it needs the same permission as deleting
+    DeleteCompanyClaims, Operation::Delete, |req| path_id(req, "company_id").map(Resource::Company)
+);
@@ -1,6 +1,8 @@
+use sqlx::QueryBuilder;
+
 use crate::{error::error::AppError, util::db::to_app_error};
 
-use super::{company::{CreateCompanyDb, RetrieveCompanyDb}, custom_models::company_filter::CompanyFilterDb};
+use super::{company::{CreateCompanyDb, RetrieveCompanyDb, UpdateCompanyProfileDb, UpdateCompanySettingsDb}, custom_models::company_filter::CompanyFilterDb};
 
 pub struct CompanyRepository {
 
@@ -19,7 +21,7 @@ impl CompanyRepository {
             r#"
             INSERT INTO Company (name)
             VALUES ($1)
-            RETURNING id, name 
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
             "#,
             company.name
         )
@@ -44,20 +46,177 @@ impl CompanyRepository {
         .map_err(to_app_error)
     }
 
-    pub async fn get_companies(&self, tx: &mut sqlx::SqliteConnection, filters: CompanyFilterDb) -> Result<Vec<RetrieveCompanyDb>, AppError> {
+    pub async fn get_companies(&self, tx: &mut sqlx::SqliteConnection, filter: &CompanyFilterDb) -> Result<Vec<RetrieveCompanyDb>, AppError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            FROM Company
+            "#
+        );
+
+        filter.fill_query(&mut query);
+
+        query.build_query_as()
+            .fetch_all(tx)
+            .await
+            .map_err(to_app_error)
+    }
+
+    pub async fn count_companies(&self, tx: &mut sqlx::SqliteConnection, filter: &CompanyFilterDb) -> Result<i64, AppError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT COUNT(*)
+            FROM Company
+            "#
+        );
+
+        filter.fill_conditions(&mut query);
+
+        query.build_query_scalar()
+            .fetch_one(tx)
+            .await
+            .map_err(to_app_error)
+    }
+
+    pub async fn get_company_by_id(&self, tx: &mut sqlx::SqliteConnection, company_id: i64) -> Result<Option<RetrieveCompanyDb>, AppError> {
         sqlx::query_as!(
             RetrieveCompanyDb,
             r#"
-            SELECT id, name
+            SELECT id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
             FROM Company
-            LIMIT $1
-            OFFSET $2
+            WHERE id = $1
+            LIMIT 1
             "#,
-            filters.limit,
-            filters.offset
+            company_id
         )
-        .fetch_all(tx)
+        .fetch_optional(tx)
         .await
         .map_err(to_app_error)
     }
-}
+
+    pub async fn update_company(&self, tx: &mut sqlx::SqliteConnection, company_id: i64, name: &str) -> Result<RetrieveCompanyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyDb,
+            r#"
+            UPDATE Company
+            SET name = $1
+            WHERE id = $2
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            "#,
+            name,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_archived_at(&self, tx: &mut sqlx::SqliteConnection, company_id: i64, archived_at: Option<&str>) -> Result<RetrieveCompanyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyDb,
+            r#"
+            UPDATE Company
+            SET archived_at = $1
+            WHERE id = $2
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            "#,
+            archived_at,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_company(&self, tx: &mut sqlx::SqliteConnection, company_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM Company
+            WHERE id = $1
+            "#,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_company_profile(&self, tx: &mut sqlx::SqliteConnection, company_id: i64, profile: &UpdateCompanyProfileDb) -> Result<RetrieveCompanyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyDb,
+            r#"
+            UPDATE Company
+            SET legal_name = $1, tax_id = $2, address = $3, postal_code = $4, city = $5, province = $6, country = $7, contact_email = $8
+            WHERE id = $9
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            "#,
+            profile.legal_name,
+            profile.tax_id,
+            profile.address,
+            profile.postal_code,
+            profile.city,
+            profile.province,
+            profile.country,
+            profile.contact_email,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_company_settings(&self, tx: &mut sqlx::SqliteConnection, company_id: i64, settings: &UpdateCompanySettingsDb) -> Result<RetrieveCompanyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyDb,
+            r#"
+            UPDATE Company
+            SET locale = $1, time_zone = $2
+            WHERE id = $3
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            "#,
+            settings.locale,
+            settings.time_zone,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_logo(&self, tx: &mut sqlx::SqliteConnection, company_id: i64, logo_object_key: Option<&str>, logo_content_type: Option<&str>) -> Result<RetrieveCompanyDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyDb,
+            r#"
+            UPDATE Company
+            SET logo_object_key = $1, logo_content_type = $2
+            WHERE id = $3
+            RETURNING id, name, archived_at, legal_name, tax_id, address, postal_code, city, province, country, contact_email, logo_object_key, logo_content_type, locale, time_zone
+            "#,
+            logo_object_key,
+            logo_content_type,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Whether another company already has the tax id
+    pub async fn tax_id_exists(&self, tx: &mut sqlx::SqliteConnection, tax_id: &str, company_id: i64) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM Company
+            WHERE tax_id = $1 AND id != $2
+            LIMIT 1
+            "#,
+            tax_id,
+            company_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|r| r.is_some())
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
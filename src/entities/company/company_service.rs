@@ -1,20 +1,25 @@
+use std::sync::Arc;
+
 use macros::executor;
 use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
 
-use crate::error::error::AppError;
+use crate::{config, error::error::{AppError, AppErrorType}, service, util::{file::remove_file, minio::MinioService, pagination::Page}};
 
-use super::{company::{CreateCompanyDb, CreateCompanyDto, RetrieveCompanyDto}, company_repository::CompanyRepository, custom_models::company_filter::{CompanyFilterDb, CompanyFilterDto}};
+use super::{company::{Company, CreateCompanyDb, CreateCompanyDto, RetrieveCompanyDb, RetrieveCompanyDto, UpdateCompanyProfileDb, UpdateCompanyProfileDto, UpdateCompanySettingsDb, UpdateCompanySettingsDto}, company_repository::CompanyRepository, custom_dto::company_dto::UpdateCompanyDto, custom_models::{company_filter::{CompanyFilterDb, CompanyFilterDto}, company_logo::CompanyLogoDto}};
 
 pub struct CompanyService {
     db_pool: SqlitePool,
-    company_repository: CompanyRepository
+    company_repository: CompanyRepository,
+    bucket_service: Arc<MinioService>
 }
 
 impl CompanyService {
-    pub fn new(db_pool: SqlitePool, company_repository: CompanyRepository) -> CompanyService {
+    pub fn new(db_pool: SqlitePool, company_repository: CompanyRepository, bucket_service: Arc<MinioService>) -> CompanyService {
         CompanyService {
             db_pool,
-            company_repository
+            company_repository,
+            bucket_service
         }
     }
 
@@ -31,14 +36,241 @@ impl CompanyService {
     }
 
     #[executor]
-    pub async fn get_companies(&self, filter: CompanyFilterDto) -> Result<Vec<RetrieveCompanyDto>, AppError> {
-        let companies = self.company_repository.get_companies(tx, CompanyFilterDb::from_company_filter_dto(filter)?).await?;
+    pub async fn get_companies(&self, filter: CompanyFilterDto) -> Result<Page<RetrieveCompanyDto>, AppError> {
+        let filter = CompanyFilterDb::from_company_filter_dto(filter)?;
+
+        let companies = self.company_repository.get_companies(tx, &filter).await?;
+        let total = self.company_repository.count_companies(tx, &filter).await?;
+
+        Page::new(companies, total, &filter.pagination).try_map(|company| company.to_retrieve_company_dto())
+    }
+
+    #[executor]
+    pub async fn get_company(&self, company_id: i64) -> Result<RetrieveCompanyDto, AppError> {
+        self.get_existing_company(tx, company_id).await?.to_retrieve_company_dto()
+    }
+
+    /// Same as `get_company`, but a missing company is not an error
+    #[executor]
+    pub async fn find_company(&self, company_id: i64) -> Result<Option<RetrieveCompanyDto>, AppError> {
+        self.company_repository.get_company_by_id(tx, company_id).await?
+            .map(|company| company.to_retrieve_company_dto())
+            .transpose()
+    }
+
+    #[executor]
+    pub async fn update_company(&self, company_id: i64, update: UpdateCompanyDto) -> Result<RetrieveCompanyDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        let name = update.name.unwrap_or(company.name);
+
+        Company::check_name(&name)?;
 
-        Ok(
-            companies
-                .into_iter()
-                .map(|company| company.to_retrieve_company_dto())
-                .collect::<Result<_, _>>()?
+        self.company_repository.update_company(tx, company_id, &name).await?.to_retrieve_company_dto()
+    }
+
+    /// Replaces the whole profile. Missing fields are cleared.
+    #[executor]
+    pub async fn update_company_profile(&self, company_id: i64, profile: UpdateCompanyProfileDto) -> Result<RetrieveCompanyDto, AppError> {
+        self.get_existing_company(tx, company_id).await?;
+
+        let profile = UpdateCompanyProfileDb::from_update_company_profile_dto(profile)?;
+
+        if let Some(tax_id) = &profile.tax_id {
+            if self.company_repository.tax_id_exists(tx, tax_id, company_id).await? {
+                return Err(AppError::new(
+                    String::from(r#"Company with tax id "$1" already exists"#),
+                    AppErrorType::Conflict,
+                    Some(vec![tax_id.to_string()])
+                ));
+            }
+        }
+
+        self.company_repository.update_company_profile(tx, company_id, &profile).await?.to_retrieve_company_dto()
+    }
+
+    #[executor]
+    pub async fn update_company_settings(&self, company_id: i64, settings: UpdateCompanySettingsDto) -> Result<RetrieveCompanyDto, AppError> {
+        self.get_existing_company(tx, company_id).await?;
+
+        let settings = UpdateCompanySettingsDb::from_update_company_settings_dto(settings)?;
+
+        self.company_repository.update_company_settings(tx, company_id, &settings).await?.to_retrieve_company_dto()
+    }
+
+    /// Uploads the logo from the temporary file and removes the file. The previous logo is removed from the bucket
+    /// once the new one is stored.
+    #[executor]
+    pub async fn upload_logo(&self, company_id: i64, file_path: &str, file_size: i64) -> Result<RetrieveCompanyDto, AppError> {
+        let result = self.do_upload_logo(tx, company_id, file_path, file_size).await;
+        remove_file(file_path).await?;
+        result
+    }
+
+    #[executor]
+    pub async fn download_logo(&self, company_id: i64) -> Result<CompanyLogoDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        let (object_key, content_type) = match (company.logo_object_key, company.logo_content_type) {
+            (Some(object_key), Some(content_type)) => (object_key, content_type),
+            _ => return Err(Self::logo_not_found(company_id))
+        };
+
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        let stream_info = self.bucket_service.get_file_stream(bucket_name, &object_key).await?;
+
+        Ok(CompanyLogoDto {
+            content_type,
+            file_size: stream_info.size,
+            stream: stream_info.stream
+        })
+    }
+
+    #[executor]
+    pub async fn delete_logo(&self, company_id: i64) -> Result<RetrieveCompanyDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        let object_key = match company.logo_object_key {
+            Some(object_key) => object_key,
+            None => return Err(Self::logo_not_found(company_id))
+        };
+
+        let updated = self.company_repository.update_logo(tx, company_id, None, None).await?;
+
+        // The logo is no longer in use, so failing to remove it is not an error of the deletion
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        self.bucket_service.remove_file(bucket_name, &object_key).await.ok();
+
+        updated.to_retrieve_company_dto()
+    }
+
+    /// Archived companies are kept with their users and payrolls, but they are hidden from listings and cannot get
+    /// new users until they are unarchived.
+    #[executor]
+    pub async fn archive_company(&self, company_id: i64) -> Result<RetrieveCompanyDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        if company.archived_at.is_some() {
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" is already archived"#),
+                AppErrorType::Conflict,
+                Some(vec![company_id.to_string()])
+            ));
+        }
+
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.company_repository.update_archived_at(tx, company_id, Some(&now)).await?.to_retrieve_company_dto()
+    }
+
+    #[executor]
+    pub async fn unarchive_company(&self, company_id: i64) -> Result<RetrieveCompanyDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        if company.archived_at.is_none() {
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" is not archived"#),
+                AppErrorType::Conflict,
+                Some(vec![company_id.to_string()])
+            ));
+        }
+
+        self.company_repository.update_archived_at(tx, company_id, None).await?.to_retrieve_company_dto()
+    }
+
+    /// Only companies without users can be deleted, so no payroll object in the bucket is left without its company.
+    /// Companies that still have users must be archived instead. The API keys and departments of the company and the
+    /// grants over it are deleted along with it.
+    pub async fn delete_company(&self, company_id: i64) -> Result<(), AppError> {
+        let logo_object_key = self.delete_company_records(company_id).await?;
+
+        // The company is already deleted, so failing to remove its logo is not an error of the deletion. Failures are
+        // already logged when the error is created
+        if let Some(object_key) = logo_object_key {
+            let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+            self.bucket_service.remove_file(bucket_name, &object_key).await.ok();
+        }
+
+        Ok(())
+    }
+
+    /// Object key of the logo of the deleted company
+    #[executor(transaction)]
+    async fn delete_company_records(&self, company_id: i64) -> Result<Option<String>, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        let users = service::get().user().count_users_by_company_id_executor(tx, company_id).await?;
+
+        if users > 0 {
+            let payrolls = service::get().payroll().count_payrolls_by_company_id_executor(tx, company_id).await?;
+
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" still has $2 users and $3 payrolls, archive it instead"#),
+                AppErrorType::Conflict,
+                Some(vec![company_id.to_string(), users.to_string(), payrolls.to_string()])
+            ));
+        }
+
+        service::get().api_key().delete_company_api_keys_executor(tx, company_id).await?;
+        service::get().company_grant().delete_company_grants_executor(tx, company_id).await?;
+        service::get().department().delete_company_departments_executor(tx, company_id).await?;
+
+        self.company_repository.delete_company(tx, company_id).await?;
+
+        Ok(company.logo_object_key)
+    }
+
+    async fn do_upload_logo(&self, tx: &mut SqliteConnection, company_id: i64, file_path: &str, file_size: i64) -> Result<RetrieveCompanyDto, AppError> {
+        let company = self.get_existing_company(tx, company_id).await?;
+
+        if file_size > Company::MAX_LOGO_SIZE {
+            return Err(AppError::new(
+                format!("The logo cannot exceed {} bytes", Company::MAX_LOGO_SIZE),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let content = tokio::fs::read(file_path).await.map_err(AppError::internal_from_generic)?;
+        let content_type = Company::logo_content_type(&content)?;
+
+        let object_key = format!("{}/{}/{}", Company::LOGO_OBJECT_PREFIX, company_id, Uuid::now_v7());
+
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        self.bucket_service.upload_file(bucket_name, file_path, &object_key).await?;
+
+        let updated = match self.company_repository.update_logo(tx, company_id, Some(&object_key), Some(content_type)).await {
+            Ok(company) => company,
+            Err(e) => {
+                self.bucket_service.remove_file(bucket_name, &object_key).await?;
+                return Err(e);
+            }
+        };
+
+        // The new logo is already in use, so failing to remove the previous one is not an error of the upload
+        if let Some(previous_object_key) = company.logo_object_key {
+            self.bucket_service.remove_file(bucket_name, &previous_object_key).await.ok();
+        }
+
+        updated.to_retrieve_company_dto()
+    }
+
+    fn logo_not_found(company_id: i64) -> AppError {
+        AppError::new(
+            String::from(r#"Company with id "$1" has no logo"#),
+            AppErrorType::NotFound,
+            Some(vec![company_id.to_string()])
         )
     }
+
+    async fn get_existing_company(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<RetrieveCompanyDb, AppError> {
+        match self.company_repository.get_company_by_id(tx, company_id).await? {
+            Some(company) => Ok(company),
+            None => Err(AppError::new(
+                String::from(r#"Company with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![company_id.to_string()])
+            ))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+/// Only the given fields are updated
+#[derive(Deserialize)]
+pub struct UpdateCompanyDto {
+    pub name: Option<String>
+}
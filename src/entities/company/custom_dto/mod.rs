@@ -0,0 +1 @@
+pub mod company_dto;
@@ -1,50 +1,72 @@
 use serde::Deserialize;
+use sqlx::{QueryBuilder, Sqlite};
 
-use crate::error::error::{AppError, AppErrorType};
+use crate::{error::error::AppError, util::{db::contains_pattern, pagination::{Pagination, SortOrder}}};
 
 pub struct CompanyFilterDb {
-    pub limit: i64,
-    pub offset: i64
+    pub search: Option<String>,
+    pub include_archived: bool,
+    pub sort: CompanySort,
+    pub order: SortOrder,
+    pub pagination: Pagination
 }
 
 impl CompanyFilterDb {
-    pub fn check_limit(limit: i64) -> Result<(), AppError> {
-        if limit < 1 || limit > 25 {
-            return Err(AppError::new(
-                String::from(r#"Limit must be between 1 and 25"#),
-                AppErrorType::BadRequest,
-                None
-            ));
-        }
+    pub const MAX_LIMIT: i64 = 25;
 
-        Ok(())
+    pub fn from_company_filter_dto(filter: CompanyFilterDto) -> Result<CompanyFilterDb, AppError> {
+        Ok(CompanyFilterDb {
+            search: filter.search.map(|search| search.trim().to_lowercase()).filter(|search| !search.is_empty()),
+            include_archived: filter.include_archived.unwrap_or(false),
+            sort: filter.sort.unwrap_or_default(),
+            order: filter.order.unwrap_or_default(),
+            pagination: Pagination::new(filter.limit, filter.offset, Self::MAX_LIMIT)?
+        })
     }
 
-    pub fn check_offset(offset: i64) -> Result<(), AppError> {
-        if offset < 0 {
-            return Err(AppError::new(
-                String::from(r#"Offset must be greater than or equal to 0"#),
-                AppErrorType::BadRequest,
-                None
-            ));
+    /// Conditions of the filter, shared by the listing and its count
+    pub fn fill_conditions(&self, query: &mut QueryBuilder<Sqlite>) {
+        query.push(" WHERE 1 = 1");
+
+        if !self.include_archived {
+            query.push(" AND archived_at IS NULL");
         }
 
-        Ok(())
+        if let Some(search) = &self.search {
+            query.push(" AND lower(name) LIKE ");
+            query.push_bind(contains_pattern(search));
+            query.push(" ESCAPE '\\'");
+        }
     }
 
-    pub fn from_company_filter_dto(filter: CompanyFilterDto) -> Result<CompanyFilterDb, AppError> {
-        Self::check_limit(filter.limit)?;
-        Self::check_offset(filter.offset)?;
-        
-        Ok(CompanyFilterDb {
-            limit: filter.limit,
-            offset: filter.offset
-        })
+    pub fn fill_query(&self, query: &mut QueryBuilder<Sqlite>) {
+        self.fill_conditions(query);
+
+        match self.sort {
+            CompanySort::Id => query.push(format!(" ORDER BY id {}", self.order.as_sql())),
+            CompanySort::Name => query.push(format!(" ORDER BY name COLLATE NOCASE {0}, id {0}", self.order.as_sql()))
+        };
+
+        self.pagination.fill_query(query);
     }
 }
 
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CompanySort {
+    #[default]
+    Id,
+    Name
+}
+
 #[derive(Deserialize)]
 pub struct CompanyFilterDto {
-    pub limit: i64,
-    pub offset: i64
+    /// Part of the name, case insensitive
+    pub search: Option<String>,
+    /// Archived companies are only listed when requested
+    pub include_archived: Option<bool>,
+    pub sort: Option<CompanySort>,
+    pub order: Option<SortOrder>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+use std::pin::Pin;
+
+use actix_web::web;
+use futures_util::Stream;
+
+pub struct CompanyLogoDto {
+    pub content_type: String,
+    pub file_size: i64,
+    pub stream: Pin<Box<dyn Stream<Item = Result<web::Bytes, std::io::Error>> + Send>>
+}
\ No newline at end of file
@@ -1 +1,2 @@
-pub mod company_filter;
\ No newline at end of file
+pub mod company_filter;
+pub mod company_logo;
\ No newline at end of file
@@ -2,4 +2,6 @@ pub mod company;
 pub mod company_service;
 pub mod company_repository;
 pub mod company_controller;
+pub mod company_policy;
+pub mod custom_dto;
 pub mod custom_models;
\ No newline at end of file
@@ -0,0 +1,78 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+
+use crate::{entities::permission::permission::Permission, error::error::{AppError, AppErrorType}};
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateCompanyGrantDb",
+    fields(user_id, company_id, granted_by, user, payroll, company, created_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveCompanyGrantDb",
+    fields(id, user_id, company_id, granted_by, user, payroll, company, created_at, expires_at, revoked_at)
+))]
+#[custom_model(model(
+    name = "RetrieveCompanyGrantDto",
+    fields(id, user_id, company_id, granted_by, user, payroll, company, created_at, expires_at, revoked_at),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct CompanyGrant {
+    id: i64,
+    user_id: i64,
+    company_id: i64,
+    granted_by: i64,
+    user: i16,
+    payroll: i16,
+    company: i16,
+    created_at: String,
+    expires_at: String,
+    revoked_at: Option<String>
+}
+
+impl CompanyGrant {
+    pub const MAX_EXPIRATION_DAYS: i64 = 365;
+
+    pub fn check_expires_in_days(expires_in_days: i64) -> Result<(), AppError> {
+        if !(1..=Self::MAX_EXPIRATION_DAYS).contains(&expires_in_days) {
+            return Err(AppError::new(
+                String::from("Grants must expire in between 1 and $1 days"),
+                AppErrorType::BadRequest,
+                Some(vec![Self::MAX_EXPIRATION_DAYS.to_string()])
+            ))
+        }
+
+        Ok(())
+    }
+
+    /// Grants give access to a single company, so they can only hold `SelfCompany` scopes
+    pub fn check_permission(permission: &Permission) -> Result<(), AppError> {
+        if !permission.is_company_scoped() {
+            return Err(AppError::new(
+                String::from("Grants can only give permissions over their company"),
+                AppErrorType::BadRequest,
+                None
+            ))
+        }
+
+        Ok(())
+    }
+}
+
+impl RetrieveCompanyGrantDb {
+    pub fn to_retrieve_company_grant_dto(self) -> RetrieveCompanyGrantDto {
+        RetrieveCompanyGrantDto {
+            id: self.id,
+            user_id: self.user_id,
+            company_id: self.company_id,
+            granted_by: self.granted_by,
+            user: self.user,
+            payroll: self.payroll,
+            company: self.company,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            revoked_at: self.revoked_at
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::{web, Responder};
+
+use crate::{service, util::json_response::json_response};
+
+use super::{custom_dto::company_grant_dto::CreateCompanyGrantDto, company_grant_policy::ManageCompanyGrantsClaims};
+
+/// Routes are nested in the `/companies` scope. API keys cannot be used to manage grants.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/{company_id}/grants")
+            .route("", web::post().to(create_company_grant))
+            .route("", web::get().to(get_company_grants))
+            .route("/{grant_id}", web::delete().to(revoke_company_grant))
+    );
+}
+
+pub async fn create_company_grant(company_id: web::Path<i64>, grant: web::Json<CreateCompanyGrantDto>, claims: ManageCompanyGrantsClaims) -> impl Responder {
+    let ManageCompanyGrantsClaims(claims) = claims;
+    let company_id = company_id.into_inner();
+
+    let created = service::get().company_grant().create_company_grant(&claims, company_id, grant.into_inner()).await;
+
+    json_response(&created)
+}
+
+pub async fn get_company_grants(company_id: web::Path<i64>, _claims: ManageCompanyGrantsClaims) -> impl Responder {
+    let company_id = company_id.into_inner();
+
+    let grants = service::get().company_grant().get_company_grants(company_id).await;
+
+    json_response(&grants)
+}
+
+pub async fn revoke_company_grant(path: web::Path<(i64, i64)>, _claims: ManageCompanyGrantsClaims) -> impl Responder {
+    let (company_id, grant_id) = path.into_inner();
+
+    let revoked = service::get().company_grant().revoke_company_grant(company_id, grant_id).await;
+
+    json_response(&revoked)
+}
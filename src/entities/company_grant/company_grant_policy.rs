@@ -0,0 +1,9 @@
+use crate::{auth::{jwt::TokenScope::Full, policy::{path_id, Action, Resource}}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, SelfCompany}}};
+
+authorized_claims!(
+    /// Only members of the company manage its grants, so a grant cannot be used to give access to other users. The
+    /// permissions of a grant are checked against the ones of the actor when it is created.
+    ManageCompanyGrantsClaims,
+    action: Action::new(Operation::Update).scopes(&[Any, SelfCompany]).tokens(&[Full]).members_only(),
+    |req| path_id(req, "company_id").map(Resource::Company)
+);
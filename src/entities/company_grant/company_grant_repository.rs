@@ -0,0 +1,127 @@
+use sqlx::SqliteConnection;
+
+use crate::{entities::permission::permission::Permission, error::error::AppError, util::db::to_app_error};
+
+use super::company_grant::{CreateCompanyGrantDb, RetrieveCompanyGrantDb};
+
+pub struct CompanyGrantRepository {}
+
+impl CompanyGrantRepository {
+    pub fn new() -> CompanyGrantRepository {
+        CompanyGrantRepository {
+
+        }
+    }
+
+    pub async fn create_company_grant(&self, tx: &mut SqliteConnection, grant: &CreateCompanyGrantDb) -> Result<RetrieveCompanyGrantDb, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyGrantDb,
+            r#"
+            INSERT INTO CompanyGrant (user_id, company_id, granted_by, user, payroll, company, created_at, expires_at)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING
+                id as "id!: i64",
+                user_id,
+                company_id,
+                granted_by,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                revoked_at
+            "#,
+            grant.user_id,
+            grant.company_id,
+            grant.granted_by,
+            grant.user,
+            grant.payroll,
+            grant.company,
+            grant.created_at,
+            grant.expires_at
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_company_grants_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<Vec<RetrieveCompanyGrantDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveCompanyGrantDb,
+            r#"
+            SELECT
+                id as "id!: i64",
+                user_id,
+                company_id,
+                granted_by,
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16",
+                created_at,
+                expires_at,
+                revoked_at
+            FROM CompanyGrant
+            WHERE company_id = $1
+            ORDER BY id
+            "#,
+            company_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Permissions of the grants of the user over the company that are neither revoked nor expired
+    pub async fn get_active_permissions(&self, tx: &mut SqliteConnection, user_id: i64, company_id: i64, now: &str) -> Result<Vec<Permission>, AppError> {
+        sqlx::query_as!(
+            Permission,
+            r#"
+            SELECT
+                user_id as "user_id: i64",
+                user as "user: i16",
+                payroll as "payroll: i16",
+                company as "company: i16"
+            FROM CompanyGrant
+            WHERE user_id = $1 AND company_id = $2 AND revoked_at IS NULL AND expires_at > $3
+            "#,
+            user_id,
+            company_id,
+            now
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the grant does not belong to the company or was already revoked.
+    pub async fn revoke_company_grant(&self, tx: &mut SqliteConnection, id: i64, company_id: i64, now: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE CompanyGrant
+            SET revoked_at = $1
+            WHERE id = $2 AND company_id = $3 AND revoked_at IS NULL
+            "#,
+            now,
+            id,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_company_grants_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM CompanyGrant
+            WHERE company_id = $1
+            "#,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
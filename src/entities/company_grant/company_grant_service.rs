@@ -0,0 +1,121 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{auth::{jwt::Claims, policy::permission_denied}, entities::permission::permission::Permission, error::error::{AppError, AppErrorType}, service};
+
+use super::{company_grant::{CompanyGrant, CreateCompanyGrantDb, RetrieveCompanyGrantDto}, company_grant_repository::CompanyGrantRepository, custom_dto::company_grant_dto::CreateCompanyGrantDto};
+
+pub struct CompanyGrantService {
+    db_pool: SqlitePool,
+    company_grant_repository: CompanyGrantRepository
+}
+
+impl CompanyGrantService {
+    pub fn new(db_pool: SqlitePool, company_grant_repository: CompanyGrantRepository) -> CompanyGrantService {
+        CompanyGrantService {
+            db_pool,
+            company_grant_repository
+        }
+    }
+
+    /// Grants a user of another company permissions over this one until the grant expires or is revoked. Creating a
+    /// grant requires holding every permission granted with it.
+    #[executor]
+    pub async fn create_company_grant(&self, actor: &Claims, company_id: i64, grant: CreateCompanyGrantDto) -> Result<RetrieveCompanyGrantDto, AppError> {
+        let granted_by = actor.sub;
+        let permission = Self::requested_permission(&grant);
+
+        CompanyGrant::check_expires_in_days(grant.expires_in_days)?;
+        CompanyGrant::check_permission(&permission)?;
+
+        if !service::get().permission().can_grant_executor(tx, actor, company_id, &permission).await? {
+            return Err(permission_denied());
+        }
+
+        if !service::get().company().company_exists_by_id_executor(tx, company_id).await? {
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![company_id.to_string()])
+            ))
+        }
+
+        match service::get().user().get_company_by_user_id_executor(tx, grant.user_id).await? {
+            Some(user_company_id) if user_company_id == company_id => return Err(AppError::new(
+                String::from("Users cannot be granted permissions over their own company"),
+                AppErrorType::BadRequest,
+                None
+            )),
+            Some(_) => (),
+            None => return Err(AppError::new(
+                String::from(r#"User with id "$1" does not exist"#),
+                AppErrorType::BadRequest,
+                Some(vec![grant.user_id.to_string()])
+            ))
+        }
+
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::days(grant.expires_in_days))
+            .expect("valid timestamp");
+
+        let created = self.company_grant_repository.create_company_grant(tx, &CreateCompanyGrantDb {
+            user_id: grant.user_id,
+            company_id,
+            granted_by,
+            user: grant.user,
+            payroll: grant.payroll,
+            company: grant.company,
+            created_at: now.naive_utc().to_string(),
+            expires_at: expires_at.naive_utc().to_string()
+        }).await?;
+
+        Ok(created.to_retrieve_company_grant_dto())
+    }
+
+    #[executor]
+    pub async fn get_company_grants(&self, company_id: i64) -> Result<Vec<RetrieveCompanyGrantDto>, AppError> {
+        let grants = self.company_grant_repository.get_company_grants_by_company_id(tx, company_id).await?;
+
+        Ok(grants.into_iter().map(|grant| grant.to_retrieve_company_grant_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn revoke_company_grant(&self, company_id: i64, grant_id: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        if !self.company_grant_repository.revoke_company_grant(tx, grant_id, company_id, &now).await? {
+            return Err(AppError::new(
+                String::from(r#"Active grant with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![grant_id.to_string()])
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Removes every grant over the company, revoked or not. Only used when the company is deleted.
+    #[executor]
+    pub async fn delete_company_grants(&self, company_id: i64) -> Result<(), AppError> {
+        self.company_grant_repository.delete_company_grants_by_company_id(tx, company_id).await
+    }
+
+    /// Union of the active grants of the user over the company. It has no permission if there are none.
+    #[executor]
+    pub async fn get_granted_permission(&self, user_id: i64, company_id: i64) -> Result<Permission, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+        let grants = self.company_grant_repository.get_active_permissions(tx, user_id, company_id, &now).await?;
+
+        Ok(grants.into_iter().fold(Permission::default(user_id), |mut permission, grant| {
+            permission.user |= grant.user;
+            permission.payroll |= grant.payroll;
+            permission.company |= grant.company;
+            permission
+        }))
+    }
+
+    pub fn requested_permission(grant: &CreateCompanyGrantDto) -> Permission {
+        Permission::new(grant.user_id, grant.user, grant.payroll, grant.company)
+    }
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+/// Permissions use the `Permission` bitmasks, and may only include `SelfCompany` scopes. They apply to the company
+/// of the route.
+#[derive(Deserialize)]
+pub struct CreateCompanyGrantDto {
+    pub user_id: i64,
+    pub user: i16,
+    pub payroll: i16,
+    pub company: i16,
+    pub expires_in_days: i64
+}
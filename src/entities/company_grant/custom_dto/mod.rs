@@ -0,0 +1 @@
+pub mod company_grant_dto;
\ No newline at end of file
@@ -0,0 +1,6 @@
+pub mod company_grant;
+pub mod company_grant_service;
+pub mod company_grant_repository;
+pub mod company_grant_controller;
+pub mod company_grant_policy;
+pub mod custom_dto;
\ No newline at end of file
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+/// Also used to update departments, replacing every field
+#[derive(Deserialize)]
+pub struct CreateDepartmentDto {
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub cost_center: Option<String>,
+    /// User of the company that manages the department and its subdepartments
+    pub manager_user_id: Option<i64>
+}
+
+/// A missing department removes the user from their department
+#[derive(Deserialize)]
+pub struct ChangeDepartmentDto {
+    pub department_id: Option<i64>
+}
\ No newline at end of file
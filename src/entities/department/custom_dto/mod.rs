@@ -0,0 +1 @@
+pub mod department_dto;
\ No newline at end of file
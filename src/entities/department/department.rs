@@ -0,0 +1,100 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::error::error::{AppError, AppErrorType};
+
+use super::custom_dto::department_dto::CreateDepartmentDto;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateDepartmentDb",
+    fields(company_id, parent_id, name, cost_center, manager_user_id)
+))]
+#[custom_model(model(
+    name = "RetrieveDepartmentDb",
+    fields(id, company_id, parent_id, name, cost_center, manager_user_id)
+))]
+#[custom_model(model(
+    name = "RetrieveDepartmentDto",
+    fields(id, company_id, parent_id, name, cost_center, manager_user_id),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct Department {
+    id: i64,
+    company_id: i64,
+    /// Departments without a parent are at the top of the company
+    parent_id: Option<i64>,
+    name: String,
+    cost_center: Option<String>,
+    manager_user_id: Option<i64>
+}
+
+impl Department {
+    pub fn check_name(name: &str) -> Result<(), AppError> {
+        if name.is_empty() || name.len() > 50 {
+            return Err(AppError::new(
+                String::from("The department name must be between 1 and 50 characters long"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_cost_center(cost_center: &Option<String>) -> Result<(), AppError> {
+        if let Some(cost_center) = cost_center {
+            if cost_center.is_empty() || cost_center.len() > 20 {
+                return Err(AppError::new(
+                    String::from("The cost center must be between 1 and 20 characters long"),
+                    AppErrorType::BadRequest,
+                    None
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pushes a subquery with the ids of the department and of its subdepartments, the same departments a manager of
+    /// this one manages, see `DepartmentRepository::is_managed_by`
+    pub fn push_descendant_ids(query: &mut QueryBuilder<Sqlite>, department_id: i64) {
+        query.push("(WITH RECURSIVE Descendant(id) AS (SELECT id FROM Department WHERE id = ");
+        query.push_bind(department_id);
+        query.push(" UNION SELECT Department.id FROM Department JOIN Descendant ON Department.parent_id = Descendant.id)");
+        query.push(" SELECT id FROM Descendant)");
+    }
+}
+
+impl RetrieveDepartmentDb {
+    pub fn to_retrieve_department_dto(self) -> RetrieveDepartmentDto {
+        RetrieveDepartmentDto {
+            id: self.id,
+            company_id: self.company_id,
+            parent_id: self.parent_id,
+            name: self.name,
+            cost_center: self.cost_center,
+            manager_user_id: self.manager_user_id
+        }
+    }
+}
+
+impl CreateDepartmentDb {
+    pub fn from_create_department_dto(company_id: i64, department: CreateDepartmentDto) -> Result<CreateDepartmentDb, AppError> {
+        let name = department.name.trim().to_string();
+        let cost_center = department.cost_center.map(|cost_center| cost_center.trim().to_string());
+
+        Department::check_name(&name)?;
+        Department::check_cost_center(&cost_center)?;
+
+        Ok(CreateDepartmentDb {
+            company_id,
+            parent_id: department.parent_id,
+            name,
+            cost_center,
+            manager_user_id: department.manager_user_id
+        })
+    }
+}
\ No newline at end of file
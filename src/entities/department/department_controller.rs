@@ -0,0 +1,53 @@
+use actix_web::{web, Responder};
+
+use crate::{service, util::json_response::json_response};
+
+use super::{custom_dto::department_dto::CreateDepartmentDto, department_policy::{ManageDepartmentsClaims, ReadDepartmentsClaims}};
+
+/// Routes are nested in the `/companies` scope
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/{company_id}/departments")
+            .route("", web::post().to(create_department))
+            .route("", web::get().to(get_departments))
+            .route("/{department_id}", web::get().to(get_department))
+            .route("/{department_id}", web::put().to(update_department))
+            .route("/{department_id}", web::delete().to(delete_department))
+    );
+}
+
+pub async fn create_department(company_id: web::Path<i64>, department: web::Json<CreateDepartmentDto>, _claims: ManageDepartmentsClaims) -> impl Responder {
+    let created = service::get().department().create_department(company_id.into_inner(), department.into_inner()).await;
+
+    json_response(&created)
+}
+
+pub async fn get_departments(company_id: web::Path<i64>, _claims: ReadDepartmentsClaims) -> impl Responder {
+    let departments = service::get().department().get_departments(company_id.into_inner()).await;
+
+    json_response(&departments)
+}
+
+pub async fn get_department(path: web::Path<(i64, i64)>, _claims: ReadDepartmentsClaims) -> impl Responder {
+    let (company_id, department_id) = path.into_inner();
+
+    let department = service::get().department().get_department(company_id, department_id).await;
+
+    json_response(&department)
+}
+
+pub async fn update_department(path: web::Path<(i64, i64)>, department: web::Json<CreateDepartmentDto>, _claims: ManageDepartmentsClaims) -> impl Responder {
+    let (company_id, department_id) = path.into_inner();
+
+    let updated = service::get().department().update_department(company_id, department_id, department.into_inner()).await;
+
+    json_response(&updated)
+}
+
+pub async fn delete_department(path: web::Path<(i64, i64)>, _claims: ManageDepartmentsClaims) -> impl Responder {
+    let (company_id, department_id) = path.into_inner();
+
+    let deleted = service::get().department().delete_department(company_id, department_id).await;
+
+    json_response(&deleted)
+}
\ No newline at end of file
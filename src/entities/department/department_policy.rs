@@ -0,0 +1,11 @@
+use crate::{auth::policy::{path_id, Resource}, authorized_claims, entities::permission::permission::Operation};
+
+// Departments are part of their company, so they follow the policy of the company
+
+authorized_claims!(
+    ReadDepartmentsClaims, Operation::Read, |req| path_id(req, "company_id").map(Resource::Company)
+);
+
+authorized_claims!(
+    ManageDepartmentsClaims, Operation::Update, |req| path_id(req, "company_id").map(Resource::Company)
+);
\ No newline at end of file
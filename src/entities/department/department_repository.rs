@@ -0,0 +1,209 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::department::{CreateDepartmentDb, RetrieveDepartmentDb};
+
+pub struct DepartmentRepository {}
+
+impl DepartmentRepository {
+    pub fn new() -> DepartmentRepository {
+        DepartmentRepository {
+
+        }
+    }
+
+    pub async fn create_department(&self, tx: &mut SqliteConnection, department: &CreateDepartmentDb) -> Result<RetrieveDepartmentDb, AppError> {
+        sqlx::query_as!(
+            RetrieveDepartmentDb,
+            r#"
+            INSERT INTO Department (company_id, parent_id, name, cost_center, manager_user_id)
+            VALUES($1, $2, $3, $4, $5)
+            RETURNING id as "id!: i64", company_id, parent_id, name, cost_center, manager_user_id
+            "#,
+            department.company_id,
+            department.parent_id,
+            department.name,
+            department.cost_center,
+            department.manager_user_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_departments_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<Vec<RetrieveDepartmentDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveDepartmentDb,
+            r#"
+            SELECT id as "id!: i64", company_id, parent_id, name, cost_center, manager_user_id
+            FROM Department
+            WHERE company_id = $1
+            ORDER BY id
+            "#,
+            company_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_department_by_id(&self, tx: &mut SqliteConnection, id: i64) -> Result<Option<RetrieveDepartmentDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveDepartmentDb,
+            r#"
+            SELECT id as "id!: i64", company_id, parent_id, name, cost_center, manager_user_id
+            FROM Department
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn department_exists_by_name(&self, tx: &mut SqliteConnection, company_id: i64, name: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM Department
+            WHERE company_id = $1 AND name = $2
+            LIMIT 1
+            "#,
+            company_id,
+            name
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|r| r.is_some())
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_department(&self, tx: &mut SqliteConnection, id: i64, department: &CreateDepartmentDb) -> Result<RetrieveDepartmentDb, AppError> {
+        sqlx::query_as!(
+            RetrieveDepartmentDb,
+            r#"
+            UPDATE Department
+            SET parent_id = $1, name = $2, cost_center = $3, manager_user_id = $4
+            WHERE id = $5
+            RETURNING id as "id!: i64", company_id, parent_id, name, cost_center, manager_user_id
+            "#,
+            department.parent_id,
+            department.name,
+            department.cost_center,
+            department.manager_user_id,
+            id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_department(&self, tx: &mut SqliteConnection, id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM Department
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_departments_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM Department
+            WHERE company_id = $1
+            "#,
+            company_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn count_subdepartments(&self, tx: &mut SqliteConnection, id: i64) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM Department
+            WHERE parent_id = $1
+            "#,
+            id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn count_users(&self, tx: &mut SqliteConnection, id: i64) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM AppUser
+            WHERE department_id = $1
+            "#,
+            id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Whether `ancestor_id` is the department or one of its parents
+    pub async fn is_ancestor(&self, tx: &mut SqliteConnection, ancestor_id: i64, id: i64) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE Ancestor(id, parent_id) AS (
+                SELECT id, parent_id FROM Department WHERE id = $1
+                UNION
+                SELECT Department.id, Department.parent_id
+                FROM Department
+                JOIN Ancestor ON Department.id = Ancestor.parent_id
+            )
+            SELECT 1 as "exists!: i64"
+            FROM Ancestor
+            WHERE id = $2
+            LIMIT 1
+            "#,
+            id,
+            ancestor_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|r| r.is_some())
+        .map_err(to_app_error)
+    }
+
+    /// Whether the user manages the department or one of its parents
+    pub async fn is_managed_by(&self, tx: &mut SqliteConnection, id: i64, user_id: i64) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE Ancestor(id, parent_id, manager_user_id) AS (
+                SELECT id, parent_id, manager_user_id FROM Department WHERE id = $1
+                UNION
+                SELECT Department.id, Department.parent_id, Department.manager_user_id
+                FROM Department
+                JOIN Ancestor ON Department.id = Ancestor.parent_id
+            )
+            SELECT 1 as "exists!: i64"
+            FROM Ancestor
+            WHERE manager_user_id = $2
+            LIMIT 1
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|r| r.is_some())
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
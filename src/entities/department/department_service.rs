@@ -0,0 +1,166 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{error::error::{AppError, AppErrorType}, service};
+
+use super::{custom_dto::department_dto::CreateDepartmentDto, department::{CreateDepartmentDb, RetrieveDepartmentDb, RetrieveDepartmentDto}, department_repository::DepartmentRepository};
+
+pub struct DepartmentService {
+    db_pool: SqlitePool,
+    department_repository: DepartmentRepository
+}
+
+impl DepartmentService {
+    pub fn new(db_pool: SqlitePool, department_repository: DepartmentRepository) -> DepartmentService {
+        DepartmentService {
+            db_pool,
+            department_repository
+        }
+    }
+
+    #[executor]
+    pub async fn create_department(&self, company_id: i64, department: CreateDepartmentDto) -> Result<RetrieveDepartmentDto, AppError> {
+        if !service::get().company().company_exists_by_id_executor(tx, company_id).await? {
+            return Err(AppError::new(
+                String::from(r#"Company with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![company_id.to_string()])
+            ))
+        }
+
+        let create_department_db = CreateDepartmentDb::from_create_department_dto(company_id, department)?;
+
+        if self.department_repository.department_exists_by_name(tx, company_id, &create_department_db.name).await? {
+            return Err(Self::department_name_taken(create_department_db.name));
+        }
+
+        self.check_references(tx, None, &create_department_db).await?;
+
+        let created = self.department_repository.create_department(tx, &create_department_db).await?;
+
+        Ok(created.to_retrieve_department_dto())
+    }
+
+    #[executor]
+    pub async fn get_departments(&self, company_id: i64) -> Result<Vec<RetrieveDepartmentDto>, AppError> {
+        let departments = self.department_repository.get_departments_by_company_id(tx, company_id).await?;
+
+        Ok(departments.into_iter().map(|department| department.to_retrieve_department_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn get_department(&self, company_id: i64, department_id: i64) -> Result<RetrieveDepartmentDto, AppError> {
+        Ok(self.get_company_department(tx, company_id, department_id).await?.to_retrieve_department_dto())
+    }
+
+    /// Replaces every field of the department. It cannot be moved under itself or one of its subdepartments.
+    #[executor]
+    pub async fn update_department(&self, company_id: i64, department_id: i64, department: CreateDepartmentDto) -> Result<RetrieveDepartmentDto, AppError> {
+        let existing = self.get_company_department(tx, company_id, department_id).await?;
+        let create_department_db = CreateDepartmentDb::from_create_department_dto(company_id, department)?;
+
+        if create_department_db.name != existing.name && self.department_repository.department_exists_by_name(tx, company_id, &create_department_db.name).await? {
+            return Err(Self::department_name_taken(create_department_db.name));
+        }
+
+        self.check_references(tx, Some(department_id), &create_department_db).await?;
+
+        let updated = self.department_repository.update_department(tx, department_id, &create_department_db).await?;
+
+        Ok(updated.to_retrieve_department_dto())
+    }
+
+    /// Departments with users or subdepartments cannot be deleted
+    #[executor]
+    pub async fn delete_department(&self, company_id: i64, department_id: i64) -> Result<(), AppError> {
+        self.get_company_department(tx, company_id, department_id).await?;
+
+        let subdepartments = self.department_repository.count_subdepartments(tx, department_id).await?;
+        let users = self.department_repository.count_users(tx, department_id).await?;
+
+        if subdepartments > 0 || users > 0 {
+            return Err(AppError::new(
+                String::from(r#"Department with id "$1" still has $2 subdepartments and $3 users"#),
+                AppErrorType::Conflict,
+                Some(vec![department_id.to_string(), subdepartments.to_string(), users.to_string()])
+            ));
+        }
+
+        self.department_repository.delete_department(tx, department_id).await
+    }
+
+    /// Removes every department of the company. Only used when the company is deleted, so they have no users.
+    #[executor]
+    pub async fn delete_company_departments(&self, company_id: i64) -> Result<(), AppError> {
+        self.department_repository.delete_departments_by_company_id(tx, company_id).await
+    }
+
+    #[executor]
+    pub async fn get_company_by_department_id(&self, department_id: i64) -> Result<Option<i64>, AppError> {
+        Ok(
+            self.department_repository.get_department_by_id(tx, department_id).await?
+                .map(|department| department.company_id)
+        )
+    }
+
+    /// Whether the user manages the department, directly or through one of its parents
+    #[executor]
+    pub async fn is_managed_by(&self, department_id: i64, user_id: i64) -> Result<bool, AppError> {
+        self.department_repository.is_managed_by(tx, department_id, user_id).await
+    }
+
+    /// The parent and the manager must belong to the company of the department
+    async fn check_references(&self, tx: &mut SqliteConnection, department_id: Option<i64>, department: &CreateDepartmentDb) -> Result<(), AppError> {
+        if let Some(parent_id) = department.parent_id {
+            match self.department_repository.get_department_by_id(tx, parent_id).await? {
+                Some(parent) if parent.company_id == department.company_id => (),
+                _ => return Err(AppError::new(
+                    String::from(r#"Department with id "$1" does not exist in the company"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![parent_id.to_string()])
+                ))
+            }
+
+            if let Some(department_id) = department_id {
+                if self.department_repository.is_ancestor(tx, department_id, parent_id).await? {
+                    return Err(AppError::new(
+                        String::from("A department cannot be moved under itself or one of its subdepartments"),
+                        AppErrorType::BadRequest,
+                        None
+                    ));
+                }
+            }
+        }
+
+        if let Some(manager_user_id) = department.manager_user_id {
+            if service::get().user().get_company_by_user_id_executor(tx, manager_user_id).await? != Some(department.company_id) {
+                return Err(AppError::new(
+                    String::from(r#"User with id "$1" does not exist in the company"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![manager_user_id.to_string()])
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_company_department(&self, tx: &mut SqliteConnection, company_id: i64, department_id: i64) -> Result<RetrieveDepartmentDb, AppError> {
+        match self.department_repository.get_department_by_id(tx, department_id).await? {
+            Some(department) if department.company_id == company_id => Ok(department),
+            _ => Err(AppError::new(
+                String::from(r#"Department with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![department_id.to_string()])
+            ))
+        }
+    }
+
+    fn department_name_taken(name: String) -> AppError {
+        AppError::new(
+            String::from(r#"Department with name "$1" already exists"#),
+            AppErrorType::Conflict,
+            Some(vec![name])
+        )
+    }
+}
\ No newline at end of file
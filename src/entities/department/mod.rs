@@ -0,0 +1,6 @@
+pub mod department;
+pub mod department_service;
+pub mod department_repository;
+pub mod department_controller;
+pub mod department_policy;
+pub mod custom_dto;
\ No newline at end of file
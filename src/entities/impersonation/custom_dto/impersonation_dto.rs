@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+/// The reason is kept in the audit trail of the impersonation
+#[derive(Deserialize)]
+pub struct StartImpersonationDto {
+    pub reason: String
+}
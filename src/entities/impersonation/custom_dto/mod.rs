@@ -0,0 +1 @@
+pub mod impersonation_dto;
\ No newline at end of file
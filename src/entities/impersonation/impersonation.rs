@@ -0,0 +1,58 @@
+use macros::DeriveCustomModel;
+
+use crate::error::error::{AppError, AppErrorType};
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateImpersonationDb",
+    fields(actor_user_id, user_id, session_id, reason, started_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveImpersonationDb",
+    fields(id, actor_user_id, user_id, session_id, expires_at)
+))]
+#[allow(dead_code)]
+pub struct Impersonation {
+    id: i64,
+    actor_user_id: i64,
+    user_id: i64,
+    session_id: i64,
+    reason: String,
+    started_at: String,
+    expires_at: String,
+    ended_at: Option<String>
+}
+
+impl Impersonation {
+    pub const MAX_REASON_LENGTH: usize = 200;
+
+    pub fn check_reason(reason: &str) -> Result<(), AppError> {
+        let length = reason.trim().chars().count();
+
+        if length == 0 || length > Self::MAX_REASON_LENGTH {
+            return Err(AppError::new(
+                String::from("The reason must have between 1 and $1 characters"),
+                AppErrorType::BadRequest,
+                Some(vec![Self::MAX_REASON_LENGTH.to_string()])
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateImpersonationRequestDb",
+    fields(impersonation_id, actor_user_id, user_id, method, path, requested_at)
+))]
+#[allow(dead_code)]
+pub struct ImpersonationRequest {
+    id: i64,
+    impersonation_id: i64,
+    actor_user_id: i64,
+    user_id: i64,
+    method: String,
+    path: String,
+    requested_at: String
+}
@@ -0,0 +1,87 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::impersonation::{CreateImpersonationDb, CreateImpersonationRequestDb, RetrieveImpersonationDb};
+
+pub struct ImpersonationRepository {}
+
+impl ImpersonationRepository {
+    pub fn new() -> ImpersonationRepository {
+        ImpersonationRepository {
+
+        }
+    }
+
+    pub async fn create_impersonation(&self, tx: &mut SqliteConnection, impersonation: &CreateImpersonationDb) -> Result<RetrieveImpersonationDb, AppError> {
+        sqlx::query_as!(
+            RetrieveImpersonationDb,
+            r#"
+            INSERT INTO Impersonation (actor_user_id, user_id, session_id, reason, started_at, expires_at)
+            VALUES($1, $2, $3, $4, $5, $6)
+            RETURNING id as "id!: i64", actor_user_id, user_id, session_id, expires_at
+            "#,
+            impersonation.actor_user_id,
+            impersonation.user_id,
+            impersonation.session_id,
+            impersonation.reason,
+            impersonation.started_at,
+            impersonation.expires_at
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_active_impersonation_by_session_id(&self, tx: &mut SqliteConnection, session_id: i64, now: &str) -> Result<Option<RetrieveImpersonationDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveImpersonationDb,
+            r#"
+            SELECT id as "id!: i64", actor_user_id, user_id, session_id, expires_at
+            FROM Impersonation
+            WHERE session_id = $1 AND ended_at IS NULL AND expires_at > $2
+            LIMIT 1
+            "#,
+            session_id,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn end_impersonation(&self, tx: &mut SqliteConnection, session_id: i64, ended_at: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Impersonation
+            SET ended_at = $1
+            WHERE session_id = $2 AND ended_at IS NULL
+            "#,
+            ended_at,
+            session_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn create_request(&self, tx: &mut SqliteConnection, request: &CreateImpersonationRequestDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO ImpersonationRequest (impersonation_id, actor_user_id, user_id, method, path, requested_at)
+            VALUES($1, $2, $3, $4, $5, $6)
+            "#,
+            request.impersonation_id,
+            request.actor_user_id,
+            request.user_id,
+            request.method,
+            request.path,
+            request.requested_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+}
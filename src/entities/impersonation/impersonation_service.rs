@@ -0,0 +1,65 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::error::{AppError, AppErrorType};
+
+use super::{impersonation::{CreateImpersonationDb, CreateImpersonationRequestDb, RetrieveImpersonationDb}, impersonation_repository::ImpersonationRepository};
+
+/// Keeps the audit trail of impersonations: who impersonated whom, why, and every request made meanwhile.
+pub struct ImpersonationService {
+    db_pool: SqlitePool,
+    impersonation_repository: ImpersonationRepository
+}
+
+impl ImpersonationService {
+    pub fn new(db_pool: SqlitePool, impersonation_repository: ImpersonationRepository) -> ImpersonationService {
+        ImpersonationService {
+            db_pool,
+            impersonation_repository
+        }
+    }
+
+    #[executor]
+    pub async fn start(&self, actor_user_id: i64, user_id: i64, session_id: i64, reason: &str, expires_at: &chrono::DateTime<chrono::Utc>) -> Result<RetrieveImpersonationDb, AppError> {
+        self.impersonation_repository.create_impersonation(tx, &CreateImpersonationDb {
+            actor_user_id,
+            user_id,
+            session_id,
+            reason: reason.trim().to_string(),
+            started_at: chrono::Utc::now().naive_utc().to_string(),
+            expires_at: expires_at.naive_utc().to_string()
+        }).await
+    }
+
+    /// Records a request made with an impersonation token. It fails if the impersonation is over or does not match
+    /// the token, so requests are never served without being recorded.
+    #[executor]
+    pub async fn record_request(&self, actor_user_id: i64, user_id: i64, session_id: i64, method: &str, path: &str) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        let impersonation = match self.impersonation_repository.get_active_impersonation_by_session_id(tx, session_id, &now).await? {
+            Some(impersonation) if impersonation.actor_user_id == actor_user_id && impersonation.user_id == user_id => impersonation,
+            _ => return Err(AppError::new(
+                String::from("The impersonation has ended"),
+                AppErrorType::Unauthorized,
+                None
+            ))
+        };
+
+        self.impersonation_repository.create_request(tx, &CreateImpersonationRequestDb {
+            impersonation_id: impersonation.id,
+            actor_user_id,
+            user_id,
+            method: method.to_string(),
+            path: path.to_string(),
+            requested_at: now
+        }).await
+    }
+
+    #[executor]
+    pub async fn end(&self, session_id: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.impersonation_repository.end_impersonation(tx, session_id, &now).await
+    }
+}
@@ -0,0 +1,4 @@
+pub mod impersonation;
+pub mod impersonation_service;
+pub mod impersonation_repository;
+pub mod custom_dto;
\ No newline at end of file
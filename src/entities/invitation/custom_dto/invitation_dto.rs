@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+/// The user is created in the company of the route, without a password
+#[derive(Deserialize)]
+pub struct InviteUserDto {
+    pub username: String,
+    pub email: String,
+    pub name: String,
+    pub role_id: i64
+}
+
+#[derive(Deserialize)]
+pub struct AcceptInvitationDto {
+    pub token: String,
+    pub password: String
+}
@@ -0,0 +1 @@
+pub mod invitation_dto;
\ No newline at end of file
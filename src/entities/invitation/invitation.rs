@@ -0,0 +1,52 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateInvitationDb",
+    fields(user_id, company_id, invited_by, email, token_hash, created_at, last_sent_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveInvitationDb",
+    fields(id, user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at)
+))]
+#[custom_model(model(
+    name = "RetrieveInvitationDto",
+    fields(id, user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct Invitation {
+    id: i64,
+    user_id: i64,
+    company_id: i64,
+    invited_by: i64,
+    email: String,
+    token_hash: String,
+    created_at: String,
+    last_sent_at: String,
+    expires_at: String,
+    accepted_at: Option<String>,
+    revoked_at: Option<String>
+}
+
+impl RetrieveInvitationDb {
+    pub fn is_pending(&self) -> bool {
+        self.accepted_at.is_none() && self.revoked_at.is_none()
+    }
+
+    pub fn to_retrieve_invitation_dto(self) -> RetrieveInvitationDto {
+        RetrieveInvitationDto {
+            id: self.id,
+            user_id: self.user_id,
+            company_id: self.company_id,
+            invited_by: self.invited_by,
+            email: self.email,
+            created_at: self.created_at,
+            last_sent_at: self.last_sent_at,
+            expires_at: self.expires_at,
+            accepted_at: self.accepted_at,
+            revoked_at: self.revoked_at
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use actix_web::{web, Responder};
+
+use crate::{entities::user::user_policy::CreateCompanyUserClaims, service, util::json_response::json_response};
+
+use super::custom_dto::invitation_dto::InviteUserDto;
+
+/// Routes are nested in the `/companies` scope. Inviting creates a user in the company, so it requires the same
+/// permission as creating one there.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/{company_id}/invitations")
+            .route("", web::post().to(invite_user))
+            .route("", web::get().to(get_pending_invitations))
+            .route("/{invitation_id}/resend", web::post().to(resend_invitation))
+            .route("/{invitation_id}", web::delete().to(revoke_invitation))
+    );
+}
+
+pub async fn invite_user(company_id: web::Path<i64>, invitation: web::Json<InviteUserDto>, claims: CreateCompanyUserClaims) -> impl Responder {
+    let CreateCompanyUserClaims(claims) = claims;
+    let company_id = company_id.into_inner();
+
+    let invitation = service::get().auth().invite_user(&claims, company_id, invitation.into_inner()).await;
+
+    json_response(&invitation)
+}
+
+pub async fn get_pending_invitations(company_id: web::Path<i64>, _claims: CreateCompanyUserClaims) -> impl Responder {
+    let company_id = company_id.into_inner();
+
+    let invitations = service::get().invitation().get_pending_invitations(company_id).await;
+
+    json_response(&invitations)
+}
+
+pub async fn resend_invitation(path: web::Path<(i64, i64)>, _claims: CreateCompanyUserClaims) -> impl Responder {
+    let (company_id, invitation_id) = path.into_inner();
+
+    let invitation = service::get().auth().resend_invitation(company_id, invitation_id).await;
+
+    json_response(&invitation)
+}
+
+pub async fn revoke_invitation(path: web::Path<(i64, i64)>, _claims: CreateCompanyUserClaims) -> impl Responder {
+    let (company_id, invitation_id) = path.into_inner();
+
+    let revoked = service::get().invitation().revoke_invitation(company_id, invitation_id).await;
+
+    json_response(&revoked)
+}
@@ -0,0 +1,157 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::invitation::{CreateInvitationDb, RetrieveInvitationDb};
+
+pub struct InvitationRepository {}
+
+impl InvitationRepository {
+    pub fn new() -> InvitationRepository {
+        InvitationRepository {
+
+        }
+    }
+
+    pub async fn create_invitation(&self, tx: &mut SqliteConnection, invitation: &CreateInvitationDb) -> Result<RetrieveInvitationDb, AppError> {
+        sqlx::query_as!(
+            RetrieveInvitationDb,
+            r#"
+            INSERT INTO Invitation (user_id, company_id, invited_by, email, token_hash, created_at, last_sent_at, expires_at)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id as "id!: i64", user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at
+            "#,
+            invitation.user_id,
+            invitation.company_id,
+            invitation.invited_by,
+            invitation.email,
+            invitation.token_hash,
+            invitation.created_at,
+            invitation.last_sent_at,
+            invitation.expires_at
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_pending_invitations_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<Vec<RetrieveInvitationDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveInvitationDb,
+            r#"
+            SELECT id as "id!: i64", user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at
+            FROM Invitation
+            WHERE company_id = $1 AND accepted_at IS NULL AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+            company_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_invitation_by_id(&self, tx: &mut SqliteConnection, company_id: i64, invitation_id: i64) -> Result<Option<RetrieveInvitationDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveInvitationDb,
+            r#"
+            SELECT id as "id!: i64", user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at
+            FROM Invitation
+            WHERE id = $1 AND company_id = $2
+            LIMIT 1
+            "#,
+            invitation_id,
+            company_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Replaces the token of a pending invitation. Returns `None` if it was accepted or revoked meanwhile.
+    pub async fn renew_token(&self, tx: &mut SqliteConnection, invitation_id: i64, token_hash: &str, last_sent_at: &str, expires_at: &str) -> Result<Option<RetrieveInvitationDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveInvitationDb,
+            r#"
+            UPDATE Invitation
+            SET token_hash = $1, last_sent_at = $2, expires_at = $3
+            WHERE id = $4 AND accepted_at IS NULL AND revoked_at IS NULL
+            RETURNING id as "id!: i64", user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at
+            "#,
+            token_hash,
+            last_sent_at,
+            expires_at,
+            invitation_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the invitation was already accepted or revoked.
+    pub async fn revoke_invitation(&self, tx: &mut SqliteConnection, invitation_id: i64, revoked_at: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Invitation
+            SET revoked_at = $1
+            WHERE id = $2 AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            invitation_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn revoke_user_invitations(&self, tx: &mut SqliteConnection, user_id: i64, revoked_at: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Invitation
+            SET revoked_at = $1
+            WHERE user_id = $2 AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_valid_invitation_by_hash(&self, tx: &mut SqliteConnection, token_hash: &str, now: &str) -> Result<Option<RetrieveInvitationDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveInvitationDb,
+            r#"
+            SELECT id as "id!: i64", user_id, company_id, invited_by, email, created_at, last_sent_at, expires_at, accepted_at, revoked_at
+            FROM Invitation
+            WHERE token_hash = $1 AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at > $2
+            LIMIT 1
+            "#,
+            token_hash,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the invitation was already accepted or revoked by a concurrent request.
+    pub async fn mark_invitation_accepted(&self, tx: &mut SqliteConnection, invitation_id: i64, accepted_at: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Invitation
+            SET accepted_at = $1
+            WHERE id = $2 AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+            accepted_at,
+            invitation_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+}
@@ -0,0 +1,141 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{config, error::error::{AppError, AppErrorType}, util::crypto::{generate_secret_token, hash_secret_token}};
+
+use super::{invitation::{CreateInvitationDb, RetrieveInvitationDb, RetrieveInvitationDto}, invitation_repository::InvitationRepository};
+
+pub struct InvitationService {
+    db_pool: SqlitePool,
+    invitation_repository: InvitationRepository
+}
+
+impl InvitationService {
+    pub fn new(db_pool: SqlitePool, invitation_repository: InvitationRepository) -> InvitationService {
+        InvitationService {
+            db_pool,
+            invitation_repository
+        }
+    }
+
+    /// Creates an invitation for a pending user and returns it with the raw token
+    #[executor]
+    pub async fn create_invitation(&self, user_id: i64, company_id: i64, invited_by: i64, email: &str) -> Result<(RetrieveInvitationDb, String), AppError> {
+        let token = generate_secret_token();
+        let (now, expires_at) = Self::token_validity();
+
+        let invitation = self.invitation_repository.create_invitation(tx, &CreateInvitationDb {
+            user_id,
+            company_id,
+            invited_by,
+            email: email.to_string(),
+            token_hash: hash_secret_token(&token),
+            created_at: now.clone(),
+            last_sent_at: now,
+            expires_at
+        }).await?;
+
+        Ok((invitation, token))
+    }
+
+    /// Invitations that were neither accepted nor revoked, including the expired ones, which can be resent
+    #[executor]
+    pub async fn get_pending_invitations(&self, company_id: i64) -> Result<Vec<RetrieveInvitationDto>, AppError> {
+        let invitations = self.invitation_repository.get_pending_invitations_by_company_id(tx, company_id).await?;
+
+        Ok(invitations.into_iter().map(|invitation| invitation.to_retrieve_invitation_dto()).collect())
+    }
+
+    /// Issues a new token for a pending invitation, so the previous one stops being valid, and returns it with the
+    /// raw token
+    #[executor]
+    pub async fn renew_invitation(&self, company_id: i64, invitation_id: i64) -> Result<(RetrieveInvitationDb, String), AppError> {
+        let invitation = self.get_invitation_executor(tx, company_id, invitation_id).await?;
+
+        if !invitation.is_pending() {
+            return Err(Self::not_pending(invitation_id));
+        }
+
+        let token = generate_secret_token();
+        let (now, expires_at) = Self::token_validity();
+
+        match self.invitation_repository.renew_token(tx, invitation.id, &hash_secret_token(&token), &now, &expires_at).await? {
+            Some(invitation) => Ok((invitation, token)),
+            None => Err(Self::not_pending(invitation_id))
+        }
+    }
+
+    #[executor]
+    pub async fn revoke_invitation(&self, company_id: i64, invitation_id: i64) -> Result<(), AppError> {
+        let invitation = self.get_invitation_executor(tx, company_id, invitation_id).await?;
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        if !self.invitation_repository.revoke_invitation(tx, invitation.id, &now).await? {
+            return Err(Self::not_pending(invitation_id));
+        }
+
+        Ok(())
+    }
+
+    #[executor]
+    pub async fn revoke_user_invitations(&self, user_id: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.invitation_repository.revoke_user_invitations(tx, user_id, &now).await
+    }
+
+    /// Marks the invitation as accepted and returns the id of the invited user.
+    #[executor]
+    pub async fn consume_token(&self, token: &str) -> Result<i64, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        let invitation = match self.invitation_repository.get_valid_invitation_by_hash(tx, &hash_secret_token(token), &now).await? {
+            Some(invitation) => invitation,
+            None => return Err(Self::invalid_token())
+        };
+
+        if !self.invitation_repository.mark_invitation_accepted(tx, invitation.id, &now).await? {
+            return Err(Self::invalid_token());
+        }
+
+        Ok(invitation.user_id)
+    }
+
+    #[executor]
+    pub async fn get_invitation(&self, company_id: i64, invitation_id: i64) -> Result<RetrieveInvitationDb, AppError> {
+        match self.invitation_repository.get_invitation_by_id(tx, company_id, invitation_id).await? {
+            Some(invitation) => Ok(invitation),
+            None => Err(AppError::new(
+                String::from(r#"Invitation with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![invitation_id.to_string()])
+            ))
+        }
+    }
+
+    /// Creation time and expiration of a new token
+    fn token_validity() -> (String, String) {
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::seconds(config::get().auth.invitation_token_ttl))
+            .expect("valid timestamp");
+
+        (now.naive_utc().to_string(), expires_at.naive_utc().to_string())
+    }
+
+    fn not_pending(invitation_id: i64) -> AppError {
+        AppError::new(
+            String::from(r#"Invitation with id "$1" was already accepted or revoked"#),
+            AppErrorType::Conflict,
+            Some(vec![invitation_id.to_string()])
+        )
+    }
+
+    fn invalid_token() -> AppError {
+        AppError::new(
+            String::from("Invalid or expired invitation token"),
+            AppErrorType::BadRequest,
+            None
+        )
+    }
+}
@@ -0,0 +1,5 @@
+pub mod invitation;
+pub mod invitation_service;
+pub mod invitation_repository;
+pub mod invitation_controller;
+pub mod custom_dto;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use macros::DeriveCustomModel;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateLoginAttemptDb",
+    fields(username, ip_address, success, failure_reason, attempted_at)
+))]
+#[allow(dead_code)]
+pub struct LoginAttempt {
+    id: i64,
+    username: String,
+    ip_address: String,
+    success: bool,
+    failure_reason: Option<String>,
+    attempted_at: String
+}
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "UpsertLoginThrottleDb",
+    fields(kind, value, failures, last_failure_at, blocked_until)
+))]
+#[custom_model(model(
+    name = "RetrieveLoginThrottleDb",
+    fields(failures, last_failure_at, blocked_until)
+))]
+#[allow(dead_code)]
+pub struct LoginThrottle {
+    kind: String,
+    value: String,
+    failures: i64,
+    last_failure_at: String,
+    blocked_until: String
+}
+
+/// What the failures of a throttle are counted for
+#[derive(Clone, Copy)]
+pub enum LoginThrottleKind {
+    Username,
+    Ip
+}
+
+impl LoginThrottleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginThrottleKind::Username => "username",
+            LoginThrottleKind::Ip => "ip"
+        }
+    }
+
+    pub fn max_failures(&self) -> i64 {
+        let auth_config = &crate::config::get().auth;
+
+        match self {
+            LoginThrottleKind::Username => auth_config.login_max_failures_per_username,
+            LoginThrottleKind::Ip => auth_config.login_max_failures_per_ip
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum LoginFailureReason {
+    UnknownUser,
+    InvalidPassword,
+    /// The password was correct, but the user is not active
+    InactiveAccount,
+    InvalidMfaCode,
+    Throttled
+}
+
+impl LoginFailureReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginFailureReason::UnknownUser => "unknown_user",
+            LoginFailureReason::InvalidPassword => "invalid_password",
+            LoginFailureReason::InactiveAccount => "inactive_account",
+            LoginFailureReason::InvalidMfaCode => "invalid_mfa_code",
+            LoginFailureReason::Throttled => "throttled"
+        }
+    }
+
+    /// Throttled attempts are audited but do not extend the lockout, otherwise it could never expire while an
+    /// attacker keeps trying. Sign ins to inactive accounts had the right password, so they do not lock out its owner.
+    pub fn counts_as_failure(&self) -> bool {
+        !matches!(self, LoginFailureReason::Throttled | LoginFailureReason::InactiveAccount)
+    }
+}
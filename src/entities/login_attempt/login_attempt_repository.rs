@@ -0,0 +1,106 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::login_attempt::{CreateLoginAttemptDb, RetrieveLoginThrottleDb, UpsertLoginThrottleDb};
+
+pub struct LoginAttemptRepository {}
+
+impl LoginAttemptRepository {
+    pub fn new() -> LoginAttemptRepository {
+        LoginAttemptRepository {
+
+        }
+    }
+
+    pub async fn create_attempt(&self, tx: &mut SqliteConnection, attempt: &CreateLoginAttemptDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO LoginAttempt (username, ip_address, success, failure_reason, attempted_at)
+            VALUES($1, $2, $3, $4, $5)
+            "#,
+            attempt.username,
+            attempt.ip_address,
+            attempt.success,
+            attempt.failure_reason,
+            attempt.attempted_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_throttle(&self, tx: &mut SqliteConnection, kind: &str, value: &str) -> Result<Option<RetrieveLoginThrottleDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveLoginThrottleDb,
+            r#"
+            SELECT failures, last_failure_at, blocked_until
+            FROM LoginThrottle
+            WHERE kind = $1 AND value = $2
+            "#,
+            kind,
+            value
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn upsert_throttle(&self, tx: &mut SqliteConnection, throttle: &UpsertLoginThrottleDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO LoginThrottle (kind, value, failures, last_failure_at, blocked_until)
+            VALUES($1, $2, $3, $4, $5)
+            ON CONFLICT(kind, value) DO UPDATE SET
+                failures = excluded.failures,
+                last_failure_at = excluded.last_failure_at,
+                blocked_until = excluded.blocked_until
+            "#,
+            throttle.kind,
+            throttle.value,
+            throttle.failures,
+            throttle.last_failure_at,
+            throttle.blocked_until
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Removes one failure. The block is lifted when the remaining failures are below `max_failures`, since the
+    /// failure being removed was counted after checking that the value was not blocked.
+    pub async fn release_failure(&self, tx: &mut SqliteConnection, kind: &str, value: &str, max_failures: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE LoginThrottle
+            SET failures = failures - 1,
+                blocked_until = CASE WHEN failures - 1 < $3 THEN last_failure_at ELSE blocked_until END
+            WHERE kind = $1 AND value = $2 AND failures > 0
+            "#,
+            kind,
+            value,
+            max_failures
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_throttle(&self, tx: &mut SqliteConnection, kind: &str, value: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM LoginThrottle
+            WHERE kind = $1 AND value = $2
+            "#,
+            kind,
+            value
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::NaiveDateTime;
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{config, error::error::{AppError, AppErrorType}};
+
+use super::{login_attempt::{CreateLoginAttemptDb, LoginFailureReason, LoginThrottleKind, UpsertLoginThrottleDb}, login_attempt_repository::LoginAttemptRepository};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Audits sign in attempts and throttles consecutive failures per username and per client ip.
+///
+/// Every failure blocks the username for an exponentially growing delay (1s, 2s, 4s...) and, after
+/// `login_max_failures_per_username` failures, for the whole lockout duration. Ips are shared by many users behind
+/// the same network, so they are only locked once they reach `login_max_failures_per_ip`. Failures older than the
+/// lockout duration are forgotten.
+///
+/// Attempts are counted as failures when they are reserved, before the credentials are checked, so parallel attempts
+/// cannot all try them before the first failure is counted. The attempts that turn out not to be failures are released.
+pub struct LoginAttemptService {
+    db_pool: SqlitePool,
+    login_attempt_repository: LoginAttemptRepository
+}
+
+impl LoginAttemptService {
+    pub fn new(db_pool: SqlitePool, login_attempt_repository: LoginAttemptRepository) -> LoginAttemptService {
+        LoginAttemptService {
+            db_pool,
+            login_attempt_repository
+        }
+    }
+
+    /// Fails with `TooManyRequests` if the username or the ip are blocked, otherwise counts the attempt as a failure
+    /// until it is recorded. It must be called before checking the credentials, so blocked attempts do not get to try
+    /// them.
+    pub async fn reserve_attempt(&self, username: &str, ip_address: &str) -> Result<(), AppError> {
+        match self.reserve_unless_blocked(username, ip_address).await? {
+            Some(retry_after) => Err(AppError::new(
+                String::from("Too many failed sign in attempts, try again in $1 seconds"),
+                AppErrorType::TooManyRequests,
+                Some(vec![retry_after.to_string()])
+            )),
+            None => Ok(())
+        }
+    }
+
+    /// Checks the throttles and counts the failure in one transaction. Returns the seconds to wait when blocked.
+    #[executor(transaction)]
+    pub async fn reserve_unless_blocked(&self, username: &str, ip_address: &str) -> Result<Option<i64>, AppError> {
+        let now = chrono::Utc::now().naive_utc();
+
+        let mut blocked_until: Option<NaiveDateTime> = None;
+        for (kind, value) in [(LoginThrottleKind::Username, username), (LoginThrottleKind::Ip, ip_address)] {
+            if let Some(throttle) = self.login_attempt_repository.get_throttle(tx, kind.as_str(), value).await? {
+                let throttle_blocked_until = Self::parse_timestamp(&throttle.blocked_until)?;
+
+                if throttle_blocked_until > now {
+                    blocked_until = blocked_until.max(Some(throttle_blocked_until));
+                }
+            }
+        }
+
+        let blocked_until = match blocked_until {
+            Some(blocked_until) => blocked_until,
+            None => {
+                self.register_failure(tx, LoginThrottleKind::Username, username, now).await?;
+                self.register_failure(tx, LoginThrottleKind::Ip, ip_address, now).await?;
+
+                return Ok(None);
+            }
+        };
+
+        self.login_attempt_repository.create_attempt(tx, &CreateLoginAttemptDb {
+            username: username.to_string(),
+            ip_address: ip_address.to_string(),
+            success: false,
+            failure_reason: Some(LoginFailureReason::Throttled.as_str().to_string()),
+            attempted_at: now.to_string()
+        }).await?;
+
+        Ok(Some((blocked_until - now).num_seconds() + 1))
+    }
+
+    /// Records a reserved attempt that failed. Its failure was counted when it was reserved, and is released when the
+    /// reason does not count.
+    #[executor(transaction)]
+    pub async fn record_failure(&self, username: &str, ip_address: &str, reason: LoginFailureReason) -> Result<(), AppError> {
+        self.login_attempt_repository.create_attempt(tx, &CreateLoginAttemptDb {
+            username: username.to_string(),
+            ip_address: ip_address.to_string(),
+            success: false,
+            failure_reason: Some(reason.as_str().to_string()),
+            attempted_at: chrono::Utc::now().naive_utc().to_string()
+        }).await?;
+
+        if reason.counts_as_failure() {
+            return Ok(());
+        }
+
+        self.release_attempt_executor(tx, username, ip_address).await
+    }
+
+    /// Records a reserved attempt that succeeded and clears the failures of the username. Previous failures of the ip
+    /// are kept, otherwise an attacker could reset them by signing in to an account of their own.
+    #[executor(transaction)]
+    pub async fn record_success(&self, username: &str, ip_address: &str) -> Result<(), AppError> {
+        self.login_attempt_repository.create_attempt(tx, &CreateLoginAttemptDb {
+            username: username.to_string(),
+            ip_address: ip_address.to_string(),
+            success: true,
+            failure_reason: None,
+            attempted_at: chrono::Utc::now().naive_utc().to_string()
+        }).await?;
+
+        self.login_attempt_repository.delete_throttle(tx, LoginThrottleKind::Username.as_str(), username).await?;
+        self.login_attempt_repository.release_failure(tx, LoginThrottleKind::Ip.as_str(), ip_address, LoginThrottleKind::Ip.max_failures()).await
+    }
+
+    /// Stops counting a reserved attempt as a failure, for attempts that did not get to fail
+    #[executor]
+    pub async fn release_attempt(&self, username: &str, ip_address: &str) -> Result<(), AppError> {
+        for (kind, value) in [(LoginThrottleKind::Username, username), (LoginThrottleKind::Ip, ip_address)] {
+            self.login_attempt_repository.release_failure(tx, kind.as_str(), value, kind.max_failures()).await?;
+        }
+
+        Ok(())
+    }
+
+    #[executor]
+    pub async fn unlock_username(&self, username: &str) -> Result<(), AppError> {
+        self.login_attempt_repository.delete_throttle(tx, LoginThrottleKind::Username.as_str(), username).await
+    }
+
+    async fn register_failure(&self, tx: &mut SqliteConnection, kind: LoginThrottleKind, value: &str, now: NaiveDateTime) -> Result<(), AppError> {
+        let lockout_duration = config::get().auth.login_lockout_duration;
+
+        let failures = match self.login_attempt_repository.get_throttle(tx, kind.as_str(), value).await? {
+            Some(throttle) if Self::parse_timestamp(&throttle.last_failure_at)? + chrono::Duration::seconds(lockout_duration) > now => {
+                throttle.failures + 1
+            },
+            _ => 1
+        };
+
+        let blocked_for = if failures >= kind.max_failures() {
+            lockout_duration
+        } else {
+            match kind {
+                LoginThrottleKind::Username => 2i64.saturating_pow((failures - 1) as u32).min(lockout_duration),
+                LoginThrottleKind::Ip => 0
+            }
+        };
+
+        self.login_attempt_repository.upsert_throttle(tx, &UpsertLoginThrottleDb {
+            kind: kind.as_str().to_string(),
+            value: value.to_string(),
+            failures,
+            last_failure_at: now.to_string(),
+            blocked_until: (now + chrono::Duration::seconds(blocked_for)).to_string()
+        }).await
+    }
+
+    fn parse_timestamp(timestamp: &str) -> Result<NaiveDateTime, AppError> {
+        NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+            .map_err(AppError::internal_from_generic)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod login_attempt;
+pub mod login_attempt_service;
+pub mod login_attempt_repository;
\ No newline at end of file
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::user::custom_dto::auth_dto::AuthDto;
+
+#[derive(Serialize)]
+pub struct MfaEnrollmentDto {
+    pub secret: String,
+    pub otpauth_uri: String
+}
+
+#[derive(Deserialize)]
+pub struct MfaCodeDto {
+    pub code: String
+}
+
+/// Either a code of the authenticator app or one of the recovery codes
+#[derive(Deserialize)]
+pub struct MfaVerificationDto {
+    pub code: Option<String>,
+    pub recovery_code: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct MfaConfirmationDto {
+    pub recovery_codes: Vec<String>,
+    /// Present when the enrollment completed a sign in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthDto>
+}
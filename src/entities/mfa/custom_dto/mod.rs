@@ -0,0 +1 @@
+pub mod mfa_dto;
\ No newline at end of file
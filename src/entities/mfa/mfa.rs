@@ -0,0 +1,37 @@
+use macros::DeriveCustomModel;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateRecoveryCodeDb",
+    fields(user_id, code_hash, created_at)
+))]
+#[allow(dead_code)]
+pub struct RecoveryCode {
+    id: i64,
+    user_id: i64,
+    code_hash: String,
+    created_at: String,
+    used_at: Option<String>
+}
+
+pub struct MfaStateDb {
+    /// Encrypted, see `MfaService`
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool
+}
+
+pub struct TotpSecretDb {
+    pub user_id: i64,
+    pub totp_secret: String
+}
+
+impl RecoveryCode {
+    /// Recovery codes are shown grouped with dashes, but users may type them without them or in upper case
+    pub fn normalize(code: &str) -> String {
+        code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+}
@@ -0,0 +1,48 @@
+use actix_web::{web, HttpRequest, Responder};
+
+use crate::{auth::jwt::{MfaEnrollmentClaims, MfaPendingClaims, SessionClaims}, service, util::{json_response::json_response, request::client_ip}};
+
+use super::custom_dto::mfa_dto::{MfaCodeDto, MfaVerificationDto};
+
+/// Routes are nested in the `/auth` scope
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/mfa")
+            .route("/enroll", web::post().to(enroll))
+            .route("/confirm", web::post().to(confirm))
+            .route("/verify", web::post().to(verify))
+            .route("/disable", web::post().to(disable))
+    );
+}
+
+pub async fn enroll(claims: MfaEnrollmentClaims) -> impl Responder {
+    let MfaEnrollmentClaims(claims) = claims;
+
+    let enrollment = service::get().auth().enroll_mfa(claims.sub).await;
+
+    json_response(&enrollment)
+}
+
+pub async fn confirm(code: web::Json<MfaCodeDto>, claims: MfaEnrollmentClaims) -> impl Responder {
+    let MfaEnrollmentClaims(claims) = claims;
+
+    let confirmation = service::get().auth().confirm_mfa(&claims, code.into_inner()).await;
+
+    json_response(&confirmation)
+}
+
+pub async fn verify(req: HttpRequest, verification: web::Json<MfaVerificationDto>, claims: MfaPendingClaims) -> impl Responder {
+    let MfaPendingClaims(claims) = claims;
+
+    let auth = service::get().auth().verify_mfa(&claims, verification.into_inner(), &client_ip(&req)).await;
+
+    json_response(&auth)
+}
+
+pub async fn disable(verification: web::Json<MfaVerificationDto>, claims: SessionClaims) -> impl Responder {
+    let SessionClaims(claims) = claims;
+
+    let disabled = service::get().auth().disable_mfa(claims.sub, verification.into_inner()).await;
+
+    json_response(&disabled)
+}
@@ -0,0 +1,174 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::mfa::{CreateRecoveryCodeDb, MfaStateDb, TotpSecretDb};
+
+pub struct MfaRepository {}
+
+impl MfaRepository {
+    pub fn new() -> MfaRepository {
+        MfaRepository {
+
+        }
+    }
+
+    pub async fn get_mfa_state(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Option<MfaStateDb>, AppError> {
+        sqlx::query_as!(
+            MfaStateDb,
+            r#"
+            SELECT totp_secret, totp_enabled as "totp_enabled: bool"
+            FROM AppUser
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn set_pending_totp_secret(&self, tx: &mut SqliteConnection, user_id: i64, secret: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET totp_secret = $1, totp_enabled = 0, totp_last_used_step = NULL
+            WHERE id = $2
+            "#,
+            secret,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Secrets stored before they were encrypted at rest, which have no `v1:` prefix
+    pub async fn get_plaintext_totp_secrets(&self, tx: &mut SqliteConnection) -> Result<Vec<TotpSecretDb>, AppError> {
+        sqlx::query_as!(
+            TotpSecretDb,
+            r#"
+            SELECT id as "user_id!: i64", totp_secret as "totp_secret!: String"
+            FROM AppUser
+            WHERE totp_secret IS NOT NULL AND totp_secret NOT LIKE 'v1:%'
+            "#
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_totp_secret(&self, tx: &mut SqliteConnection, user_id: i64, secret: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET totp_secret = $1
+            WHERE id = $2
+            "#,
+            secret,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn enable_totp(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET totp_enabled = 1
+            WHERE id = $1 AND totp_secret IS NOT NULL
+            "#,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn disable_totp(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET totp_secret = NULL, totp_enabled = 0, totp_last_used_step = NULL
+            WHERE id = $1
+            "#,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Records the time step of an accepted code. Returns `false` if that step (or a later one) was already used,
+    /// which means the code is being replayed.
+    pub async fn use_totp_step(&self, tx: &mut SqliteConnection, user_id: i64, step: i64) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET totp_last_used_step = $1
+            WHERE id = $2 AND (totp_last_used_step IS NULL OR totp_last_used_step < $1)
+            "#,
+            step,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn create_recovery_code(&self, tx: &mut SqliteConnection, recovery_code: &CreateRecoveryCodeDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO RecoveryCode (user_id, code_hash, created_at)
+            VALUES($1, $2, $3)
+            "#,
+            recovery_code.user_id,
+            recovery_code.code_hash,
+            recovery_code.created_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_recovery_codes(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM RecoveryCode
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the code does not exist or was already used.
+    pub async fn use_recovery_code(&self, tx: &mut SqliteConnection, user_id: i64, code_hash: &str, used_at: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE RecoveryCode
+            SET used_at = $1
+            WHERE user_id = $2 AND code_hash = $3 AND used_at IS NULL
+            "#,
+            used_at,
+            user_id,
+            code_hash
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(to_app_error)
+    }
+}
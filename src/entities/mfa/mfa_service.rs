@@ -0,0 +1,223 @@
+use macros::executor;
+use rand::{rngs::OsRng, RngCore};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{auth::totp, config, error::error::{AppError, AppErrorType}, util::crypto::{decrypt, derive_key, encrypt, hash_secret_token}};
+
+use super::{custom_dto::mfa_dto::{MfaEnrollmentDto, MfaVerificationDto}, mfa::{CreateRecoveryCodeDb, MfaStateDb, RecoveryCode}, mfa_repository::MfaRepository};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Prefix of the encrypted TOTP secrets. Secrets stored before they were encrypted have none
+const ENCRYPTED_SECRET_PREFIX: &str = "v1:";
+const SECRET_KEY_PURPOSE: &str = "totp-secret";
+
+/// TOTP secrets are encrypted at rest with a key derived from `MFA_ENCRYPTION_KEY`, bound to the user they belong to.
+pub struct MfaService {
+    db_pool: SqlitePool,
+    mfa_repository: MfaRepository
+}
+
+impl MfaService {
+    pub fn new(db_pool: SqlitePool, mfa_repository: MfaRepository) -> MfaService {
+        MfaService {
+            db_pool,
+            mfa_repository
+        }
+    }
+
+    #[executor]
+    pub async fn is_mfa_enabled(&self, user_id: i64) -> Result<bool, AppError> {
+        Ok(self.get_mfa_state(tx, user_id).await?.totp_enabled)
+    }
+
+    /// Generates a new TOTP secret for the user. It is not required on sign in until it is confirmed with a valid
+    /// code through `confirm`.
+    #[executor]
+    pub async fn enroll(&self, user_id: i64, account_name: &str) -> Result<MfaEnrollmentDto, AppError> {
+        if self.get_mfa_state(tx, user_id).await?.totp_enabled {
+            return Err(AppError::new(
+                String::from("Two-factor authentication is already enabled"),
+                AppErrorType::Conflict,
+                None
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        self.mfa_repository.set_pending_totp_secret(tx, user_id, &Self::encrypt_secret(user_id, &secret)).await?;
+
+        Ok(MfaEnrollmentDto {
+            otpauth_uri: totp::otpauth_uri(&config::get().auth.mfa_issuer, account_name, &secret),
+            secret
+        })
+    }
+
+    /// Enables two-factor authentication once the user proves the authenticator app is set up, and returns a new
+    /// set of recovery codes. The raw codes are only available here.
+    #[executor]
+    pub async fn confirm(&self, user_id: i64, code: &str) -> Result<Vec<String>, AppError> {
+        let state = self.get_mfa_state(tx, user_id).await?;
+
+        if state.totp_enabled {
+            return Err(AppError::new(
+                String::from("Two-factor authentication is already enabled"),
+                AppErrorType::Conflict,
+                None
+            ));
+        }
+
+        let secret = match state.totp_secret {
+            Some(secret) => Self::decrypt_secret(user_id, &secret)?,
+            None => {
+                return Err(AppError::new(
+                    String::from("Two-factor authentication enrollment has not been started"),
+                    AppErrorType::BadRequest,
+                    None
+                ));
+            }
+        };
+
+        self.check_totp_code(tx, user_id, &secret, code).await?;
+        self.mfa_repository.enable_totp(tx, user_id).await?;
+
+        self.regenerate_recovery_codes(tx, user_id).await
+    }
+
+    /// Checks the second factor of a user, either a TOTP code or an unused recovery code.
+    #[executor]
+    pub async fn verify(&self, user_id: i64, verification: &MfaVerificationDto) -> Result<(), AppError> {
+        let state = self.get_mfa_state(tx, user_id).await?;
+
+        let secret = match (state.totp_enabled, state.totp_secret) {
+            (true, Some(secret)) => Self::decrypt_secret(user_id, &secret)?,
+            _ => {
+                return Err(AppError::new(
+                    String::from("Two-factor authentication is not enabled"),
+                    AppErrorType::BadRequest,
+                    None
+                ));
+            }
+        };
+
+        match (&verification.code, &verification.recovery_code) {
+            (Some(code), None) => self.check_totp_code(tx, user_id, &secret, code).await,
+            (None, Some(recovery_code)) => {
+                let code_hash = hash_secret_token(&RecoveryCode::normalize(recovery_code));
+                let now = chrono::Utc::now().naive_utc().to_string();
+
+                if self.mfa_repository.use_recovery_code(tx, user_id, &code_hash, &now).await? {
+                    Ok(())
+                } else {
+                    Err(Self::invalid_code())
+                }
+            },
+            _ => Err(AppError::new(
+                String::from("Either a code or a recovery code must be provided"),
+                AppErrorType::BadRequest,
+                None
+            ))
+        }
+    }
+
+    #[executor]
+    pub async fn disable(&self, user_id: i64, verification: &MfaVerificationDto) -> Result<(), AppError> {
+        self.verify_executor(tx, user_id, verification).await?;
+
+        self.mfa_repository.disable_totp(tx, user_id).await?;
+        self.mfa_repository.delete_recovery_codes(tx, user_id).await
+    }
+
+    /// Encrypts the secrets stored before they were encrypted at rest. It runs on startup.
+    #[executor]
+    pub async fn encrypt_plaintext_totp_secrets(&self) -> Result<(), AppError> {
+        for stored in self.mfa_repository.get_plaintext_totp_secrets(tx).await? {
+            let secret = Self::encrypt_secret(stored.user_id, &stored.totp_secret);
+            self.mfa_repository.update_totp_secret(tx, stored.user_id, &secret).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_mfa_state(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<MfaStateDb, AppError> {
+        match self.mfa_repository.get_mfa_state(tx, user_id).await? {
+            Some(state) => Ok(state),
+            None => Err(AppError::new(
+                String::from(r#"User with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![user_id.to_string()])
+            ))
+        }
+    }
+
+    async fn check_totp_code(&self, tx: &mut SqliteConnection, user_id: i64, secret: &str, code: &str) -> Result<(), AppError> {
+        let step = match totp::verify_code(secret, code, chrono::Utc::now().timestamp()) {
+            Some(step) => step,
+            None => return Err(Self::invalid_code())
+        };
+
+        // A code can only be used once, even if it is still inside its time window
+        if !self.mfa_repository.use_totp_step(tx, user_id, step).await? {
+            return Err(Self::invalid_code());
+        }
+
+        Ok(())
+    }
+
+    async fn regenerate_recovery_codes(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Vec<String>, AppError> {
+        self.mfa_repository.delete_recovery_codes(tx, user_id).await?;
+
+        let now = chrono::Utc::now().naive_utc().to_string();
+        let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+
+        for _ in 0..RECOVERY_CODE_COUNT {
+            let mut bytes = [0u8; 8];
+            OsRng.fill_bytes(&mut bytes);
+
+            let raw = hex::encode(bytes);
+            let code = format!("{}-{}-{}-{}", &raw[0..4], &raw[4..8], &raw[8..12], &raw[12..16]);
+
+            self.mfa_repository.create_recovery_code(tx, &CreateRecoveryCodeDb {
+                user_id,
+                code_hash: hash_secret_token(&RecoveryCode::normalize(&code)),
+                created_at: now.clone()
+            }).await?;
+
+            codes.push(code);
+        }
+
+        Ok(codes)
+    }
+
+    fn encryption_key() -> [u8; 32] {
+        derive_key(&config::get().auth.mfa_encryption_key, SECRET_KEY_PURPOSE)
+    }
+
+    fn encrypt_secret(user_id: i64, secret: &str) -> String {
+        let encrypted = encrypt(&Self::encryption_key(), secret.as_bytes(), &user_id.to_be_bytes());
+
+        format!("{}{}", ENCRYPTED_SECRET_PREFIX, encrypted)
+    }
+
+    fn decrypt_secret(user_id: i64, stored: &str) -> Result<String, AppError> {
+        // Not encrypted yet, see `encrypt_plaintext_totp_secrets`
+        let encrypted = match stored.strip_prefix(ENCRYPTED_SECRET_PREFIX) {
+            Some(encrypted) => encrypted,
+            None => return Ok(stored.to_string())
+        };
+
+        decrypt(&Self::encryption_key(), encrypted, &user_id.to_be_bytes())
+            .and_then(|secret| String::from_utf8(secret).ok())
+            .ok_or_else(|| AppError::new(
+                String::from("The TOTP secret cannot be decrypted, the MFA encryption key may have changed"),
+                AppErrorType::InternalServerError,
+                None
+            ))
+    }
+
+    fn invalid_code() -> AppError {
+        AppError::new(
+            String::from("Invalid authentication code"),
+            AppErrorType::Unauthorized,
+            None
+        )
+    }
+}
@@ -0,0 +1,5 @@
+pub mod mfa;
+pub mod mfa_service;
+pub mod mfa_repository;
+pub mod mfa_controller;
+pub mod custom_dto;
\ No newline at end of file
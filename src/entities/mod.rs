@@ -1,4 +1,15 @@
 pub mod user;
 pub mod company;
 pub mod payroll;
-pub mod permission;
\ No newline at end of file
+pub mod permission;
+pub mod session;
+pub mod password_reset;
+pub mod mfa;
+pub mod login_attempt;
+pub mod api_key;
+pub mod oidc;
+pub mod impersonation;
+pub mod invitation;
+pub mod role;
+pub mod company_grant;
+pub mod department;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod oidc_dto;
\ No newline at end of file
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+pub struct OidcAuthorizationDto {
+    /// Where the browser must be sent to sign in with the identity provider
+    pub authorization_url: String
+}
+
+/// Query parameters received by the redirect url
+#[derive(Deserialize)]
+pub struct OidcCallbackDto {
+    pub code: String,
+    pub state: String
+}
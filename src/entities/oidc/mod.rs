@@ -0,0 +1,5 @@
+pub mod oidc;
+pub mod oidc_service;
+pub mod oidc_repository;
+pub mod oidc_controller;
+pub mod custom_dto;
\ No newline at end of file
@@ -0,0 +1,71 @@
+use macros::DeriveCustomModel;
+use serde::Deserialize;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateOidcLoginStateDb",
+    fields(state_hash, nonce, code_verifier, created_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveOidcLoginStateDb",
+    fields(id, nonce, code_verifier)
+))]
+#[allow(dead_code)]
+pub struct OidcLoginState {
+    id: i64,
+    state_hash: String,
+    nonce: String,
+    code_verifier: String,
+    created_at: String,
+    expires_at: String,
+    used_at: Option<String>
+}
+
+impl OidcLoginState {
+    /// Seconds the user has to complete the sign in at the identity provider
+    pub const TTL: i64 = 600;
+}
+
+/// Fields of the discovery document used by the authorization code flow
+#[derive(Deserialize, Clone)]
+pub struct OidcProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String
+}
+
+#[derive(Deserialize)]
+pub struct OidcTokenResponse {
+    pub id_token: String
+}
+
+#[derive(Deserialize)]
+pub struct OidcIdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>
+}
+
+/// User authenticated by the identity provider
+pub struct OidcIdentity {
+    pub subject: String,
+    /// Only set if the identity provider verified it
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>
+}
+
+impl OidcIdTokenClaims {
+    pub fn to_oidc_identity(self) -> OidcIdentity {
+        OidcIdentity {
+            subject: self.sub,
+            email: if self.email_verified == Some(true) { self.email } else { None },
+            name: self.name,
+            preferred_username: self.preferred_username
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use actix_web::{web, Responder};
+
+use crate::{service, util::json_response::json_response};
+
+use super::custom_dto::oidc_dto::OidcCallbackDto;
+
+/// Routes are nested in the `/auth` scope
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/oidc")
+            .route("/login", web::get().to(login))
+            .route("/callback", web::post().to(callback))
+    );
+}
+
+pub async fn login() -> impl Responder {
+    let authorization = service::get().oidc().create_authorization_url().await;
+
+    json_response(&authorization)
+}
+
+pub async fn callback(callback: web::Json<OidcCallbackDto>) -> impl Responder {
+    let auth = service::get().auth().sign_in_with_oidc(callback.into_inner()).await;
+
+    json_response(&auth)
+}
@@ -0,0 +1,67 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::oidc::{CreateOidcLoginStateDb, RetrieveOidcLoginStateDb};
+
+pub struct OidcRepository {}
+
+impl OidcRepository {
+    pub fn new() -> OidcRepository {
+        OidcRepository {
+
+        }
+    }
+
+    pub async fn create_login_state(&self, tx: &mut SqliteConnection, login_state: &CreateOidcLoginStateDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO OidcLoginState (state_hash, nonce, code_verifier, created_at, expires_at)
+            VALUES($1, $2, $3, $4, $5)
+            "#,
+            login_state.state_hash,
+            login_state.nonce,
+            login_state.code_verifier,
+            login_state.created_at,
+            login_state.expires_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_valid_login_state_by_hash(&self, tx: &mut SqliteConnection, state_hash: &str, now: &str) -> Result<Option<RetrieveOidcLoginStateDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveOidcLoginStateDb,
+            r#"
+            SELECT id as "id!: i64", nonce, code_verifier
+            FROM OidcLoginState
+            WHERE state_hash = $1 AND used_at IS NULL AND expires_at > $2
+            LIMIT 1
+            "#,
+            state_hash,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the state was already used by a concurrent request.
+    pub async fn mark_login_state_used(&self, tx: &mut SqliteConnection, login_state_id: i64, now: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE OidcLoginState
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL
+            "#,
+            now,
+            login_state_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+}
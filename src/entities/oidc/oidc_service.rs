@@ -0,0 +1,482 @@
+use std::{sync::RwLock, time::{Duration, Instant}};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use macros::executor;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{config::{self, OidcConfig}, error::error::{AppError, AppErrorType}, util::crypto::{generate_secret_token, hash_secret_token}};
+
+use super::{custom_dto::oidc_dto::{OidcAuthorizationDto, OidcCallbackDto}, oidc::{CreateOidcLoginStateDb, OidcIdTokenClaims, OidcIdentity, OidcLoginState, OidcProviderMetadata, OidcTokenResponse, RetrieveOidcLoginStateDb}, oidc_repository::OidcRepository};
+
+/// Authorization code flow with PKCE against the configured identity provider.
+///
+/// The state, nonce and code verifier of each sign in are kept in the database until the user comes back from the
+/// identity provider, so any instance can handle the callback. The discovery document and the keys of the identity
+/// provider are cached for `CACHE_TTL`.
+pub struct OidcService {
+    db_pool: SqlitePool,
+    oidc_repository: OidcRepository,
+    http_client: reqwest::Client,
+    metadata: RwLock<Option<CachedResponse<OidcProviderMetadata>>>,
+    jwks: RwLock<Option<CachedResponse<JwkSet>>>
+}
+
+struct CachedResponse<T> {
+    value: T,
+    fetched_at: Instant
+}
+
+impl OidcService {
+    const CACHE_TTL: Duration = Duration::from_secs(3600);
+    /// Requests to the identity provider are made while the user waits for the sign in
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(db_pool: SqlitePool, oidc_repository: OidcRepository) -> OidcService {
+        OidcService {
+            db_pool,
+            oidc_repository,
+            http_client: reqwest::Client::builder()
+                .timeout(Self::REQUEST_TIMEOUT)
+                .build()
+                .expect("valid http client"),
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None)
+        }
+    }
+
+    pub async fn create_authorization_url(&self) -> Result<OidcAuthorizationDto, AppError> {
+        let oidc_config = Self::get_config()?;
+        let metadata = self.get_provider_metadata(oidc_config).await?;
+
+        let state = generate_secret_token();
+        let nonce = generate_secret_token();
+        let code_verifier = generate_secret_token();
+
+        self.create_login_state(&state, &nonce, &code_verifier).await?;
+
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let authorization_url = reqwest::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", oidc_config.client_id.as_str()),
+                ("redirect_uri", oidc_config.redirect_url.as_str()),
+                ("scope", oidc_config.scopes.as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256")
+            ]
+        )
+        .map_err(AppError::internal_from_generic)?;
+
+        Ok(OidcAuthorizationDto {
+            authorization_url: authorization_url.to_string()
+        })
+    }
+
+    /// Exchanges the authorization code and returns the identity of the validated id token.
+    pub async fn authenticate(&self, callback: OidcCallbackDto) -> Result<OidcIdentity, AppError> {
+        let oidc_config = Self::get_config()?;
+
+        let login_state = self.consume_login_state(&callback.state).await?;
+
+        self.complete_authentication(oidc_config, &callback.code, &login_state).await
+    }
+
+    async fn complete_authentication(&self, oidc_config: &OidcConfig, code: &str, login_state: &RetrieveOidcLoginStateDb) -> Result<OidcIdentity, AppError> {
+        let metadata = self.get_provider_metadata(oidc_config).await?;
+        let id_token = self.exchange_code(oidc_config, &metadata, code, &login_state.code_verifier).await?;
+        let claims = self.validate_id_token(oidc_config, &metadata, &id_token).await?;
+
+        if claims.nonce.as_deref() != Some(login_state.nonce.as_str()) {
+            return Err(Self::invalid_id_token());
+        }
+
+        Ok(claims.to_oidc_identity())
+    }
+
+    #[executor]
+    pub async fn create_login_state(&self, state: &str, nonce: &str, code_verifier: &str) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::seconds(OidcLoginState::TTL))
+            .expect("valid timestamp");
+
+        self.oidc_repository.create_login_state(tx, &CreateOidcLoginStateDb {
+            state_hash: hash_secret_token(state),
+            nonce: nonce.to_string(),
+            code_verifier: code_verifier.to_string(),
+            created_at: now.naive_utc().to_string(),
+            expires_at: expires_at.naive_utc().to_string()
+        }).await
+    }
+
+    /// Each state can only complete one sign in
+    #[executor]
+    pub async fn consume_login_state(&self, state: &str) -> Result<RetrieveOidcLoginStateDb, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        let login_state = match self.oidc_repository.get_valid_login_state_by_hash(tx, &hash_secret_token(state), &now).await? {
+            Some(login_state) => login_state,
+            None => return Err(Self::invalid_state())
+        };
+
+        if !self.oidc_repository.mark_login_state_used(tx, login_state.id, &now).await? {
+            return Err(Self::invalid_state());
+        }
+
+        Ok(login_state)
+    }
+
+    async fn get_provider_metadata(&self, oidc_config: &OidcConfig) -> Result<OidcProviderMetadata, AppError> {
+        if let Some(metadata) = Self::get_cached(&self.metadata) {
+            return Ok(metadata);
+        }
+
+        let metadata: OidcProviderMetadata = self.fetch_json(&format!("{}/.well-known/openid-configuration", oidc_config.issuer)).await?;
+
+        if metadata.issuer.trim_end_matches('/') != oidc_config.issuer {
+            return Err(AppError::new(
+                String::from("The issuer of the identity provider does not match the configured one: $1"),
+                AppErrorType::InternalServerError,
+                Some(vec![metadata.issuer])
+            ));
+        }
+
+        Self::set_cached(&self.metadata, metadata.clone());
+
+        Ok(metadata)
+    }
+
+    /// Keys of the identity provider. They are fetched again when `refresh` is set, for tokens signed with a key that
+    /// is not cached yet.
+    async fn get_jwks(&self, metadata: &OidcProviderMetadata, refresh: bool) -> Result<JwkSet, AppError> {
+        if !refresh {
+            if let Some(jwks) = Self::get_cached(&self.jwks) {
+                return Ok(jwks);
+            }
+        }
+
+        let jwks: JwkSet = self.fetch_json(&metadata.jwks_uri).await?;
+
+        Self::set_cached(&self.jwks, jwks.clone());
+
+        Ok(jwks)
+    }
+
+    async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(AppError::internal_from_generic)?
+            .json()
+            .await
+            .map_err(AppError::internal_from_generic)
+    }
+
+    fn get_cached<T: Clone>(cache: &RwLock<Option<CachedResponse<T>>>) -> Option<T> {
+        let cache = cache.read().ok()?;
+
+        cache.as_ref()
+            .filter(|cached| cached.fetched_at.elapsed() < Self::CACHE_TTL)
+            .map(|cached| cached.value.clone())
+    }
+
+    fn set_cached<T>(cache: &RwLock<Option<CachedResponse<T>>>, value: T) {
+        if let Ok(mut cache) = cache.write() {
+            *cache = Some(CachedResponse {
+                value,
+                fetched_at: Instant::now()
+            });
+        }
+    }
+
+    async fn exchange_code(&self, oidc_config: &OidcConfig, metadata: &OidcProviderMetadata, code: &str, code_verifier: &str) -> Result<String, AppError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", oidc_config.redirect_url.as_str()),
+            ("client_id", oidc_config.client_id.as_str()),
+            ("code_verifier", code_verifier)
+        ];
+
+        if let Some(client_secret) = &oidc_config.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+
+        let response = self.http_client
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(AppError::internal_from_generic)?;
+
+        if !response.status().is_success() {
+            return Err(AppError::new(
+                String::from("Invalid authorization code"),
+                AppErrorType::Unauthorized,
+                None
+            ));
+        }
+
+        let token_response: OidcTokenResponse = response
+            .json()
+            .await
+            .map_err(AppError::internal_from_generic)?;
+
+        Ok(token_response.id_token)
+    }
+
+    async fn validate_id_token(&self, oidc_config: &OidcConfig, metadata: &OidcProviderMetadata, id_token: &str) -> Result<OidcIdTokenClaims, AppError> {
+        let header = decode_header(id_token).map_err(|_| Self::invalid_id_token())?;
+
+        // Symmetric algorithms would verify the token with the client secret, which is not a proof of the issuer
+        if !matches!(
+            header.alg,
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 |
+            Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 |
+            Algorithm::ES256 | Algorithm::ES384 | Algorithm::EdDSA
+        ) {
+            return Err(Self::invalid_id_token());
+        }
+
+        let mut jwks = self.get_jwks(metadata, false).await?;
+
+        // The identity provider may have rotated its keys since they were cached
+        if header.kid.as_ref().is_some_and(|kid| jwks.find(kid).is_none()) {
+            jwks = self.get_jwks(metadata, true).await?;
+        }
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None
+        };
+
+        let decoding_key = match jwk {
+            Some(jwk) => DecodingKey::from_jwk(jwk).map_err(|_| Self::invalid_id_token())?,
+            None => return Err(Self::invalid_id_token())
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&oidc_config.client_id]);
+
+        decode::<OidcIdTokenClaims>(id_token, &decoding_key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| Self::invalid_id_token())
+    }
+
+    fn get_config() -> Result<&'static OidcConfig, AppError> {
+        match &config::get().oidc {
+            Some(oidc_config) => Ok(oidc_config),
+            None => Err(AppError::new(
+                String::from("Single sign-on is not enabled"),
+                AppErrorType::NotFound,
+                None
+            ))
+        }
+    }
+
+    fn invalid_state() -> AppError {
+        AppError::new(
+            String::from("Invalid or expired single sign-on state"),
+            AppErrorType::BadRequest,
+            None
+        )
+    }
+
+    fn invalid_id_token() -> AppError {
+        AppError::new(
+            String::from("Invalid id token"),
+            AppErrorType::Unauthorized,
+            None
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, net::TcpListener, sync::atomic::{AtomicUsize, Ordering}};
+
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use ed25519_dalek::{pkcs8::{spki::der::pem::LineEnding, EncodePrivateKey}, SigningKey};
+    use jsonwebtoken::{encode, jwk::{AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, OctetKeyPairParameters, OctetKeyPairType}, EncodingKey, Header};
+    use serde_json::json;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "payroll-manager";
+    const KEY_ID: &str = "mock-key";
+
+    /// Identity provider that signs its id tokens with a fixed Ed25519 key. The nonce of each id token is the
+    /// authorization code it was exchanged for.
+    struct MockIdp {
+        issuer: String,
+        signing_key: SigningKey,
+        discovery_requests: AtomicUsize,
+        jwks_requests: AtomicUsize
+    }
+
+    async fn discovery(idp: web::Data<MockIdp>) -> HttpResponse {
+        idp.discovery_requests.fetch_add(1, Ordering::SeqCst);
+
+        HttpResponse::Ok().json(json!({
+            "issuer": idp.issuer,
+            "authorization_endpoint": format!("{}/authorize", idp.issuer),
+            "token_endpoint": format!("{}/token", idp.issuer),
+            "jwks_uri": format!("{}/jwks", idp.issuer)
+        }))
+    }
+
+    async fn jwks(idp: web::Data<MockIdp>) -> HttpResponse {
+        idp.jwks_requests.fetch_add(1, Ordering::SeqCst);
+
+        HttpResponse::Ok().json(JwkSet {
+            keys: vec![Jwk {
+                common: CommonParameters {
+                    key_id: Some(KEY_ID.to_string()),
+                    ..Default::default()
+                },
+                algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(idp.signing_key.verifying_key().as_bytes())
+                })
+            }]
+        })
+    }
+
+    async fn token(idp: web::Data<MockIdp>, form: web::Form<HashMap<String, String>>) -> HttpResponse {
+        let code = form.get("code").cloned().unwrap_or_default();
+
+        if form.get("code_verifier").map(String::as_str) != Some("verifier") || code.is_empty() {
+            return HttpResponse::BadRequest().finish();
+        }
+
+        let private_pem = idp.signing_key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(KEY_ID.to_string());
+
+        let claims = json!({
+            "iss": idp.issuer,
+            "aud": form.get("client_id"),
+            "sub": "subject",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": code,
+            "email": "employee@example.com",
+            "email_verified": true
+        });
+        let id_token = encode(&header, &claims, &EncodingKey::from_ed_pem(private_pem.as_bytes()).unwrap()).unwrap();
+
+        HttpResponse::Ok().json(json!({ "id_token": id_token }))
+    }
+
+    fn start_mock_idp() -> web::Data<MockIdp> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let idp = web::Data::new(MockIdp {
+            issuer: format!("http://{}", listener.local_addr().unwrap()),
+            signing_key: SigningKey::from_bytes(&[7; 32]),
+            discovery_requests: AtomicUsize::new(0),
+            jwks_requests: AtomicUsize::new(0)
+        });
+
+        let app_idp = idp.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_idp.clone())
+                .route("/.well-known/openid-configuration", web::get().to(discovery))
+                .route("/jwks", web::get().to(jwks))
+                .route("/token", web::post().to(token))
+        })
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+
+        actix_web::rt::spawn(server);
+
+        idp
+    }
+
+    fn oidc_config(issuer: &str, client_id: &str) -> OidcConfig {
+        OidcConfig {
+            issuer: issuer.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: None,
+            redirect_url: String::from("http://localhost/callback"),
+            scopes: String::from("openid email"),
+            provisioning: None
+        }
+    }
+
+    fn login_state(nonce: &str) -> RetrieveOidcLoginStateDb {
+        RetrieveOidcLoginStateDb {
+            id: 1,
+            nonce: nonce.to_string(),
+            code_verifier: String::from("verifier")
+        }
+    }
+
+    fn oidc_service() -> OidcService {
+        OidcService::new(SqlitePool::connect_lazy("sqlite::memory:").unwrap(), OidcRepository::new())
+    }
+
+    #[actix_web::test]
+    async fn authenticates_and_caches_the_provider() {
+        let idp = start_mock_idp();
+        let config = oidc_config(&idp.issuer, CLIENT_ID);
+        let service = oidc_service();
+
+        for code in ["first-code", "second-code"] {
+            let identity = service.complete_authentication(&config, code, &login_state(code)).await
+                .unwrap_or_else(|_| panic!("the sign in should succeed"));
+
+            assert_eq!(identity.subject, "subject");
+            assert_eq!(identity.email.as_deref(), Some("employee@example.com"));
+        }
+
+        assert_eq!(idp.discovery_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(idp.jwks_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn rejects_id_tokens_with_another_nonce() {
+        let idp = start_mock_idp();
+        let config = oidc_config(&idp.issuer, CLIENT_ID);
+
+        let result = oidc_service().complete_authentication(&config, "code", &login_state("other-nonce")).await;
+
+        assert!(result.is_err());
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_codes() {
+        let idp = start_mock_idp();
+        let config = oidc_config(&idp.issuer, CLIENT_ID);
+        let mut state = login_state("code");
+        state.code_verifier = String::from("other-verifier");
+
+        let result = oidc_service().complete_authentication(&config, "code", &state).await;
+
+        assert!(result.is_err());
+    }
+
+    #[actix_web::test]
+    async fn rejects_providers_with_another_issuer() {
+        let idp = start_mock_idp();
+        let config = oidc_config(&format!("{}/realm", idp.issuer), CLIENT_ID);
+
+        let result = oidc_service().complete_authentication(&config, "code", &login_state("code")).await;
+
+        assert!(result.is_err());
+        assert_eq!(idp.jwks_requests.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod password_reset;
+pub mod password_reset_service;
+pub mod password_reset_repository;
\ No newline at end of file
@@ -0,0 +1,20 @@
+use macros::DeriveCustomModel;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreatePasswordResetTokenDb",
+    fields(user_id, token_hash, created_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrievePasswordResetTokenDb",
+    fields(id, user_id)
+))]
+#[allow(dead_code)]
+pub struct PasswordResetToken {
+    id: i64,
+    user_id: i64,
+    token_hash: String,
+    created_at: String,
+    expires_at: String,
+    used_at: Option<String>
+}
@@ -0,0 +1,82 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::password_reset::{CreatePasswordResetTokenDb, RetrievePasswordResetTokenDb};
+
+pub struct PasswordResetRepository {}
+
+impl PasswordResetRepository {
+    pub fn new() -> PasswordResetRepository {
+        PasswordResetRepository {
+
+        }
+    }
+
+    pub async fn create_token(&self, tx: &mut SqliteConnection, token: &CreatePasswordResetTokenDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO PasswordResetToken (user_id, token_hash, created_at, expires_at)
+            VALUES($1, $2, $3, $4)
+            "#,
+            token.user_id,
+            token.token_hash,
+            token.created_at,
+            token.expires_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn invalidate_user_tokens(&self, tx: &mut SqliteConnection, user_id: i64, now: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE PasswordResetToken
+            SET used_at = $1
+            WHERE user_id = $2 AND used_at IS NULL
+            "#,
+            now,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_valid_token_by_hash(&self, tx: &mut SqliteConnection, token_hash: &str, now: &str) -> Result<Option<RetrievePasswordResetTokenDb>, AppError> {
+        sqlx::query_as!(
+            RetrievePasswordResetTokenDb,
+            r#"
+            SELECT id as "id!: i64", user_id
+            FROM PasswordResetToken
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2
+            LIMIT 1
+            "#,
+            token_hash,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Returns `false` if the token was already used by a concurrent request.
+    pub async fn mark_token_used(&self, tx: &mut SqliteConnection, token_id: i64, now: &str) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE PasswordResetToken
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL
+            "#,
+            now,
+            token_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+}
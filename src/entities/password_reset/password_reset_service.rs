@@ -0,0 +1,69 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{config, error::error::{AppError, AppErrorType}, util::crypto::{generate_secret_token, hash_secret_token}};
+
+use super::{password_reset::CreatePasswordResetTokenDb, password_reset_repository::PasswordResetRepository};
+
+pub struct PasswordResetService {
+    db_pool: SqlitePool,
+    password_reset_repository: PasswordResetRepository
+}
+
+impl PasswordResetService {
+    pub fn new(db_pool: SqlitePool, password_reset_repository: PasswordResetRepository) -> PasswordResetService {
+        PasswordResetService {
+            db_pool,
+            password_reset_repository
+        }
+    }
+
+    /// Creates a single-use reset token for the user and returns the raw token. Any previous token that was not
+    /// used yet stops being valid.
+    #[executor]
+    pub async fn create_token(&self, user_id: i64) -> Result<String, AppError> {
+        let token = generate_secret_token();
+
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::seconds(config::get().auth.password_reset_token_ttl))
+            .expect("valid timestamp");
+
+        let now = now.naive_utc().to_string();
+
+        self.password_reset_repository.invalidate_user_tokens(tx, user_id, &now).await?;
+        self.password_reset_repository.create_token(tx, &CreatePasswordResetTokenDb {
+            user_id,
+            token_hash: hash_secret_token(&token),
+            created_at: now,
+            expires_at: expires_at.naive_utc().to_string()
+        }).await?;
+
+        Ok(token)
+    }
+
+    /// Marks the token as used and returns the id of the user it belongs to.
+    #[executor]
+    pub async fn consume_token(&self, token: &str) -> Result<i64, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        let reset_token = match self.password_reset_repository.get_valid_token_by_hash(tx, &hash_secret_token(token), &now).await? {
+            Some(reset_token) => reset_token,
+            None => return Err(Self::invalid_token())
+        };
+
+        if !self.password_reset_repository.mark_token_used(tx, reset_token.id, &now).await? {
+            return Err(Self::invalid_token());
+        }
+
+        Ok(reset_token.user_id)
+    }
+
+    fn invalid_token() -> AppError {
+        AppError::new(
+            String::from("Invalid or expired password reset token"),
+            AppErrorType::BadRequest,
+            None
+        )
+    }
+}
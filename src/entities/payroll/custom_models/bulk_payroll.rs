@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::error::{AppError, AppErrorType};
+
+use super::uploaded_payroll::UploadedPayrollDto;
+
+/// Entry of the manifest of a bulk upload. The file is matched by its name inside the archive, without directories,
+/// and the employee either by `username` or by `user_id`.
+#[derive(Deserialize)]
+pub struct BulkPayrollEntryDto {
+    pub filename: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    pub date: String
+}
+
+impl BulkPayrollEntryDto {
+    pub const MAX_MANIFEST_SIZE: u64 = 1024 * 1024;
+
+    /// Parses a CSV manifest with a header row, or a JSON array, depending on the extension of its file name
+    pub fn from_manifest(file_name: &str, content: &[u8]) -> Result<Vec<BulkPayrollEntryDto>, AppError> {
+        let file_name = file_name.to_lowercase();
+
+        let entries = if file_name.ends_with(".csv") {
+            csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(content)
+                .deserialize()
+                .collect::<Result<Vec<BulkPayrollEntryDto>, _>>()
+                .map_err(invalid_manifest)?
+        } else if file_name.ends_with(".json") {
+            serde_json::from_slice::<Vec<BulkPayrollEntryDto>>(content)
+                .map_err(invalid_manifest)?
+        } else {
+            return Err(AppError::new(
+                String::from("The manifest must be a CSV or JSON file"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        };
+
+        if entries.is_empty() {
+            return Err(AppError::new(
+                String::from("The manifest has no entries"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let mut filenames = HashSet::new();
+
+        for entry in &entries {
+            if !filenames.insert(entry.filename.as_str()) {
+                return Err(AppError::new(
+                    String::from(r#"The manifest has more than one entry for "$1""#),
+                    AppErrorType::BadRequest,
+                    Some(vec![entry.filename.clone()])
+                ));
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BulkPayrollResultDto {
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payroll: Option<UploadedPayrollDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>
+}
+
+impl BulkPayrollResultDto {
+    pub fn from_result(filename: String, result: Result<UploadedPayrollDto, AppError>) -> BulkPayrollResultDto {
+        match result {
+            Ok(payroll) => BulkPayrollResultDto {
+                filename,
+                payroll: Some(payroll),
+                error: None
+            },
+            Err(error) => BulkPayrollResultDto {
+                filename,
+                payroll: None,
+                error: Some(error)
+            }
+        }
+    }
+}
+
+/// Outcome of every file of a bulk upload. A failed file does not prevent the others from being uploaded.
+#[derive(Serialize)]
+pub struct BulkPayrollReportDto {
+    pub uploaded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkPayrollResultDto>
+}
+
+impl BulkPayrollReportDto {
+    pub fn from_results(results: Vec<BulkPayrollResultDto>) -> BulkPayrollReportDto {
+        let uploaded = results.iter().filter(|result| result.payroll.is_some()).count();
+
+        BulkPayrollReportDto {
+            uploaded,
+            failed: results.len() - uploaded,
+            results
+        }
+    }
+}
+
+fn invalid_manifest<E>(err: E) -> AppError
+where E: std::fmt::Display
+{
+    AppError::new(
+        format!("Invalid manifest: {}", err),
+        AppErrorType::BadRequest,
+        None
+    )
+}
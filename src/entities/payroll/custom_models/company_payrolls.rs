@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+/// Company whose users get the payrolls of an upload of several employees
+#[derive(Deserialize)]
+pub struct CompanyPayrollsQueryDto {
+    pub company_id: i64
+}
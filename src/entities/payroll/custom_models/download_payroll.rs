@@ -3,9 +3,26 @@ use std::pin::Pin;
 use actix_web::web;
 use futures_util::Stream;
 
+use crate::util::download::ByteRange;
+
 pub struct DownloadPayrollDto {
     pub filename: String,
     pub content_type: String,
     pub file_size: i64,
+    /// Quoted content hash of the file
+    pub etag: String,
+    /// Part of the file that is streamed, or none when it is the whole file
+    pub range: Option<ByteRange>,
     pub stream: Pin<Box<dyn Stream<Item = Result<web::Bytes, std::io::Error>> + Send>>
 }
+
+pub enum PayrollDownload {
+    File(DownloadPayrollDto),
+    /// The client already has the current file
+    NotModified {
+        etag: String
+    },
+    RangeNotSatisfiable {
+        file_size: i64
+    }
+}
@@ -1,2 +1,6 @@
 pub mod payroll_filter;
-pub mod download_payroll;
\ No newline at end of file
+pub mod download_payroll;
+pub mod bulk_payroll;
+pub mod split_payroll;
+pub mod uploaded_payroll;
+pub mod company_payrolls;
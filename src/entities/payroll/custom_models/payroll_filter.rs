@@ -1,14 +1,18 @@
 use serde::Deserialize;
 use sqlx::{QueryBuilder, Sqlite};
 
-use crate::{entities::payroll::payroll::Payroll, error::error::AppError};
+use crate::{entities::{department::department::Department, payroll::payroll::Payroll}, error::error::AppError, util::pagination::Pagination};
 
 pub struct PayrollFilterDb {
     pub user_id: Option<i64>,
-    pub date: Option<String>
+    pub department_id: Option<i64>,
+    pub date: Option<String>,
+    pub pagination: Pagination
 }
 
 impl PayrollFilterDb {
+    pub const MAX_LIMIT: i64 = 100;
+
     pub fn from_payroll_filter_dto(filter: PayrollFilterDto) -> Result<PayrollFilterDb, AppError> {
         if let Some(date) = &filter.date {
             Payroll::check_date(date)?;
@@ -16,11 +20,14 @@ impl PayrollFilterDb {
 
         Ok(PayrollFilterDb {
             user_id: filter.user_id,
-            date: filter.date
+            department_id: filter.department_id,
+            date: filter.date,
+            pagination: Pagination::new(filter.limit, filter.offset, Self::MAX_LIMIT)?
         })
     }
 
-    pub fn fill_query(self, query: &mut QueryBuilder<Sqlite>) {
+    /// Conditions of the filter, shared by the listing and its count
+    pub fn fill_conditions(&self, query: &mut QueryBuilder<Sqlite>) {
         query.push(" WHERE 1 = 1");
 
         if let Some(user_id) = self.user_id {
@@ -28,15 +35,33 @@ impl PayrollFilterDb {
             query.push_bind(user_id);
         }
 
-        if let Some(date) = self.date {
+        if let Some(department_id) = self.department_id {
+            query.push(" AND user_id IN (SELECT id FROM AppUser WHERE department_id IN ");
+            Department::push_descendant_ids(query, department_id);
+            query.push(")");
+        }
+
+        if let Some(date) = &self.date {
             query.push(" AND date = ");
-            query.push_bind(date);
+            query.push_bind(date.clone());
         }
     }
+
+    pub fn fill_query(&self, query: &mut QueryBuilder<Sqlite>) {
+        self.fill_conditions(query);
+
+        query.push(" ORDER BY id");
+
+        self.pagination.fill_query(query);
+    }
 }
 
 #[derive(Deserialize)]
 pub struct PayrollFilterDto {
     pub user_id: Option<i64>,
-    pub date: Option<String>
+    /// Payrolls of the users of the department and of its subdepartments
+    pub department_id: Option<i64>,
+    pub date: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>
 }
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{entities::user::user::UserIdentifiersDto, error::error::AppError, util::{pdf::RemovedContent, tax_id::is_valid_spanish_national_id}};
+
+use super::uploaded_payroll::UploadedPayrollDto;
+
+/// Sent along with a PDF that contains the payslips of several employees of the company, one after the other
+#[derive(Deserialize)]
+pub struct SplitPayrollDto {
+    pub date: String,
+    pub identifier: EmployeeIdentifier,
+    /// Only reports how the pages would be split, without creating any payroll
+    #[serde(default)]
+    pub dry_run: bool
+}
+
+/// Identifier of the employees that is searched in the pages. National ids have a check letter, so they are searched
+/// anywhere in the text, but employee numbers only right after a label like "Nº empleado", since short ones are
+/// otherwise indistinguishable from amounts or dates.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EmployeeIdentifier {
+    NationalId,
+    EmployeeNumber
+}
+
+impl EmployeeIdentifier {
+    /// Users of the company by the value of this identifier
+    pub fn index(&self, users: &[UserIdentifiersDto]) -> HashMap<String, i64> {
+        users.iter()
+            .filter_map(|user| match self {
+                EmployeeIdentifier::NationalId => user.national_id.as_ref(),
+                EmployeeIdentifier::EmployeeNumber => user.employee_number.as_ref()
+            }.map(|identifier| (identifier.clone(), user.id)))
+            .collect()
+    }
+
+    /// Tokens of the page that can be the identifier of an employee
+    fn candidates<'a>(&self, tokens: &'a [String]) -> Vec<&'a String> {
+        match self {
+            EmployeeIdentifier::NationalId => tokens.iter().collect(),
+            EmployeeIdentifier::EmployeeNumber => tokens.iter()
+                .enumerate()
+                .filter(|(index, _)| tokens[index.saturating_sub(EMPLOYEE_NUMBER_LABEL_DISTANCE)..*index]
+                    .iter()
+                    .any(|token| EMPLOYEE_NUMBER_LABELS.contains(&token.as_str())))
+                .map(|(_, token)| token)
+                .collect()
+        }
+    }
+}
+
+/// Words that label the employee number in a payslip, once tokenized
+const EMPLOYEE_NUMBER_LABELS: [&str; 5] = ["EMPLEADO", "TRABAJADOR", "MATRÍCULA", "MATRICULA", "EMPLOYEE"];
+
+/// How many tokens before the employee number its label can be, as in "Nº de empleado: 12" or "Employee number 12"
+const EMPLOYEE_NUMBER_LABEL_DISTANCE: usize = 2;
+
+/// Pages of the document that belong to an employee
+pub struct EmployeePages {
+    pub user_id: i64,
+    pub identifier: String,
+    pub pages: Vec<u32>
+}
+
+/// Assigns every page to the employee whose identifier it contains. Pages without any identifier continue the
+/// payslip of the previous page, as long as it was assigned.
+///
+/// `users` are indexed by the `identifier` being searched, and `national_ids` by their national id. Every valid national
+/// id of a page is taken as the one of an employee, whatever the identifier being searched, so a page with the
+/// national id of someone else is never appended to the payslip of the previous employee.
+pub fn assign_pages(
+    page_texts: &[(u32, String)],
+    identifier: EmployeeIdentifier,
+    users: &HashMap<String, i64>,
+    national_ids: &HashMap<String, i64>
+) -> (Vec<EmployeePages>, Vec<UnmatchedPageDto>)
+{
+    let mut employees: Vec<EmployeePages> = Vec::new();
+    let mut unmatched = Vec::new();
+    let mut current: Option<usize> = None;
+
+    for (page, text) in page_texts {
+        let tokens = tokenize(text);
+        let page_national_ids = find_national_ids(&tokens);
+
+        let mut matched: Vec<(&String, i64)> = identifier.candidates(&tokens)
+            .into_iter()
+            .chain(page_national_ids.iter())
+            .filter_map(|token| users.get_key_value(token).map(|(identifier, user_id)| (identifier, *user_id)))
+            .collect();
+        matched.sort_by_key(|(_, user_id)| *user_id);
+        matched.dedup_by_key(|(_, user_id)| *user_id);
+
+        match matched.as_slice() {
+            [(identifier, user_id)] => {
+                let index = match employees.iter().position(|employee| employee.user_id == *user_id) {
+                    Some(index) => index,
+                    None => {
+                        employees.push(EmployeePages {
+                            user_id: *user_id,
+                            identifier: identifier.to_string(),
+                            pages: Vec::new()
+                        });
+                        employees.len() - 1
+                    }
+                };
+
+                employees[index].pages.push(*page);
+                current = Some(index);
+            },
+            [] => {
+                let current_user_id = current.map(|index| employees[index].user_id);
+
+                // National ids of anyone but the employee of the previous page
+                let other_national_ids: Vec<String> = page_national_ids.into_iter()
+                    .filter(|national_id| current_user_id.is_none() || national_ids.get(national_id) != current_user_id.as_ref())
+                    .collect();
+
+                match current {
+                    Some(index) if other_national_ids.is_empty() => employees[index].pages.push(*page),
+                    _ => {
+                        let reason = if other_national_ids.is_empty() { UnmatchedPageReason::NoIdentifier } else { UnmatchedPageReason::OtherIdentifier };
+
+                        unmatched.push(UnmatchedPageDto {
+                            page: *page,
+                            reason
+                        });
+                        current = None;
+                    }
+                }
+            },
+            _ => {
+                unmatched.push(UnmatchedPageDto {
+                    page: *page,
+                    reason: UnmatchedPageReason::SeveralEmployees
+                });
+                current = None;
+            }
+        }
+    }
+
+    (employees, unmatched)
+}
+
+/// Words of the text in uppercase and without the separators that identifiers are usually written with
+fn tokenize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).replace(['-', '.'], "").to_uppercase())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// National ids of the tokens, also the ones written with the letter apart from the number
+fn find_national_ids(tokens: &[String]) -> Vec<String> {
+    let mut national_ids: Vec<String> = tokens.iter()
+        .cloned()
+        .chain(tokens.windows(2).map(|pair| format!("{}{}", pair[0], pair[1])))
+        .filter(|token| is_valid_spanish_national_id(token))
+        .collect();
+
+    national_ids.sort();
+    national_ids.dedup();
+    national_ids
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmatchedPageReason {
+    /// The page has no identifier and it does not follow a page of an employee
+    NoIdentifier,
+    /// The page has no identifier being searched, but it has the national id of someone that is not the employee of
+    /// the previous page
+    OtherIdentifier,
+    /// The page has the identifiers of more than one employee
+    SeveralEmployees
+}
+
+/// The identifiers found in the page are not reported, since they are personal data of the employees
+#[derive(Serialize)]
+pub struct UnmatchedPageDto {
+    pub page: u32,
+    pub reason: UnmatchedPageReason
+}
+
+#[derive(Serialize)]
+pub struct SplitPayrollDocumentDto {
+    pub user_id: i64,
+    /// Only reported for the employees that the actor can upload payrolls for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    pub pages: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payroll: Option<UploadedPayrollDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>
+}
+
+/// How the pages of the document were split, or would be split on a dry run
+#[derive(Serialize)]
+pub struct SplitPayrollReportDto {
+    pub dry_run: bool,
+    pub pages: usize,
+    pub documents: Vec<SplitPayrollDocumentDto>,
+    pub unmatched_pages: Vec<UnmatchedPageDto>,
+    /// Active content stripped from the document before it was split
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_content: Vec<RemovedContent>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(texts: &[&str]) -> Vec<(u32, String)> {
+        texts.iter().enumerate().map(|(index, text)| (index as u32 + 1, text.to_string())).collect()
+    }
+
+    fn index(entries: &[(&str, i64)]) -> HashMap<String, i64> {
+        entries.iter().map(|(identifier, user_id)| (identifier.to_string(), *user_id)).collect()
+    }
+
+    #[test]
+    fn assigns_pages_by_national_id() {
+        let national_ids = index(&[("12345678Z", 1), ("87654321X", 2)]);
+        let texts = pages(&["Nómina de 12345678-z", "Total 1.234,56", "DNI 87654321 X"]);
+
+        let (employees, unmatched) = assign_pages(&texts, EmployeeIdentifier::NationalId, &national_ids, &national_ids);
+
+        assert_eq!(employees.len(), 2);
+        assert_eq!((employees[0].user_id, employees[0].pages.clone()), (1, vec![1, 2]));
+        assert_eq!((employees[1].user_id, employees[1].pages.clone()), (2, vec![3]));
+        assert!(unmatched.is_empty());
+    }
+
+    #[test]
+    fn matches_employee_numbers_only_after_a_label() {
+        let users = index(&[("12", 1), ("7", 2)]);
+        let texts = pages(&["Nº de empleado: 12", "Fecha 12 de mayo, importe 7", "Employee number 7"]);
+
+        let (employees, unmatched) = assign_pages(&texts, EmployeeIdentifier::EmployeeNumber, &users, &HashMap::new());
+
+        assert_eq!(employees.len(), 2);
+        assert_eq!((employees[0].user_id, employees[0].pages.clone()), (1, vec![1, 2]));
+        assert_eq!((employees[1].user_id, employees[1].pages.clone()), (2, vec![3]));
+        assert!(unmatched.is_empty());
+    }
+
+    #[test]
+    fn does_not_continue_a_payslip_with_the_national_id_of_someone_else() {
+        let users = index(&[("A1", 1)]);
+        let national_ids = index(&[("12345678Z", 1), ("87654321X", 2)]);
+        let texts = pages(&["Trabajador A1 12345678Z", "87654321X"]);
+
+        let (employees, unmatched) = assign_pages(&texts, EmployeeIdentifier::EmployeeNumber, &users, &national_ids);
+
+        assert_eq!(employees.len(), 1);
+        assert_eq!(employees[0].pages, vec![1]);
+        assert_eq!(unmatched.len(), 1);
+        assert!(matches!(unmatched[0].reason, UnmatchedPageReason::OtherIdentifier));
+    }
+
+    #[test]
+    fn reports_pages_without_employee_or_with_several() {
+        let national_ids = index(&[("12345678Z", 1), ("87654321X", 2)]);
+        let texts = pages(&["Cover page", "12345678Z 87654321X"]);
+
+        let (employees, unmatched) = assign_pages(&texts, EmployeeIdentifier::NationalId, &national_ids, &national_ids);
+
+        assert!(employees.is_empty());
+        assert_eq!(unmatched.len(), 2);
+        assert!(matches!(unmatched[0].reason, UnmatchedPageReason::NoIdentifier));
+        assert!(matches!(unmatched[1].reason, UnmatchedPageReason::SeveralEmployees));
+    }
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+use crate::{entities::payroll::payroll::RetrievePayrollDto, util::pdf::RemovedContent};
+
+/// Payroll created or replaced from an uploaded file, along with the active content stripped from the file
+#[derive(Serialize)]
+pub struct UploadedPayrollDto {
+    #[serde(flatten)]
+    pub payroll: RetrievePayrollDto,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_content: Vec<RemovedContent>
+}
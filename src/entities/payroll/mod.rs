@@ -1,5 +1,7 @@
 pub mod payroll;
+pub mod payroll_version;
 pub mod payroll_service;
 pub mod payroll_repository;
 pub mod payroll_controller;
+pub mod payroll_policy;
 pub mod custom_models;
\ No newline at end of file
@@ -8,16 +8,25 @@ use crate::{config, error::error::{AppError, AppErrorType}};
 #[derive(DeriveCustomModel)]
 #[custom_model(model(
     name = "CreatePayrollDb",
-    fields(date, user_id, object_key, filename, content_type, file_size, uploaded_at)
+    fields(date, user_id, object_key, filename, content_type, file_size, content_hash, uploaded_at)
 ))]
 #[custom_model(model(
     name = "RetrievePayrollDb",
-    fields(id, date, user_id, filename, file_size),
+    fields(id, date, user_id, version, filename, file_size),
     extra_derives(FromRow)
 ))]
+#[custom_model(model(
+    name = "RetrievePayrollFileDb",
+    fields(version, object_key, filename, content_type, file_size, content_hash, uploaded_at),
+    extra_derives(FromRow)
+))]
+#[custom_model(model(
+    name = "UpdatePayrollFileDb",
+    fields(version, object_key, filename, content_type, file_size, content_hash, uploaded_at)
+))]
 #[custom_model(model(
     name = "RetrievePayrollDownloadDataDb",
-    fields(object_key, filename, content_type, file_size),
+    fields(object_key, filename, content_type, file_size, content_hash),
     extra_derives(FromRow)
 ))]
 #[custom_model(model(
@@ -27,7 +36,7 @@ use crate::{config, error::error::{AppError, AppErrorType}};
 ))]
 #[custom_model(model(
     name = "RetrievePayrollDto",
-    fields(id, date, user_id, filename, file_size),
+    fields(id, date, user_id, version, filename, file_size),
     extra_derives(Serialize)
 ))]
 #[allow(dead_code)]
@@ -35,10 +44,14 @@ pub struct Payroll {
     id: i64,
     date: String,
     user_id: i64,
+    /// Number of the current file, increased every time it is replaced
+    version: i64,
     object_key: String,
     filename: String,
     content_type: String,
     file_size: i64,
+    /// SHA-256 of the file, hex encoded. Files uploaded before it was stored have none.
+    content_hash: Option<String>,
     uploaded_at: String
 }
 
@@ -129,6 +142,7 @@ impl CreatePayrollDb {
         object_key: String, filename:
         String, content_type: String,
         file_size: i64,
+        content_hash: String,
         uploaded_at: String
     ) -> Result<CreatePayrollDb, AppError>
     {
@@ -146,6 +160,37 @@ impl CreatePayrollDb {
             filename,
             content_type,
             file_size,
+            content_hash: Some(content_hash),
+            uploaded_at
+        })
+    }
+}
+
+impl UpdatePayrollFileDb {
+    /// The file that replaces the current one of the payroll, as its next version
+    pub fn from_replaced_file(
+        current: &RetrievePayrollFileDb,
+        object_key: String,
+        filename: String,
+        content_type: String,
+        file_size: i64,
+        content_hash: String,
+        uploaded_at: String
+    ) -> Result<UpdatePayrollFileDb, AppError>
+    {
+        Payroll::check_object_key(&object_key)?;
+        Payroll::check_filename(&filename)?;
+        Payroll::check_content_type(&content_type)?;
+        Payroll::check_file_size(file_size)?;
+        Payroll::check_uploaded_at(&uploaded_at)?;
+
+        Ok(UpdatePayrollFileDb {
+            version: current.version + 1,
+            object_key,
+            filename,
+            content_type,
+            file_size,
+            content_hash: Some(content_hash),
             uploaded_at
         })
     }
@@ -157,6 +202,7 @@ impl RetrievePayrollDb {
             id: self.id,
             date: self.date,
             user_id: self.user_id,
+            version: self.version,
             filename: self.filename,
             file_size: self.file_size
         }
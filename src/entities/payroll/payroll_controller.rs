@@ -1,26 +1,28 @@
 use actix_multipart::Multipart;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 
-use crate::{auth::jwt::Claims, check_permission, service, util::{json_response::json_response, multipart::{extract_body, extract_file}}};
+use crate::{service, util::{download::DownloadRequest, json_response::json_response, multipart::{extract_body, extract_field, extract_file}}};
 
-use super::custom_models::payroll_filter::PayrollFilterDto;
+use super::{custom_models::{bulk_payroll::BulkPayrollEntryDto, company_payrolls::CompanyPayrollsQueryDto, download_payroll::PayrollDownload, payroll_filter::PayrollFilterDto, split_payroll::SplitPayrollDto}, payroll_policy::{CreateCompanyPayrollsClaims, CreatePayrollClaims, DeletePayrollClaims, ListPayrollsClaims, ReadPayrollClaims, ReadPayrollVersionsClaims, UpdatePayrollClaims}};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/payrolls")
             .route("", web::post().to(upload_payroll))
             .route("", web::get().to(get_payrolls))
+            .route("/bulk", web::post().to(bulk_upload_payrolls))
+            .route("/split", web::post().to(split_payroll))
+            .route("/{payroll_id}", web::delete().to(delete_payroll))
             .route("/{payroll_id}/download", web::get().to(download_payroll))
+            .route("/{payroll_id}/file", web::put().to(replace_payroll_file))
+            .route("/{payroll_id}/versions", web::get().to(get_payroll_versions))
+            .route("/{payroll_id}/versions/{version}/download", web::get().to(download_payroll_version))
     );
 }
 
-pub async fn upload_payroll(mut payload: Multipart, claims: Claims) -> impl Responder {
-    let payroll = match extract_body(&mut payload).await {
-        Ok(body) => body,
-        Err(err) => return json_response(&Err(err))
-    };
-
-    check_permission!(service::get().permission().create_payroll(claims.sub, &payroll).await);
+/// Expects the payroll in the "body" field, followed by its file in the "file" field
+pub async fn upload_payroll(claims: CreatePayrollClaims) -> impl Responder {
+    let CreatePayrollClaims(_claims, payroll, mut payload) = claims;
 
     let file_info = match extract_file(&mut payload).await {
         Ok(file_info) => file_info,
@@ -31,41 +33,140 @@ pub async fn upload_payroll(mut payload: Multipart, claims: Claims) -> impl Resp
         payroll,
         &file_info.file_path,
         &file_info.unique_file_name,
-        &file_info.original_file_name,
-        file_info.file_size
+        &file_info.original_file_name
     ).await;
 
     json_response(&created_payroll)
 }
 
-pub async fn get_payrolls(filters: web::Query<PayrollFilterDto>, claims: Claims) -> impl Responder {
-    check_permission!(service::get().permission().get_payrolls(claims.sub, filters.user_id).await);
+/// Expects a "manifest" field with a CSV or JSON file, followed by the ZIP archive in the "file" field. The payrolls are
+/// uploaded to the users of the `company_id` query parameter.
+pub async fn bulk_upload_payrolls(query: web::Query<CompanyPayrollsQueryDto>, mut payload: Multipart, claims: CreateCompanyPayrollsClaims) -> impl Responder {
+    let CreateCompanyPayrollsClaims(claims) = claims;
+
+    let manifest = match extract_field(&mut payload, "manifest", BulkPayrollEntryDto::MAX_MANIFEST_SIZE).await {
+        Ok(manifest) => manifest,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let file_info = match extract_file(&mut payload).await {
+        Ok(file_info) => file_info,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let report = service::get().payroll().bulk_create_payrolls(&claims, query.company_id, manifest, &file_info.file_path).await;
+
+    json_response(&report)
+}
+
+/// Expects the split options in the "body" field, followed by the combined PDF in the "file" field. The employees are
+/// searched among the users of the `company_id` query parameter.
+pub async fn split_payroll(query: web::Query<CompanyPayrollsQueryDto>, mut payload: Multipart, claims: CreateCompanyPayrollsClaims) -> impl Responder {
+    let CreateCompanyPayrollsClaims(claims) = claims;
+
+    let split: SplitPayrollDto = match extract_body(&mut payload).await {
+        Ok(body) => body,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let file_info = match extract_file(&mut payload).await {
+        Ok(file_info) => file_info,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let report = service::get().payroll().split_payroll(&claims, query.company_id, split, &file_info.file_path, &file_info.original_file_name).await;
+
+    json_response(&report)
+}
 
+pub async fn get_payrolls(filters: web::Query<PayrollFilterDto>, _claims: ListPayrollsClaims) -> impl Responder {
     let payrolls = service::get().payroll().get_filtered_payrolls(filters.into_inner()).await;
 
     json_response(&payrolls)
 }
 
-pub async fn download_payroll(payroll_id: web::Path<i64>, claims: Claims) -> impl Responder {
+pub async fn download_payroll(req: HttpRequest, payroll_id: web::Path<i64>, _claims: ReadPayrollClaims) -> impl Responder {
     let payroll_id = payroll_id.into_inner();
+    let request = DownloadRequest::from_request(&req);
 
-    match service::get().permission().get_payroll(claims.sub, payroll_id).await {
-        Ok(is_allowed) => {
-            if !is_allowed {
-                return HttpResponse::Forbidden().finish();
-            }
-        },
-        Err(_) => return HttpResponse::InternalServerError().finish(),
+    match service::get().payroll().download_payroll(payroll_id, &request).await {
+        Ok(payroll_data) => payroll_file_response(payroll_data),
+        Err(err) => err.error_response()
     }
+}
+
+pub async fn replace_payroll_file(payroll_id: web::Path<i64>, mut payload: Multipart, _claims: UpdatePayrollClaims) -> impl Responder {
+    let file_info = match extract_file(&mut payload).await {
+        Ok(file_info) => file_info,
+        Err(err) => return json_response(&Err(err))
+    };
+
+    let payroll = service::get().payroll().replace_payroll_file(
+        payroll_id.into_inner(),
+        &file_info.file_path,
+        &file_info.unique_file_name,
+        &file_info.original_file_name
+    ).await;
+
+    json_response(&payroll)
+}
 
-    let payroll_data = service::get().payroll().download_payroll(payroll_id).await.unwrap();
+pub async fn delete_payroll(payroll_id: web::Path<i64>, _claims: DeletePayrollClaims) -> impl Responder {
+    let deleted = service::get().payroll().delete_payroll(payroll_id.into_inner()).await;
 
-    let mut builder = HttpResponse::Ok();
+    json_response(&deleted)
+}
+
+pub async fn get_payroll_versions(payroll_id: web::Path<i64>, _claims: ReadPayrollVersionsClaims) -> impl Responder {
+    let versions = service::get().payroll().get_payroll_versions(payroll_id.into_inner()).await;
+
+    json_response(&versions)
+}
+
+pub async fn download_payroll_version(req: HttpRequest, path: web::Path<(i64, i64)>, _claims: ReadPayrollVersionsClaims) -> impl Responder {
+    let (payroll_id, version) = path.into_inner();
+    let request = DownloadRequest::from_request(&req);
+
+    match service::get().payroll().download_payroll_version(payroll_id, version, &request).await {
+        Ok(payroll_data) => payroll_file_response(payroll_data),
+        Err(err) => err.error_response()
+    }
+}
+
+/// Payrolls are private, so they are only cached by the client, which must revalidate them with their ETag
+fn payroll_file_response(payroll_download: PayrollDownload) -> HttpResponse {
+    let payroll_data = match payroll_download {
+        PayrollDownload::File(payroll_data) => payroll_data,
+        PayrollDownload::NotModified { etag } => return HttpResponse::NotModified()
+            .append_header(("ETag", etag))
+            .append_header(("Cache-Control", "private, no-cache"))
+            .finish(),
+        PayrollDownload::RangeNotSatisfiable { file_size } => return HttpResponse::RangeNotSatisfiable()
+            .append_header(("Content-Range", format!("bytes */{}", file_size)))
+            .finish()
+    };
+
+    let mut builder = match &payroll_data.range {
+        Some(range) => {
+            let mut builder = HttpResponse::PartialContent();
+            builder
+                .append_header(("Content-Range", range.content_range(payroll_data.file_size as u64)))
+                .append_header(("Content-Length", range.length().to_string()));
+            builder
+        },
+        None => {
+            let mut builder = HttpResponse::Ok();
+            builder.append_header(("Content-Length", payroll_data.file_size.to_string()));
+            builder
+        }
+    };
 
     builder
         .content_type(payroll_data.content_type)
         .append_header(("Content-Disposition", format!("attachment; filename=\"{}\"", payroll_data.filename)))
-        .append_header(("Content-Length", payroll_data.file_size.to_string()));
+        .append_header(("Accept-Ranges", "bytes"))
+        .append_header(("Cache-Control", "private, no-cache"))
+        .append_header(("ETag", payroll_data.etag));
 
     builder.streaming(payroll_data.stream)
 }
@@ -0,0 +1,50 @@
+use crate::{auth::policy::{path_id, query_id, Action, Policy, Resource}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, Owned, SelfCompany, SelfDepartment}}};
+
+use super::payroll::CreatePayrollDto;
+
+/// Users can only read their own payrolls, and department managers the ones of their departments
+pub const PAYROLL_POLICY: Policy = Policy {
+    create: &[Any, SelfCompany],
+    read: &[Any, SelfCompany, SelfDepartment, Owned],
+    update: &[Any, SelfCompany],
+    delete: &[Any, SelfCompany]
+};
+
+authorized_claims!(
+    /// Claims of an actor allowed to list the payrolls of the `user_id` query parameter, or the ones of the
+    /// `department_id` query parameter, or every payroll without them
+    ListPayrollsClaims, Operation::Read, |req| Some(
+        match (query_id(req, "user_id"), query_id(req, "department_id")) {
+            (None, Some(department_id)) => Resource::DepartmentPayrolls(department_id),
+            (user_id, _) => Resource::Payrolls(user_id)
+        }
+    )
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to create the payroll of the "body" field of the request for its user
+    CreatePayrollClaims, action: Action::new(Operation::Create), multipart |payroll: CreatePayrollDto| Some(Resource::Payrolls(Some(payroll.user_id)))
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to create payrolls for the users of the `company_id` query parameter
+    CreateCompanyPayrollsClaims, Operation::Create, |req| query_id(req, "company_id").map(Resource::CompanyPayrolls)
+);
+
+authorized_claims!(
+    ReadPayrollClaims, Operation::Read, |req| path_id(req, "payroll_id").map(Resource::Payroll)
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to replace the file of the payroll
+    UpdatePayrollClaims, Operation::Update, |req| path_id(req, "payroll_id").map(Resource::Payroll)
+);
+
+authorized_claims!(
+    DeletePayrollClaims, Operation::Delete, |req| path_id(req, "payroll_id").map(Resource::Payroll)
+);
+
+authorized_claims!(
+    /// Prior versions of a payroll are only available to admins, not to its owner or the managers of its department
+    ReadPayrollVersionsClaims, Operation::Read, &[Any, SelfCompany], |req| path_id(req, "payroll_id").map(Resource::Payroll)
+);
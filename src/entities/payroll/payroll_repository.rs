@@ -2,7 +2,7 @@ use sqlx::{QueryBuilder, SqliteConnection};
 
 use crate::{error::error::AppError, util::db::to_app_error};
 
-use super::{custom_models::payroll_filter::PayrollFilterDb, payroll::{CreatePayrollDb, RetrievePayrollDb, RetrievePayrollDownloadDataDb}};
+use super::{custom_models::payroll_filter::PayrollFilterDb, payroll::{CreatePayrollDb, RetrievePayrollDb, RetrievePayrollDownloadDataDb, RetrievePayrollFileDb, UpdatePayrollFileDb}, payroll_version::{CreatePayrollVersionDb, RetrievePayrollVersionDb}};
 
 pub struct PayrollRepository {}
 
@@ -17,9 +17,9 @@ impl PayrollRepository {
         sqlx::query_as!(
             RetrievePayrollDb,
             r#"
-            INSERT INTO Payroll (date, user_id, object_key, filename, content_type, file_size, uploaded_at)
-            VALUES($1, $2, $3, $4, $5, $6, $7)
-            RETURNING id as "id!: i64", date, user_id, filename, file_size
+            INSERT INTO Payroll (date, user_id, object_key, filename, content_type, file_size, content_hash, uploaded_at)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id as "id!: i64", date, user_id, version, filename, file_size
             "#,
             payroll.date,
             payroll.user_id,
@@ -27,6 +27,7 @@ impl PayrollRepository {
             payroll.filename,
             payroll.content_type,
             payroll.file_size,
+            payroll.content_hash,
             payroll.uploaded_at
         )
         .fetch_one(tx)
@@ -34,10 +35,10 @@ impl PayrollRepository {
         .map_err(to_app_error)
     }
 
-    pub async fn get_filtered_payrolls(&self, tx: &mut SqliteConnection, filter: PayrollFilterDb) -> Result<Vec<RetrievePayrollDb>, AppError> {
+    pub async fn get_filtered_payrolls(&self, tx: &mut SqliteConnection, filter: &PayrollFilterDb) -> Result<Vec<RetrievePayrollDb>, AppError> {
         let mut query = QueryBuilder::new(
             r#"
-            SELECT id, date, user_id, filename, file_size
+            SELECT id, date, user_id, version, filename, file_size
             FROM Payroll
             "#
         );
@@ -50,22 +51,194 @@ impl PayrollRepository {
             .map_err(to_app_error)
     }
 
-    pub async fn get_payroll_by_id(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<RetrievePayrollDownloadDataDb, AppError> {
+    pub async fn count_filtered_payrolls(&self, tx: &mut SqliteConnection, filter: &PayrollFilterDb) -> Result<i64, AppError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT COUNT(*)
+            FROM Payroll
+            "#
+        );
+
+        filter.fill_conditions(&mut query);
+
+        query.build_query_scalar()
+            .fetch_one(tx)
+            .await
+            .map_err(to_app_error)
+    }
+
+    pub async fn get_payroll_file_by_id(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<Option<RetrievePayrollFileDb>, AppError> {
         sqlx::query_as!(
-            RetrievePayrollDownloadDataDb,
+            RetrievePayrollFileDb,
             r#"
-            SELECT object_key, filename, content_type, file_size
+            SELECT version, object_key, filename, content_type, file_size, content_hash, uploaded_at
             FROM Payroll
             WHERE id = $1
             LIMIT 1
             "#,
             payroll_id
         )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_payroll_file(&self, tx: &mut SqliteConnection, payroll_id: i64, file: &UpdatePayrollFileDb) -> Result<RetrievePayrollDb, AppError> {
+        sqlx::query_as!(
+            RetrievePayrollDb,
+            r#"
+            UPDATE Payroll
+            SET version = $1, object_key = $2, filename = $3, content_type = $4, file_size = $5, content_hash = $6, uploaded_at = $7
+            WHERE id = $8
+            RETURNING id as "id!: i64", date, user_id, version, filename, file_size
+            "#,
+            file.version,
+            file.object_key,
+            file.filename,
+            file.content_type,
+            file.file_size,
+            file.content_hash,
+            file.uploaded_at,
+            payroll_id
+        )
         .fetch_one(tx)
         .await
         .map_err(to_app_error)
     }
 
+    /// Only stores the hash while the payroll keeps the file it was computed from
+    pub async fn set_payroll_content_hash(&self, tx: &mut SqliteConnection, payroll_id: i64, object_key: &str, content_hash: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Payroll
+            SET content_hash = $1
+            WHERE id = $2 AND object_key = $3 AND content_hash IS NULL
+            "#,
+            content_hash,
+            payroll_id,
+            object_key
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_payroll(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM Payroll
+            WHERE id = $1
+            "#,
+            payroll_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn create_payroll_version(&self, tx: &mut SqliteConnection, version: &CreatePayrollVersionDb) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO PayrollVersion (payroll_id, version, object_key, filename, content_type, file_size, content_hash, uploaded_at, replaced_at)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            version.payroll_id,
+            version.version,
+            version.object_key,
+            version.filename,
+            version.content_type,
+            version.file_size,
+            version.content_hash,
+            version.uploaded_at,
+            version.replaced_at
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    /// Prior versions of the payroll, the newest first
+    pub async fn get_payroll_versions(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<Vec<RetrievePayrollVersionDb>, AppError> {
+        sqlx::query_as!(
+            RetrievePayrollVersionDb,
+            r#"
+            SELECT id as "id!: i64", payroll_id, version, filename, file_size, uploaded_at, replaced_at
+            FROM PayrollVersion
+            WHERE payroll_id = $1
+            ORDER BY version DESC
+            "#,
+            payroll_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_payroll_version_file(&self, tx: &mut SqliteConnection, payroll_id: i64, version: i64) -> Result<Option<RetrievePayrollDownloadDataDb>, AppError> {
+        sqlx::query_as!(
+            RetrievePayrollDownloadDataDb,
+            r#"
+            SELECT object_key, filename, content_type, file_size, content_hash
+            FROM PayrollVersion
+            WHERE payroll_id = $1 AND version = $2
+            LIMIT 1
+            "#,
+            payroll_id,
+            version
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn set_payroll_version_content_hash(&self, tx: &mut SqliteConnection, payroll_id: i64, version: i64, content_hash: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE PayrollVersion
+            SET content_hash = $1
+            WHERE payroll_id = $2 AND version = $3 AND content_hash IS NULL
+            "#,
+            content_hash,
+            payroll_id,
+            version
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_payroll_version_object_keys(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<Vec<String>, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT object_key
+            FROM PayrollVersion
+            WHERE payroll_id = $1
+            "#,
+            payroll_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_payroll_versions(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM PayrollVersion
+            WHERE payroll_id = $1
+            "#,
+            payroll_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
     pub async fn get_user_by_payroll_id(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<i64, AppError> {
         sqlx::query!(
             r#"
@@ -81,4 +254,19 @@ impl PayrollRepository {
         .map(|row| row.user_id)
         .map_err(to_app_error)
     }
-}
+
+    pub async fn count_payrolls_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM Payroll
+            JOIN AppUser ON AppUser.id = Payroll.user_id
+            WHERE AppUser.company_id = $1
+            "#,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
@@ -3,10 +3,11 @@ use std::sync::Arc;
 use actix_web::web;
 use macros::executor;
 use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
 
-use crate::{config, error::error::{AppError, AppErrorType}, util::{file::{check_pdf, remove_file}, minio::MinioService}};
+use crate::{auth::{jwt::Claims, policy::{permission_denied, Resource}}, config, entities::permission::permission::Operation, error::error::{AppError, AppErrorType}, service, util::{archive::extract_zip, file::{remove_file, ExtractedFile}, minio::MinioService, multipart::FieldData, download::{DownloadRequest, RangeRequest}, pagination::Page, pdf::{extract_page_texts, sanitize_pdf, split_pdf, SanitizedPdf}}};
 
-use super::{custom_models::{download_payroll::DownloadPayrollDto, payroll_filter::{PayrollFilterDb, PayrollFilterDto}}, payroll::{CreatePayrollDb, CreatePayrollDto, RetrievePayrollDto}, payroll_repository::PayrollRepository};
+use super::{custom_models::{bulk_payroll::{BulkPayrollEntryDto, BulkPayrollReportDto, BulkPayrollResultDto}, download_payroll::{DownloadPayrollDto, PayrollDownload}, payroll_filter::{PayrollFilterDb, PayrollFilterDto}, split_payroll::{assign_pages, EmployeeIdentifier, SplitPayrollDocumentDto, SplitPayrollDto, SplitPayrollReportDto}, uploaded_payroll::UploadedPayrollDto}, payroll::{CreatePayrollDb, CreatePayrollDto, Payroll, RetrievePayrollDb, RetrievePayrollDto, RetrievePayrollFileDb, UpdatePayrollFileDb}, payroll_repository::PayrollRepository, payroll_version::{CreatePayrollVersionDb, RetrievePayrollVersionDto}};
 
 pub struct PayrollService {
     db_pool: SqlitePool,
@@ -24,40 +25,137 @@ impl PayrollService {
     }
 
     #[executor]
-    pub async fn create_payroll(&self, payroll: CreatePayrollDto, file_path: &str, file_name: &str, original_file_name: &str, file_size: i64) -> Result<RetrievePayrollDto, AppError> {
-        let result = self.do_create_payroll(tx, payroll, file_path, file_name, original_file_name, file_size).await;
+    pub async fn create_payroll(&self, payroll: CreatePayrollDto, file_path: &str, file_name: &str, original_file_name: &str) -> Result<UploadedPayrollDto, AppError> {
+        let result = self.do_create_payroll(tx, payroll, file_path, file_name, original_file_name).await;
         remove_file(file_path).await?;
         result
     }
 
+    /// Uploads every payroll of the ZIP archive to the employee of the company that the manifest assigns it to. Each
+    /// file is authorized and validated on its own, so the report tells which ones failed and why.
     #[executor]
-    pub async fn get_filtered_payrolls(&self, filter: PayrollFilterDto) -> Result<Vec<RetrievePayrollDto>, AppError> {
-        let payrolls = self.payroll_repository.get_filtered_payrolls(tx, PayrollFilterDb::from_payroll_filter_dto(filter)?).await?;
+    pub async fn bulk_create_payrolls(&self, actor: &Claims, company_id: i64, manifest: FieldData, file_path: &str) -> Result<BulkPayrollReportDto, AppError> {
+        let result = self.do_bulk_create_payrolls(tx, actor, company_id, manifest, file_path).await;
+        remove_file(file_path).await?;
+        result
+    }
 
-        Ok(payrolls.into_iter().map(|payroll| payroll.to_retrieve_payroll_dto()).collect())
+    /// Splits a PDF with the payslips of several employees of the company into a payroll for each of them, finding
+    /// them by their identifier in every page. A dry run only reports how the pages would be split.
+    #[executor]
+    pub async fn split_payroll(&self, actor: &Claims, company_id: i64, split: SplitPayrollDto, file_path: &str, original_file_name: &str) -> Result<SplitPayrollReportDto, AppError> {
+        let result = self.do_split_payroll(tx, actor, company_id, split, file_path, original_file_name).await;
+        remove_file(file_path).await?;
+        result
     }
 
     #[executor]
-    pub async fn download_payroll(&self, payroll_id: i64) -> Result<DownloadPayrollDto, AppError> {
-        let payroll_data = self.payroll_repository.get_payroll_by_id(tx, payroll_id).await?;
+    pub async fn get_filtered_payrolls(&self, filter: PayrollFilterDto) -> Result<Page<RetrievePayrollDto>, AppError> {
+        let filter = PayrollFilterDb::from_payroll_filter_dto(filter)?;
 
-        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
-        let stream_info = self.bucket_service.get_file_stream(bucket_name, &payroll_data.object_key).await?;
+        let payrolls = self.payroll_repository.get_filtered_payrolls(tx, &filter).await?;
+        let total = self.payroll_repository.count_filtered_payrolls(tx, &filter).await?;
 
-        if stream_info.size != payroll_data.file_size {
-            return Err(AppError::new(
-                format!("File size mismatch: expected {}, got {}", payroll_data.file_size, stream_info.size),
-                AppErrorType::InternalServerError,
-                None
-            ));
+        Ok(Page::new(payrolls, total, &filter.pagination).map(|payroll| payroll.to_retrieve_payroll_dto()))
+    }
+
+    /// Streams the file of the payroll, or only the range of it that was requested. Nothing is streamed when the client
+    /// already has the current file.
+    #[executor]
+    pub async fn download_payroll(&self, payroll_id: i64, request: &DownloadRequest) -> Result<PayrollDownload, AppError> {
+        let payroll_data = self.get_existing_payroll_file(tx, payroll_id).await?;
+
+        let content_hash = match payroll_data.content_hash {
+            Some(content_hash) => content_hash,
+            None => {
+                let content_hash = self.hash_stored_file(&payroll_data.object_key).await?;
+                self.payroll_repository.set_payroll_content_hash(tx, payroll_id, &payroll_data.object_key, &content_hash).await?;
+                content_hash
+            }
+        };
+
+        self.stream_payroll_file(
+            &payroll_data.object_key,
+            payroll_data.filename,
+            payroll_data.content_type,
+            payroll_data.file_size,
+            content_hash,
+            request
+        ).await
+    }
+
+    /// Replaces the file of the payroll with a corrected one. The current file is kept as a prior version.
+    pub async fn replace_payroll_file(&self, payroll_id: i64, file_path: &str, file_name: &str, original_file_name: &str) -> Result<UploadedPayrollDto, AppError> {
+        let result = self.do_replace_payroll_file(payroll_id, file_path, file_name, original_file_name).await;
+        remove_file(file_path).await?;
+        result
+    }
+
+    /// Deletes the payroll along with its prior versions, then removes all of their files from the bucket
+    pub async fn delete_payroll(&self, payroll_id: i64) -> Result<(), AppError> {
+        let object_keys = self.delete_payroll_records(payroll_id).await?;
+
+        // The payroll is already deleted, so failing to remove its files is not an error of the deletion. Failures are
+        // already logged when the error is created
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        for object_key in object_keys {
+            self.bucket_service.remove_file(bucket_name, &object_key).await.ok();
         }
 
-        Ok(DownloadPayrollDto {
-            filename: payroll_data.filename,
-            content_type: payroll_data.content_type,
-            file_size: payroll_data.file_size,
-            stream: stream_info.stream
-        })
+        Ok(())
+    }
+
+    /// Object keys of the files of the deleted payroll and of its prior versions
+    #[executor(transaction)]
+    async fn delete_payroll_records(&self, payroll_id: i64) -> Result<Vec<String>, AppError> {
+        let payroll_data = self.get_existing_payroll_file(tx, payroll_id).await?;
+        let mut object_keys = self.payroll_repository.get_payroll_version_object_keys(tx, payroll_id).await?;
+
+        self.payroll_repository.delete_payroll_versions(tx, payroll_id).await?;
+        self.payroll_repository.delete_payroll(tx, payroll_id).await?;
+
+        object_keys.push(payroll_data.object_key);
+
+        Ok(object_keys)
+    }
+
+    #[executor]
+    pub async fn get_payroll_versions(&self, payroll_id: i64) -> Result<Vec<RetrievePayrollVersionDto>, AppError> {
+        self.get_existing_payroll_file(tx, payroll_id).await?;
+
+        let versions = self.payroll_repository.get_payroll_versions(tx, payroll_id).await?;
+
+        Ok(versions.into_iter().map(|version| version.to_retrieve_payroll_version_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn download_payroll_version(&self, payroll_id: i64, version: i64, request: &DownloadRequest) -> Result<PayrollDownload, AppError> {
+        let version_data = match self.payroll_repository.get_payroll_version_file(tx, payroll_id, version).await? {
+            Some(version_data) => version_data,
+            None => return Err(AppError::new(
+                String::from(r#"Payroll with id "$1" has no prior version "$2""#),
+                AppErrorType::NotFound,
+                Some(vec![payroll_id.to_string(), version.to_string()])
+            ))
+        };
+
+        let content_hash = match version_data.content_hash {
+            Some(content_hash) => content_hash,
+            None => {
+                let content_hash = self.hash_stored_file(&version_data.object_key).await?;
+                self.payroll_repository.set_payroll_version_content_hash(tx, payroll_id, version, &content_hash).await?;
+                content_hash
+            }
+        };
+
+        self.stream_payroll_file(
+            &version_data.object_key,
+            version_data.filename,
+            version_data.content_type,
+            version_data.file_size,
+            content_hash,
+            request
+        ).await
     }
 
     #[executor]
@@ -65,45 +163,30 @@ impl PayrollService {
         self.payroll_repository.get_user_by_payroll_id(tx, payroll_id).await
     }
 
+    /// Payrolls of the users of the company, each one with its object in the bucket
+    #[executor]
+    pub async fn count_payrolls_by_company_id(&self, company_id: i64) -> Result<i64, AppError> {
+        self.payroll_repository.count_payrolls_by_company_id(tx, company_id).await
+    }
+
     async fn do_create_payroll(
         &self,
         tx: &mut SqliteConnection,
         payroll: CreatePayrollDto,
         file_path: &str,
         file_name: &str,
-        original_file_name: &str,
-        file_size: i64
-    ) -> Result<RetrievePayrollDto, AppError>
+        original_file_name: &str
+    ) -> Result<UploadedPayrollDto, AppError>
     {
-        if &original_file_name[original_file_name.len() - 4..] != ".pdf" {
-            return Err(AppError::new(
-                format!("Invalid file type: {}", original_file_name),
-                AppErrorType::BadRequest,
-                None
-            ));
-        }
-
-        // check if file is pdf
-        {
-            let file_path = file_path.to_string();
-            web::block(move || {
-                check_pdf(&file_path)
-            })
-            .await
-            .map_err(|err| AppError::new(
-                format!("Failed to check pdf: {}", err),
-                AppErrorType::BadRequest,
-                None
-            ))?
-        }?;
-
+        let sanitized = Self::sanitize_payroll_file(file_path, original_file_name).await?;
 
         let create_payroll_db = CreatePayrollDb::from_create_payroll_dto(
             payroll,
             file_name.to_string(),
             original_file_name.to_string(),
             String::from("application/pdf"),
-            file_size as i64,
+            sanitized.file_size,
+            sanitized.content_hash,
             chrono::Utc::now().naive_utc().to_string()
         )?;
 
@@ -119,6 +202,353 @@ impl PayrollService {
         };
         
 
-        Ok(created_payroll.to_retrieve_payroll_dto())
+        Ok(UploadedPayrollDto {
+            payroll: created_payroll.to_retrieve_payroll_dto(),
+            removed_content: sanitized.removed
+        })
+    }
+
+    async fn do_bulk_create_payrolls(&self, tx: &mut SqliteConnection, actor: &Claims, company_id: i64, manifest: FieldData, file_path: &str) -> Result<BulkPayrollReportDto, AppError> {
+        let manifest_name = manifest.file_name.unwrap_or_default();
+        let entries = BulkPayrollEntryDto::from_manifest(&manifest_name, &manifest.content)?;
+
+        let file_config = &config::get().file;
+        let extract_dir = format!("{}/{}", file_config.temp_upload_dir, Uuid::now_v7());
+        tokio::fs::create_dir(&extract_dir).await.map_err(AppError::internal_from_generic)?;
+
+        let extracted = {
+            let file_path = file_path.to_string();
+            let extract_dir = extract_dir.clone();
+            let (max_file_size, max_total_size) = (file_config.max_size, file_config.max_uncompressed_size);
+
+            web::block(move || {
+                extract_zip(&file_path, &extract_dir, max_file_size, max_total_size)
+            })
+            .await
+            .map_err(AppError::internal_from_generic)
+        };
+
+        let mut files = match extracted {
+            Ok(Ok(files)) => files,
+            Ok(Err(e)) | Err(e) => {
+                tokio::fs::remove_dir_all(&extract_dir).await.map_err(AppError::internal_from_generic)?;
+                return Err(e);
+            }
+        };
+
+        let mut results = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let result = match files.remove(&entry.filename) {
+                Some(file) => self.create_bulk_payroll(tx, actor, company_id, &entry, &file).await,
+                None => Err(AppError::new(
+                    String::from(r#"File "$1" is not in the archive"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![entry.filename.clone()])
+                ))
+            };
+
+            results.push(BulkPayrollResultDto::from_result(entry.filename, result));
+        }
+
+        for filename in files.into_keys() {
+            results.push(BulkPayrollResultDto::from_result(filename.clone(), Err(AppError::new(
+                String::from(r#"File "$1" is not in the manifest"#),
+                AppErrorType::BadRequest,
+                Some(vec![filename])
+            ))));
+        }
+
+        tokio::fs::remove_dir_all(&extract_dir).await.map_err(AppError::internal_from_generic)?;
+
+        Ok(BulkPayrollReportDto::from_results(results))
+    }
+
+    /// Employees are only looked up in the company of the upload. Unknown employees and the ones the actor cannot
+    /// upload payrolls for get the same error, so the manifest cannot be used to find out who exists.
+    async fn create_bulk_payroll(
+        &self,
+        tx: &mut SqliteConnection,
+        actor: &Claims,
+        company_id: i64,
+        entry: &BulkPayrollEntryDto,
+        file: &ExtractedFile
+    ) -> Result<UploadedPayrollDto, AppError>
+    {
+        let (user_id, employee) = match (&entry.username, entry.user_id) {
+            (Some(username), None) => (
+                service::get().user().get_company_user_id_by_username_executor(tx, company_id, username).await?,
+                username.clone()
+            ),
+            (None, Some(user_id)) => (
+                service::get().user().get_company_by_user_id_executor(tx, user_id).await?
+                    .filter(|user_company_id| *user_company_id == company_id)
+                    .map(|_| user_id),
+                user_id.to_string()
+            ),
+            _ => return Err(AppError::new(
+                String::from("Each entry must have either a username or a user id"),
+                AppErrorType::BadRequest,
+                None
+            ))
+        };
+
+        let user_id = match user_id {
+            Some(user_id) if service::get().permission().authorize_executor(tx, actor, Operation::Create, Resource::Payrolls(Some(user_id))).await? => user_id,
+            _ => return Err(AppError::new(
+                String::from(r#"There is no employee "$1" that you can upload payrolls for in the company"#),
+                AppErrorType::BadRequest,
+                Some(vec![employee])
+            ))
+        };
+
+        let payroll = CreatePayrollDto {
+            date: entry.date.clone(),
+            user_id
+        };
+
+        self.do_create_payroll(tx, payroll, &file.file_path, &file.unique_file_name, &entry.filename).await
+    }
+
+    async fn do_split_payroll(
+        &self,
+        tx: &mut SqliteConnection,
+        actor: &Claims,
+        company_id: i64,
+        split: SplitPayrollDto,
+        file_path: &str,
+        original_file_name: &str
+    ) -> Result<SplitPayrollReportDto, AppError>
+    {
+        // The combined document is sanitized before it is split, so its parts have no active content left
+        let sanitized = Self::sanitize_payroll_file(file_path, original_file_name).await?;
+        Payroll::check_date(&split.date)?;
+
+        let users = service::get().user().get_user_identifiers_by_company_id_executor(tx, company_id).await?;
+        let identifiers = split.identifier.index(&users);
+        let national_ids = EmployeeIdentifier::NationalId.index(&users);
+
+        let page_texts = {
+            let file_path = file_path.to_string();
+            web::block(move || {
+                extract_page_texts(&file_path)
+            })
+            .await
+            .map_err(AppError::internal_from_generic)?
+        }?;
+
+        let (employees, unmatched_pages) = assign_pages(&page_texts, split.identifier, &identifiers, &national_ids);
+
+        let mut documents = Vec::with_capacity(employees.len());
+        let mut authorized = Vec::new();
+
+        for employee in employees {
+            let allowed = service::get().permission().authorize_executor(tx, actor, Operation::Create, Resource::Payrolls(Some(employee.user_id))).await?;
+
+            if allowed && !split.dry_run {
+                authorized.push(documents.len());
+            }
+
+            documents.push(SplitPayrollDocumentDto {
+                user_id: employee.user_id,
+                identifier: allowed.then_some(employee.identifier),
+                pages: employee.pages,
+                payroll: None,
+                error: if allowed { None } else { Some(permission_denied()) }
+            });
+        }
+
+        if !authorized.is_empty() {
+            let file_config = &config::get().file;
+            let split_dir = format!("{}/{}", file_config.temp_upload_dir, Uuid::now_v7());
+            tokio::fs::create_dir(&split_dir).await.map_err(AppError::internal_from_generic)?;
+
+            let split_files = {
+                let file_path = file_path.to_string();
+                let split_dir = split_dir.clone();
+                let page_groups: Vec<Vec<u32>> = authorized.iter().map(|index| documents[*index].pages.clone()).collect();
+
+                web::block(move || {
+                    split_pdf(&file_path, &page_groups, &split_dir)
+                })
+                .await
+                .map_err(AppError::internal_from_generic)
+            };
+
+            let split_files = match split_files {
+                Ok(Ok(split_files)) => split_files,
+                Ok(Err(e)) | Err(e) => {
+                    tokio::fs::remove_dir_all(&split_dir).await.map_err(AppError::internal_from_generic)?;
+                    return Err(e);
+                }
+            };
+
+            let stem = original_file_name.strip_suffix(".pdf").unwrap_or(original_file_name);
+
+            for (index, file) in authorized.into_iter().zip(split_files) {
+                let document = &mut documents[index];
+
+                let payroll = CreatePayrollDto {
+                    date: split.date.clone(),
+                    user_id: document.user_id
+                };
+                let file_name = format!("{}_{}.pdf", stem, document.identifier.as_deref().unwrap_or_default());
+
+                match self.do_create_payroll(tx, payroll, &file.file_path, &file.unique_file_name, &file_name).await {
+                    Ok(payroll) => document.payroll = Some(payroll),
+                    Err(e) => document.error = Some(e)
+                }
+            }
+
+            tokio::fs::remove_dir_all(&split_dir).await.map_err(AppError::internal_from_generic)?;
+        }
+
+        Ok(SplitPayrollReportDto {
+            dry_run: split.dry_run,
+            pages: page_texts.len(),
+            documents,
+            unmatched_pages,
+            removed_content: sanitized.removed
+        })
+    }
+
+    async fn do_replace_payroll_file(&self, payroll_id: i64, file_path: &str, file_name: &str, original_file_name: &str) -> Result<UploadedPayrollDto, AppError> {
+        let sanitized = Self::sanitize_payroll_file(file_path, original_file_name).await?;
+
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        self.bucket_service.upload_file(bucket_name, file_path, file_name).await?;
+
+        match self.replace_payroll_record(payroll_id, file_name, original_file_name, &sanitized).await {
+            Ok(updated_payroll) => Ok(UploadedPayrollDto {
+                payroll: updated_payroll.to_retrieve_payroll_dto(),
+                removed_content: sanitized.removed
+            }),
+            Err(e) => {
+                // Nothing references the new file once the transaction is rolled back. Failures to remove it are
+                // already logged when the error is created
+                self.bucket_service.remove_file(bucket_name, file_name).await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    /// Moves the current file of the payroll to its prior versions and points the payroll to the new one
+    #[executor(transaction)]
+    async fn replace_payroll_record(&self, payroll_id: i64, file_name: &str, original_file_name: &str, sanitized: &SanitizedPdf) -> Result<RetrievePayrollDb, AppError> {
+        let current = self.get_existing_payroll_file(tx, payroll_id).await?;
+
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        let update_payroll_file_db = UpdatePayrollFileDb::from_replaced_file(
+            &current,
+            file_name.to_string(),
+            original_file_name.to_string(),
+            String::from("application/pdf"),
+            sanitized.file_size,
+            sanitized.content_hash.clone(),
+            now.clone()
+        )?;
+
+        // The current file keeps its object, it only moves to the prior versions
+        let create_payroll_version_db = CreatePayrollVersionDb::from_replaced_file(payroll_id, current, now);
+
+        self.payroll_repository.create_payroll_version(tx, &create_payroll_version_db).await?;
+        self.payroll_repository.update_payroll_file(tx, payroll_id, &update_payroll_file_db).await
+    }
+
+    /// Payroll files must be valid PDF documents with a `.pdf` name. Their active content is stripped before they are
+    /// stored.
+    async fn sanitize_payroll_file(file_path: &str, original_file_name: &str) -> Result<SanitizedPdf, AppError> {
+        if !original_file_name.ends_with(".pdf") {
+            return Err(AppError::new(
+                format!("Invalid file type: {}", original_file_name),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let file_path = file_path.to_string();
+        let max_uncompressed_size = config::get().file.max_uncompressed_size;
+
+        web::block(move || {
+            sanitize_pdf(&file_path, max_uncompressed_size)
+        })
+        .await
+        .map_err(|err| AppError::new(
+            format!("Failed to check pdf: {}", err),
+            AppErrorType::BadRequest,
+            None
+        ))?
+    }
+
+    /// The ETag of the file is its content hash, so it only changes along with the content
+    async fn stream_payroll_file(
+        &self,
+        object_key: &str,
+        filename: String,
+        content_type: String,
+        file_size: i64,
+        content_hash: String,
+        request: &DownloadRequest
+    ) -> Result<PayrollDownload, AppError>
+    {
+        let etag = format!("\"{}\"", content_hash);
+
+        if request.is_not_modified(&etag) {
+            return Ok(PayrollDownload::NotModified {
+                etag
+            });
+        }
+
+        let range = match request.range(file_size as u64, Some(&etag)) {
+            RangeRequest::Full => None,
+            RangeRequest::Partial(range) => Some(range),
+            RangeRequest::NotSatisfiable => return Ok(PayrollDownload::RangeNotSatisfiable {
+                file_size
+            })
+        };
+
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        let (stream_info, expected_size) = match range {
+            Some(range) => (
+                self.bucket_service.get_file_range_stream(bucket_name, object_key, Some(range.start), Some(range.length())).await?,
+                range.length() as i64
+            ),
+            None => (self.bucket_service.get_file_stream(bucket_name, object_key).await?, file_size)
+        };
+
+        if stream_info.size != expected_size {
+            return Err(AppError::new(
+                format!("File size mismatch: expected {}, got {}", expected_size, stream_info.size),
+                AppErrorType::InternalServerError,
+                None
+            ));
+        }
+
+        Ok(PayrollDownload::File(DownloadPayrollDto {
+            filename,
+            content_type,
+            file_size,
+            etag,
+            range,
+            stream: stream_info.stream
+        }))
+    }
+
+    /// Hash of a file uploaded before hashes were stored. Once stored, only the first download reads the file twice.
+    async fn hash_stored_file(&self, object_key: &str) -> Result<String, AppError> {
+        let bucket_name = &config::get().bucket.payroll_base_bucket_name;
+        self.bucket_service.hash_file(bucket_name, object_key).await
+    }
+
+    async fn get_existing_payroll_file(&self, tx: &mut SqliteConnection, payroll_id: i64) -> Result<RetrievePayrollFileDb, AppError> {
+        match self.payroll_repository.get_payroll_file_by_id(tx, payroll_id).await? {
+            Some(payroll) => Ok(payroll),
+            None => Err(AppError::new(
+                String::from(r#"Payroll with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![payroll_id.to_string()])
+            ))
+        }
     }
 }
@@ -0,0 +1,66 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+use super::payroll::RetrievePayrollFileDb;
+
+
+/// File of a payroll that was replaced by a newer one. It is kept in the bucket until the payroll is deleted.
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreatePayrollVersionDb",
+    fields(payroll_id, version, object_key, filename, content_type, file_size, content_hash, uploaded_at, replaced_at)
+))]
+#[custom_model(model(
+    name = "RetrievePayrollVersionDb",
+    fields(id, payroll_id, version, filename, file_size, uploaded_at, replaced_at),
+    extra_derives(FromRow)
+))]
+#[custom_model(model(
+    name = "RetrievePayrollVersionDto",
+    fields(id, payroll_id, version, filename, file_size, uploaded_at, replaced_at),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct PayrollVersion {
+    id: i64,
+    payroll_id: i64,
+    version: i64,
+    object_key: String,
+    filename: String,
+    content_type: String,
+    file_size: i64,
+    content_hash: Option<String>,
+    uploaded_at: String,
+    replaced_at: String
+}
+
+impl CreatePayrollVersionDb {
+    pub fn from_replaced_file(payroll_id: i64, file: RetrievePayrollFileDb, replaced_at: String) -> CreatePayrollVersionDb {
+        CreatePayrollVersionDb {
+            payroll_id,
+            version: file.version,
+            object_key: file.object_key,
+            filename: file.filename,
+            content_type: file.content_type,
+            file_size: file.file_size,
+            content_hash: file.content_hash,
+            uploaded_at: file.uploaded_at,
+            replaced_at
+        }
+    }
+}
+
+impl RetrievePayrollVersionDb {
+    pub fn to_retrieve_payroll_version_dto(self) -> RetrievePayrollVersionDto {
+        RetrievePayrollVersionDto {
+            id: self.id,
+            payroll_id: self.payroll_id,
+            version: self.version,
+            filename: self.filename,
+            file_size: self.file_size,
+            uploaded_at: self.uploaded_at,
+            replaced_at: self.replaced_at
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod permission_dto;
\ No newline at end of file
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::policy::ScopeCheck, entities::permission::permission::{Operation, Permission, ResourceKind, Scope, ScopeKind}};
+
+/// `user_id` explains the permissions of another user instead of the ones of the actor
+#[derive(Deserialize)]
+pub struct ExplainPermissionDto {
+    pub action: Operation,
+    pub resource: ResourceKind,
+    pub resource_id: Option<i64>,
+    pub user_id: Option<i64>
+}
+
+/// Permissions of a user or API key, with the bitmasks decoded into operations per scope
+#[derive(Serialize)]
+pub struct DecodedPermissionDto {
+    pub user_id: i64,
+    pub company_id: Option<i64>,
+    pub api_key_id: Option<i64>,
+    pub user: DecodedScopesDto,
+    pub payroll: DecodedScopesDto,
+    pub company: DecodedScopesDto
+}
+
+#[derive(Serialize)]
+pub struct DecodedScopesDto {
+    pub bits: i16,
+    pub any: Vec<Operation>,
+    pub self_company: Vec<Operation>,
+    pub self_department: Vec<Operation>,
+    pub owned: Vec<Operation>
+}
+
+impl DecodedScopesDto {
+    pub fn from_bits(bits: i16) -> DecodedScopesDto {
+        DecodedScopesDto {
+            bits,
+            any: Permission::operations(bits, Scope::Any),
+            self_company: Permission::operations(bits, Scope::SelfCompany),
+            self_department: Permission::operations(bits, Scope::SelfDepartment),
+            owned: Permission::operations(bits, Scope::Owned)
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PermissionExplanationDto {
+    pub permissions: DecodedPermissionDto,
+    pub action: Operation,
+    pub resource: ResourceKind,
+    pub resource_id: Option<i64>,
+    /// User that owns the resource, for the `owned` scope
+    pub owner_user_id: Option<i64>,
+    /// Company of the resource, for the `self_company` scope
+    pub resource_company_id: Option<i64>,
+    /// Department of the resource, for the `self_department` scope
+    pub resource_department_id: Option<i64>,
+    pub checks: Vec<ScopeCheckDto>,
+    pub allowed: bool
+}
+
+/// Result of a scope. `in_policy` tells if the scope can allow the action on the entity, `held` if the permission
+/// includes it, and `matched` if it allows the action on the resource.
+#[derive(Serialize)]
+pub struct ScopeCheckDto {
+    pub scope: ScopeKind,
+    pub in_policy: bool,
+    pub held: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub same_company: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub held_through_grant: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manages_department: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owns_resource: Option<bool>,
+    pub matched: bool
+}
+
+impl ScopeCheckDto {
+    pub fn from_scope_check(check: ScopeCheck) -> ScopeCheckDto {
+        ScopeCheckDto {
+            scope: check.scope,
+            in_policy: check.in_policy,
+            held: check.held,
+            same_company: check.same_company,
+            held_through_grant: check.held_through_grant,
+            manages_department: check.manages_department,
+            owns_resource: check.owns_resource,
+            matched: check.matched
+        }
+    }
+
+    /// Clears how the subject relates to the resource, for resources the actor cannot read
+    pub fn hide_resource_relations(&mut self) {
+        self.same_company = None;
+        self.held_through_grant = None;
+        self.manages_department = None;
+        self.owns_resource = None;
+    }
+}
@@ -1,3 +1,6 @@
 pub mod permission;
 pub mod permission_service;
 pub mod permission_repository;
+pub mod permission_controller;
+pub mod permission_policy;
+pub mod custom_dto;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub struct Permission {
     pub user_id: i64,
@@ -31,51 +31,71 @@ impl Permission {
         }
     }
 
-    pub fn from_role(user_id: i64, role: Role) -> Permission {
-        let mut permission = Permission::default(user_id);
-
-        match role {
-            Role::SuperAdmin => {
-                permission.set_user(Scope::Any(Operation::Create));
-                permission.set_user(Scope::Any(Operation::Read));
-                permission.set_user(Scope::Any(Operation::Update));
-                permission.set_user(Scope::Any(Operation::Delete));
-
-                permission.set_payroll(Scope::Any(Operation::Create));
-                permission.set_payroll(Scope::Any(Operation::Read));
-                permission.set_payroll(Scope::Any(Operation::Update));
-                permission.set_payroll(Scope::Any(Operation::Delete));
-
-                permission.set_company(Scope::Any(Operation::Create));
-                permission.set_company(Scope::Any(Operation::Read));
-                permission.set_company(Scope::Any(Operation::Update));
-                permission.set_company(Scope::Any(Operation::Delete));
-            },
-            Role::Admin => {
-                permission.set_user(Scope::SelfCompany(Operation::Create));
-                permission.set_user(Scope::SelfCompany(Operation::Read));
-                permission.set_user(Scope::SelfCompany(Operation::Update));
-                permission.set_user(Scope::SelfCompany(Operation::Delete));
-
-                permission.set_payroll(Scope::SelfCompany(Operation::Create));
-                permission.set_payroll(Scope::SelfCompany(Operation::Read));
-                permission.set_payroll(Scope::SelfCompany(Operation::Update));
-                permission.set_payroll(Scope::SelfCompany(Operation::Delete));
-
-                permission.set_company(Scope::SelfCompany(Operation::Read));
-                permission.set_company(Scope::SelfCompany(Operation::Update));
-                permission.set_company(Scope::SelfCompany(Operation::Delete));
-            },
-            Role::User => {
-                permission.set_user(Scope::Owned(Operation::Read));
-
-                permission.set_payroll(Scope::Owned(Operation::Read));
-
-                permission.set_company(Scope::Owned(Operation::Read));
-            },
-        };
+    /// Whether only bits of the known scopes and operations are set
+    pub fn is_valid(&self) -> bool {
+        let mask = Operation::ALL.iter().fold(0, |mask, operation| {
+            ScopeKind::ALL.iter().fold(mask, |mask, scope| mask | scope.scope(*operation).mask())
+        });
+
+        [self.user, self.payroll, self.company].iter().all(|bits| bits & !mask == 0)
+    }
 
-        permission
+    /// Whether this permission allows everything `other` does. A scope implies the narrower ones, so `Any` includes
+    /// `SelfCompany`, which includes `SelfDepartment` and `Owned`.
+    pub fn includes(&self, other: &Permission) -> bool {
+        Operation::ALL.iter().all(|operation| {
+            let includes = |held: i16, requested: i16| {
+                let any = held & Scope::Any(*operation).mask() != 0;
+                let self_company = any || held & Scope::SelfCompany(*operation).mask() != 0;
+                let self_department = self_company || held & Scope::SelfDepartment(*operation).mask() != 0;
+                let owned = self_company || held & Scope::Owned(*operation).mask() != 0;
+
+                (any || requested & Scope::Any(*operation).mask() == 0) &&
+                (self_company || requested & Scope::SelfCompany(*operation).mask() == 0) &&
+                (self_department || requested & Scope::SelfDepartment(*operation).mask() == 0) &&
+                (owned || requested & Scope::Owned(*operation).mask() == 0)
+            };
+
+            includes(self.user, other.user) &&
+            includes(self.payroll, other.payroll) &&
+            includes(self.company, other.company)
+        })
+    }
+
+    /// Whether this permission allows anything `other` does, either in the same scope or in a broader one
+    pub fn overlaps(&self, other: &Permission) -> bool {
+        (0..16).map(|bit| 1i16 << bit).any(|mask| {
+            [
+                Permission::new(other.user_id, other.user & mask, 0, 0),
+                Permission::new(other.user_id, 0, other.payroll & mask, 0),
+                Permission::new(other.user_id, 0, 0, other.company & mask)
+            ]
+            .iter()
+            .any(|single| (single.user | single.payroll | single.company) != 0 && self.includes(single))
+        })
+    }
+
+    /// Whether every permission is limited to the company of the holder
+    pub fn is_company_scoped(&self) -> bool {
+        let company_mask = Operation::ALL.iter().fold(0, |mask, operation| mask | Scope::SelfCompany(*operation).mask());
+
+        [self.user, self.payroll, self.company].iter().all(|bits| bits & !company_mask == 0)
+    }
+
+    /// Whether `grantor` holds every company scoped permission of this one over the company it is granted for.
+    /// `same_company` tells if the grantor belongs to that company.
+    pub fn can_be_granted_by(&self, grantor: &Permission, same_company: bool) -> bool {
+        Operation::ALL.iter().all(|operation| {
+            let holds = |granted: i16, held: i16| {
+                granted & Scope::SelfCompany(*operation).mask() == 0 ||
+                held & Scope::Any(*operation).mask() != 0 ||
+                (same_company && held & Scope::SelfCompany(*operation).mask() != 0)
+            };
+
+            holds(self.user, grantor.user) &&
+            holds(self.payroll, grantor.payroll) &&
+            holds(self.company, grantor.company)
+        })
     }
 
     pub fn user(&self, scope: Scope) -> bool {
@@ -101,9 +121,30 @@ impl Permission {
     pub fn set_company(&mut self, scope: Scope) {
         self.company |= scope.mask();
     }
+
+    pub fn bits(&self, kind: ResourceKind) -> i16 {
+        match kind {
+            ResourceKind::User => self.user,
+            ResourceKind::Payroll => self.payroll,
+            ResourceKind::Company => self.company
+        }
+    }
+
+    pub fn holds(&self, kind: ResourceKind, scope: Scope) -> bool {
+        self.bits(kind) & scope.mask() != 0
+    }
+
+    /// Operations allowed by `bits` in the scope built by `scope`, e.g. `Permission::operations(bits, Scope::Any)`
+    pub fn operations(bits: i16, scope: fn(Operation) -> Scope) -> Vec<Operation> {
+        Operation::ALL.iter()
+            .copied()
+            .filter(|operation| bits & scope(*operation).mask() != 0)
+            .collect()
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
 pub enum Operation {
     Create,
     Read,
@@ -112,6 +153,8 @@ pub enum Operation {
 }
 
 impl Operation {
+    pub const ALL: [Operation; 4] = [Operation::Create, Operation::Read, Operation::Update, Operation::Delete];
+
     fn value(&self) -> i16 {
         match self {
             Operation::Create => 0,
@@ -125,7 +168,9 @@ impl Operation {
 pub enum Scope {
     Any(Operation),
     SelfCompany(Operation),
-    Owned(Operation)
+    Owned(Operation),
+    /// Resources of the users of the departments managed by the holder, including their subdepartments
+    SelfDepartment(Operation)
 }
 
 impl Scope {
@@ -134,15 +179,40 @@ impl Scope {
             Scope::Any(operation) => operation.value(),
             Scope::SelfCompany(operation) => operation.value() + 4,
             Scope::Owned(operation) => operation.value() + 8,
+            Scope::SelfDepartment(operation) => operation.value() + 12
         };
 
         1 << offset
     }
 }
 
-#[derive(Clone, Copy, Deserialize)]
-pub enum Role {
-    SuperAdmin,
-    Admin,
-    User
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScopeKind {
+    Any,
+    SelfCompany,
+    SelfDepartment,
+    Owned
+}
+
+impl ScopeKind {
+    pub const ALL: [ScopeKind; 4] = [ScopeKind::Any, ScopeKind::SelfCompany, ScopeKind::SelfDepartment, ScopeKind::Owned];
+
+    pub fn scope(&self, operation: Operation) -> Scope {
+        match self {
+            ScopeKind::Any => Scope::Any(operation),
+            ScopeKind::SelfCompany => Scope::SelfCompany(operation),
+            ScopeKind::SelfDepartment => Scope::SelfDepartment(operation),
+            ScopeKind::Owned => Scope::Owned(operation)
+        }
+    }
+}
+
+/// Resources with their own permission bitmask
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    User,
+    Payroll,
+    Company
 }
@@ -0,0 +1,32 @@
+use actix_web::{web, Responder};
+
+use crate::{auth::jwt::AuthenticatedClaims, service, util::json_response::json_response};
+
+use super::{custom_dto::permission_dto::ExplainPermissionDto, permission_policy::ExplainPermissionClaims};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/permissions")
+            .route("/me", web::get().to(get_own_permissions))
+            .route("/explain", web::get().to(explain_permission))
+    );
+}
+
+/// Every user can see their own permissions. With an API key these are the permissions of the key.
+pub async fn get_own_permissions(claims: AuthenticatedClaims) -> impl Responder {
+    let AuthenticatedClaims(claims) = claims;
+
+    let permissions = service::get().permission().get_decoded_permission(&claims).await;
+
+    json_response(&permissions)
+}
+
+/// Tells why an action on a resource is allowed or denied, to debug access denials
+pub async fn explain_permission(query: web::Query<ExplainPermissionDto>, claims: ExplainPermissionClaims) -> impl Responder {
+    let ExplainPermissionClaims(claims) = claims;
+    let query = query.into_inner();
+
+    let explanation = service::get().permission().explain(&claims, query.user_id, query.action, query.resource, query.resource_id).await;
+
+    json_response(&explanation)
+}
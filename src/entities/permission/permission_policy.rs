@@ -0,0 +1,9 @@
+use crate::{auth::policy::{query_id, Action, Resource}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, SelfCompany}}};
+
+authorized_claims!(
+    /// Explaining the permissions of the `user_id` query parameter, or the ones of the actor without it, requires
+    /// being able to read them as an admin
+    ExplainPermissionClaims,
+    action: Action::new(Operation::Read).scopes(&[Any, SelfCompany]),
+    |req| Some(query_id(req, "user_id").map_or(Resource::CurrentUser, Resource::User))
+);
@@ -1,9 +1,9 @@
 use macros::executor;
 use sqlx::{SqliteConnection, SqlitePool};
 
-use crate::{entities::payroll::payroll::CreatePayrollDto, error::error::{AppError, AppErrorType}, service::{self}, user::user::CreateUserDto};
+use crate::{auth::{jwt::{Claims, TokenScope}, policy::{Action, Authorization, Resource, ScopeCheck}}, error::error::{AppError, AppErrorType}, service::{self}};
 
-use super::{permission::{Operation, Permission, Role, Scope}, permission_repository::PermissionRepository};
+use super::{custom_dto::permission_dto::{DecodedPermissionDto, DecodedScopesDto, PermissionExplanationDto, ScopeCheckDto}, permission::{Operation, Permission, ResourceKind, Scope, ScopeKind}, permission_repository::PermissionRepository};
 
 pub struct PermissionService {
     db_pool: SqlitePool,
@@ -18,8 +18,13 @@ impl PermissionService {
         }
     }
 
-    async fn get_permission(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Permission, AppError> {
-        match self.permission_repository.get_permission_by_user_id(tx, user_id).await? {
+    /// Permissions of the actor. Requests made with an API key only have the permissions of the key.
+    async fn get_permission(&self, tx: &mut SqliteConnection, actor: &Claims) -> Result<Permission, AppError> {
+        if let Some(api_key) = &actor.api_key {
+            return service::get().api_key().get_api_key_permission_executor(tx, api_key.id).await;
+        }
+
+        match self.permission_repository.get_permission_by_user_id(tx, actor.sub).await? {
             Some(permission) => Ok(permission),
             None => return Err(AppError::new(
                 String::from("User has not permission"),
@@ -29,123 +34,229 @@ impl PermissionService {
         }
     }
 
+    /// Whether the actor may perform the operation on the resource, following the policy of its entity
     #[executor]
-    pub async fn create_permission(&self, permission: &Permission) -> Result<(), AppError> {
-        self.permission_repository.create_permission(tx, permission).await
+    pub async fn authorize(&self, actor: &Claims, operation: Operation, resource: Resource) -> Result<bool, AppError> {
+        Ok(self.evaluate_executor(tx, actor, operation, resource).await?.allowed())
     }
 
+    /// Evaluates every scope of the actor for the operation on the resource. `SelfCompany` scopes match when the actor
+    /// belongs to the company of the resource, or holds an active grant over it. `SelfDepartment` scopes match when the
+    /// actor manages the department of the resource or one of its parents.
     #[executor]
-    pub async fn create_permission_from_role(&self, user_id: i64, role: Role) -> Result<(), AppError> {
-        let permission = Permission::from_role(user_id, role);
+    pub async fn evaluate(&self, actor: &Claims, operation: Operation, resource: Resource) -> Result<Authorization, AppError> {
+        let permission = self.get_permission(tx, actor).await?;
+        let kind = resource.kind();
+        let policy = kind.policy().scopes(operation);
 
-        self.permission_repository.create_permission(tx, &permission).await
-    }
+        let (owner_user_id, company_id, department_id) = Self::resolve(tx, actor, resource).await?;
+        let actor_company_id = Self::get_actor_company(tx, actor).await;
 
-    #[executor]
-    pub async fn create_user(&self, actor_user_id: i64, user: &CreateUserDto) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Create;
-
-        Ok(
-            permission.user(Scope::Any(operation)) ||
-            (permission.user(Scope::SelfCompany(operation)) && Self::actor_and_created_user_same_company(tx, actor_user_id, user).await)
-        )
-    }
+        // Grants are never used with API keys
+        let granted = match (company_id, &actor.api_key) {
+            (Some(company_id), None) => Some(service::get().company_grant().get_granted_permission_executor(tx, actor.sub, company_id).await?),
+            _ => None
+        };
 
-    #[executor]
-    pub async fn retrieve_user(&self, actor_user_id: i64, requested_user_id: i64) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Read;
-
-        Ok(
-            (permission.user(Scope::Owned(operation)) && actor_user_id == requested_user_id) ||
-            permission.user(Scope::Any(operation)) ||
-            (permission.user(Scope::SelfCompany(operation)) && Self::actor_and_requested_user_same_company(tx, actor_user_id, requested_user_id).await)
-        )
+        let any = permission.holds(kind, Scope::Any(operation));
+
+        let self_company = permission.holds(kind, Scope::SelfCompany(operation));
+        let same_company = company_id.map(|company_id| actor_company_id == Some(company_id));
+        let held_through_grant = granted.map(|granted| granted.holds(kind, Scope::SelfCompany(operation)));
+
+        let self_department = permission.holds(kind, Scope::SelfDepartment(operation));
+        // API keys act on behalf of their company, never as their creator, so they do not manage or own anything
+        let manages_department = match (department_id, &actor.api_key) {
+            (Some(department_id), None) => Some(service::get().department().is_managed_by_executor(tx, department_id, actor.sub).await?),
+            (Some(_), Some(_)) => Some(false),
+            (None, _) => None
+        };
+
+        let owned = permission.holds(kind, Scope::Owned(operation));
+        let owns_resource = owner_user_id.map(|owner_user_id| owner_user_id == actor.sub && actor.api_key.is_none());
+
+        Ok(Authorization {
+            owner_user_id,
+            company_id,
+            department_id,
+            checks: vec![
+                ScopeCheck {
+                    scope: ScopeKind::Any,
+                    in_policy: policy.contains(&ScopeKind::Any),
+                    held: any,
+                    same_company: None,
+                    held_through_grant: None,
+                    manages_department: None,
+                    owns_resource: None,
+                    matched: policy.contains(&ScopeKind::Any) && any
+                },
+                ScopeCheck {
+                    scope: ScopeKind::SelfCompany,
+                    in_policy: policy.contains(&ScopeKind::SelfCompany),
+                    held: self_company,
+                    same_company,
+                    held_through_grant,
+                    manages_department: None,
+                    owns_resource: None,
+                    matched: policy.contains(&ScopeKind::SelfCompany) &&
+                        ((self_company && same_company == Some(true)) || held_through_grant == Some(true))
+                },
+                ScopeCheck {
+                    scope: ScopeKind::SelfDepartment,
+                    in_policy: policy.contains(&ScopeKind::SelfDepartment),
+                    held: self_department,
+                    same_company: None,
+                    held_through_grant: None,
+                    manages_department,
+                    owns_resource: None,
+                    matched: policy.contains(&ScopeKind::SelfDepartment) && self_department && manages_department == Some(true)
+                },
+                ScopeCheck {
+                    scope: ScopeKind::Owned,
+                    in_policy: policy.contains(&ScopeKind::Owned),
+                    held: owned,
+                    same_company: None,
+                    held_through_grant: None,
+                    manages_department: None,
+                    owns_resource,
+                    matched: policy.contains(&ScopeKind::Owned) && owned && owns_resource == Some(true)
+                }
+            ]
+        })
     }
 
+    /// Whether the actor may perform the action of a route on the resource. API keys are never their creator, so they
+    /// must outrank every owner, the creator included.
     #[executor]
-    pub async fn create_company(&self, actor_user_id: i64) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Create;
+    pub async fn authorize_action(&self, actor: &Claims, action: &Action, resource: Resource) -> Result<bool, AppError> {
+        let authorization = self.evaluate_executor(tx, actor, action.operation, resource).await?;
+
+        if !authorization.allowed_for(action) {
+            return Ok(false);
+        }
 
-        Ok(
-            permission.company(Scope::Any(operation))
-        )
+        match authorization.owner_user_id {
+            Some(owner_user_id) if action.outranks_owner && (owner_user_id != actor.sub || actor.api_key.is_some()) => self.outranks_executor(tx, actor, owner_user_id).await,
+            _ => Ok(true)
+        }
     }
 
+    /// Whether the permissions of the actor include every permission of the user, so acting on the user cannot give
+    /// the actor more than they already hold
     #[executor]
-    pub async fn retrieve_companies(&self, actor_user_id: i64) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Read;
+    pub async fn outranks(&self, actor: &Claims, user_id: i64) -> Result<bool, AppError> {
+        let permission = self.get_permission(tx, actor).await?;
+        let user_permission = self.permission_repository.get_permission_by_user_id(tx, user_id).await?;
 
-        Ok(
-            permission.company(Scope::Any(operation))
-        )
+        Ok(user_permission.is_some_and(|user_permission| permission.includes(&user_permission)))
     }
 
+    /// Actors can only give permissions included in their own, e.g. through a role
     #[executor]
-    pub async fn create_payroll(&self, actor_user_id: i64, payroll: &CreatePayrollDto) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Create;
-
-        Ok(
-            permission.payroll(Scope::Any(operation)) ||
-            (permission.payroll(Scope::SelfCompany(operation)) && Self::actor_and_requested_user_same_company(tx, actor_user_id, payroll.user_id).await)
-        )
+    pub async fn can_give(&self, actor: &Claims, permission: &Permission) -> Result<bool, AppError> {
+        Ok(self.get_permission(tx, actor).await?.includes(permission))
     }
 
+    /// Actors can only grant over a company the company scoped permissions they hold over it
     #[executor]
-    pub async fn get_payrolls(&self, actor_user_id: i64, requested_user_id: Option<i64>) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Read;
-
-        Ok(
-            permission.payroll(Scope::Any(operation)) ||
-            if let Some(user_id) = requested_user_id {
-                (permission.payroll(Scope::Owned(operation)) && actor_user_id == user_id) ||
-                (permission.payroll(Scope::SelfCompany(operation)) && Self::actor_and_requested_user_same_company(tx, actor_user_id, user_id).await)
-            }
-            else { false }
-        )
+    pub async fn can_grant(&self, actor: &Claims, company_id: i64, permission: &Permission) -> Result<bool, AppError> {
+        let held = self.get_permission(tx, actor).await?;
+        let same_company = Self::get_actor_company(tx, actor).await == Some(company_id);
+
+        Ok(permission.can_be_granted_by(&held, same_company))
     }
 
+    /// Permissions of the actor, with the bitmasks decoded
     #[executor]
-    pub async fn get_payroll(&self, actor_user_id: i64, payroll_id: i64) -> Result<bool, AppError> {
-        let permission = self.get_permission(tx, actor_user_id).await?;
-        let operation = Operation::Read;
-
-        Ok(
-            permission.payroll(Scope::Any(operation)) ||
-            {
-                let user_id = service::get().payroll_service.get_user_by_payroll_id_executor(tx, payroll_id).await?;
-                (permission.payroll(Scope::Owned(operation)) && actor_user_id == user_id) ||
-                (permission.payroll(Scope::SelfCompany(operation)) && Self::actor_and_requested_user_same_company(tx, actor_user_id, user_id).await)
-            }
-        )
+    pub async fn get_decoded_permission(&self, actor: &Claims) -> Result<DecodedPermissionDto, AppError> {
+        let permission = self.get_permission(tx, actor).await?;
+        let company_id = Self::get_actor_company(tx, actor).await;
+
+        Ok(DecodedPermissionDto {
+            user_id: actor.sub,
+            company_id,
+            api_key_id: actor.api_key.as_ref().map(|api_key| api_key.id),
+            user: DecodedScopesDto::from_bits(permission.user),
+            payroll: DecodedScopesDto::from_bits(permission.payroll),
+            company: DecodedScopesDto::from_bits(permission.company)
+        })
     }
 
-    async fn actor_and_created_user_same_company(tx: &mut SqliteConnection, actor_user_id: i64, user: &CreateUserDto) -> bool {
-        let user_service = &service::get().user_service;
+    /// Evaluates every scope for the action on the resource, the same way routes are authorized. Explains the
+    /// permissions of `user_id` instead of the ones of the actor when given. The owner, company and department of the
+    /// resource, and how the subject relates to them, are only returned when the actor may read the resource.
+    #[executor]
+    pub async fn explain(&self, actor: &Claims, user_id: Option<i64>, action: Operation, resource: ResourceKind, resource_id: Option<i64>) -> Result<PermissionExplanationDto, AppError> {
+        let other_user = user_id
+            .filter(|user_id| *user_id != actor.sub)
+            .map(|user_id| Claims {
+                sub: user_id,
+                sid: 0,
+                scope: TokenScope::Full,
+                exp: 0,
+                act: None,
+                api_key: None
+            });
+        let subject = other_user.as_ref().unwrap_or(actor);
+
+        let permissions = self.get_decoded_permission_executor(tx, subject).await?;
+        let authorization = self.evaluate_executor(tx, subject, action, Resource::from_kind(resource, resource_id)).await?;
+        let readable = self.authorize_executor(tx, actor, Operation::Read, Resource::from_kind(resource, resource_id)).await?;
+
+        let allowed = authorization.allowed();
 
-        let actor_company_id = user_service.get_company_by_user_id_executor(tx, actor_user_id).await;
-        if let Ok(Some(actor_company_id)) = actor_company_id {
-            return actor_company_id == user.company_id
+        let mut checks: Vec<ScopeCheckDto> = authorization.checks.into_iter().map(ScopeCheckDto::from_scope_check).collect();
+
+        if !readable {
+            checks.iter_mut().for_each(ScopeCheckDto::hide_resource_relations);
         }
 
-        false
+        Ok(PermissionExplanationDto {
+            permissions,
+            action,
+            resource,
+            resource_id,
+            allowed,
+            owner_user_id: authorization.owner_user_id.filter(|_| readable),
+            resource_company_id: authorization.company_id.filter(|_| readable),
+            resource_department_id: authorization.department_id.filter(|_| readable),
+            checks
+        })
     }
 
-    async fn actor_and_requested_user_same_company(tx: &mut SqliteConnection, actor_user_id: i64, requested_user_id: i64) -> bool {
-        let user_service = &service::get().user_service;
+    /// User that owns the resource, and company and department it belongs to, when they apply
+    async fn resolve(tx: &mut SqliteConnection, actor: &Claims, resource: Resource) -> Result<(Option<i64>, Option<i64>, Option<i64>), AppError> {
+        let user_service = service::get().user();
+
+        let owner_user_id = match resource {
+            Resource::User(user_id) | Resource::Payrolls(Some(user_id)) => user_id,
+            Resource::CurrentUser => actor.sub,
+            Resource::Roles => return Ok((None, Self::get_actor_company(tx, actor).await, None)),
+            Resource::Payroll(payroll_id) => service::get().payroll().get_user_by_payroll_id_executor(tx, payroll_id).await?,
+            Resource::DepartmentPayrolls(department_id) => {
+                let company_id = service::get().department().get_company_by_department_id_executor(tx, department_id).await?;
+                return Ok((None, company_id, Some(department_id)));
+            },
+            Resource::Users(company_id) => return Ok((None, company_id, None)),
+            Resource::Company(company_id) | Resource::CompanyPayrolls(company_id) => return Ok((None, Some(company_id), None)),
+            Resource::Payrolls(None) | Resource::Companies => return Ok((None, None, None))
+        };
 
-        let actor_company_id = user_service.get_company_by_user_id_executor(tx, actor_user_id).await;
-        let requested_company_id = user_service.get_company_by_user_id_executor(tx, requested_user_id).await;
+        Ok((
+            Some(owner_user_id),
+            user_service.get_company_by_user_id_executor(tx, owner_user_id).await?,
+            user_service.get_department_by_user_id_executor(tx, owner_user_id).await?
+        ))
+    }
 
-        if let (Ok(Some(actor_company_id)), Ok(Some(requested_company_id))) = (actor_company_id, requested_company_id) {
-            return actor_company_id == requested_company_id
+    /// Company the actor acts on behalf of. For API keys it is the company of the key.
+    async fn get_actor_company(tx: &mut SqliteConnection, actor: &Claims) -> Option<i64> {
+        if let Some(api_key) = &actor.api_key {
+            return Some(api_key.company_id);
         }
-        
-        false
+
+        let user_service = service::get().user();
+
+        user_service.get_company_by_user_id_executor(tx, actor.sub).await.ok().flatten()
     }
 }
@@ -0,0 +1 @@
+pub mod role_dto;
\ No newline at end of file
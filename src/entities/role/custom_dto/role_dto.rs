@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+/// Permissions use the `Permission` bitmasks. Also used to replace the name and permissions of a role
+#[derive(Deserialize)]
+pub struct CreateRoleDto {
+    pub name: String,
+    pub user: i16,
+    pub payroll: i16,
+    pub company: i16
+}
@@ -0,0 +1,6 @@
+pub mod role;
+pub mod role_service;
+pub mod role_repository;
+pub mod role_controller;
+pub mod role_policy;
+pub mod custom_dto;
\ No newline at end of file
@@ -0,0 +1,98 @@
+use macros::DeriveCustomModel;
+use serde::Serialize;
+
+use crate::{entities::permission::permission::Permission, error::error::{AppError, AppErrorType}};
+
+use super::custom_dto::role_dto::CreateRoleDto;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateRoleDb",
+    fields(name, user, payroll, company)
+))]
+#[custom_model(model(
+    name = "RetrieveRoleDb",
+    fields(id, name, user, payroll, company, built_in)
+))]
+#[custom_model(model(
+    name = "RetrieveRoleDto",
+    fields(id, name, user, payroll, company, built_in),
+    extra_derives(Serialize)
+))]
+#[allow(dead_code)]
+pub struct Role {
+    id: i64,
+    name: String,
+    user: i16,
+    payroll: i16,
+    company: i16,
+    /// Roles seeded by the migrations. They cannot be changed or deleted
+    built_in: bool
+}
+
+impl Role {
+    pub const SUPER_ADMIN_ID: i64 = 1;
+    pub const ADMIN_ID: i64 = 2;
+    pub const USER_ID: i64 = 3;
+
+    pub fn check_name(name: &str) -> Result<(), AppError> {
+        if name.is_empty() || name.len() > 50 {
+            return Err(AppError::new(
+                String::from("The role name must be between 1 and 50 characters long"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_permission(permission: &Permission) -> Result<(), AppError> {
+        if !permission.is_valid() {
+            return Err(AppError::new(
+                String::from("The permissions of the role must be built from the scopes and operations"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl RetrieveRoleDb {
+    pub fn permission(&self, user_id: i64) -> Permission {
+        Permission::new(user_id, self.user, self.payroll, self.company)
+    }
+
+    pub fn to_retrieve_role_dto(self) -> RetrieveRoleDto {
+        RetrieveRoleDto {
+            id: self.id,
+            name: self.name,
+            user: self.user,
+            payroll: self.payroll,
+            company: self.company,
+            built_in: self.built_in
+        }
+    }
+}
+
+impl CreateRoleDb {
+    pub fn from_create_role_dto(role: CreateRoleDto) -> Result<CreateRoleDb, AppError> {
+        let name = role.name.trim().to_string();
+
+        Role::check_name(&name)?;
+        Role::check_permission(&Permission::new(0, role.user, role.payroll, role.company))?;
+
+        Ok(CreateRoleDb {
+            name,
+            user: role.user,
+            payroll: role.payroll,
+            company: role.company
+        })
+    }
+
+    pub fn permission(&self, user_id: i64) -> Permission {
+        Permission::new(user_id, self.user, self.payroll, self.company)
+    }
+}
@@ -0,0 +1,46 @@
+use actix_web::{web, Responder};
+
+use crate::{service, util::json_response::json_response};
+
+use super::{custom_dto::role_dto::CreateRoleDto, role_policy::{ManageRolesClaims, ReadRolesClaims}};
+
+/// Roles cannot be managed with API keys
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/roles")
+            .route("", web::post().to(create_role))
+            .route("", web::get().to(get_roles))
+            .route("/{role_id}", web::put().to(update_role))
+            .route("/{role_id}", web::delete().to(delete_role))
+    );
+}
+
+pub async fn create_role(role: web::Json<CreateRoleDto>, claims: ManageRolesClaims) -> impl Responder {
+    let ManageRolesClaims(claims) = claims;
+
+    let created = service::get().role().create_role(&claims, role.into_inner()).await;
+
+    json_response(&created)
+}
+
+pub async fn get_roles(_claims: ReadRolesClaims) -> impl Responder {
+    let roles = service::get().role().get_roles().await;
+
+    json_response(&roles)
+}
+
+pub async fn update_role(role_id: web::Path<i64>, role: web::Json<CreateRoleDto>, claims: ManageRolesClaims) -> impl Responder {
+    let ManageRolesClaims(claims) = claims;
+
+    let updated = service::get().role().update_role(&claims, role_id.into_inner(), role.into_inner()).await;
+
+    json_response(&updated)
+}
+
+pub async fn delete_role(role_id: web::Path<i64>, claims: ManageRolesClaims) -> impl Responder {
+    let ManageRolesClaims(claims) = claims;
+
+    let deleted = service::get().role().delete_role(&claims, role_id.into_inner()).await;
+
+    json_response(&deleted)
+}
@@ -0,0 +1,14 @@
+use crate::{auth::{jwt::TokenScope::Full, policy::{Action, Resource}}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, SelfCompany}}};
+
+// Roles are assigned to users, so they follow the policy of the users
+
+authorized_claims!(
+    /// Anyone that can read the users of their company may read the roles, to know what they can assign
+    ReadRolesClaims, action: Action::new(Operation::Read).scopes(&[Any, SelfCompany]), |_req| Some(Resource::Roles)
+);
+
+authorized_claims!(
+    /// Roles are shared by every company, so only users that can update any user manage them. The permissions of the
+    /// roles are checked against the ones of the actor when they are created, updated or deleted.
+    ManageRolesClaims, action: Action::new(Operation::Update).scopes(&[Any]).tokens(&[Full]), |_req| Some(Resource::Roles)
+);
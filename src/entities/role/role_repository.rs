@@ -0,0 +1,160 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::role::{CreateRoleDb, RetrieveRoleDb};
+
+pub struct RoleRepository {}
+
+impl RoleRepository {
+    pub fn new() -> RoleRepository {
+        RoleRepository {
+
+        }
+    }
+
+    pub async fn create_role(&self, tx: &mut SqliteConnection, role: &CreateRoleDb) -> Result<RetrieveRoleDb, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            INSERT INTO Role (name, user, payroll, company)
+            VALUES($1, $2, $3, $4)
+            RETURNING id as "id!: i64", name, user as "user: i16", payroll as "payroll: i16", company as "company: i16", built_in as "built_in: bool"
+            "#,
+            role.name,
+            role.user,
+            role.payroll,
+            role.company
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_roles(&self, tx: &mut SqliteConnection) -> Result<Vec<RetrieveRoleDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            SELECT id as "id!: i64", name, user as "user: i16", payroll as "payroll: i16", company as "company: i16", built_in as "built_in: bool"
+            FROM Role
+            ORDER BY id
+            "#
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_role_by_id(&self, tx: &mut SqliteConnection, role_id: i64) -> Result<Option<RetrieveRoleDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            SELECT id as "id!: i64", name, user as "user: i16", payroll as "payroll: i16", company as "company: i16", built_in as "built_in: bool"
+            FROM Role
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            role_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_role_by_name(&self, tx: &mut SqliteConnection, name: &str) -> Result<Option<RetrieveRoleDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            SELECT id as "id!: i64", name, user as "user: i16", payroll as "payroll: i16", company as "company: i16", built_in as "built_in: bool"
+            FROM Role
+            WHERE name = $1
+            LIMIT 1
+            "#,
+            name
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_role_by_user_id(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Option<RetrieveRoleDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            SELECT Role.id as "id!: i64", Role.name, Role.user as "user: i16", Role.payroll as "payroll: i16", Role.company as "company: i16", Role.built_in as "built_in: bool"
+            FROM AppUser
+            INNER JOIN Role ON Role.id = AppUser.role_id
+            WHERE AppUser.id = $1
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn role_exists_by_name(&self, tx: &mut SqliteConnection, name: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM Role
+            WHERE name = $1
+            LIMIT 1
+            "#,
+            name
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|val| val.is_some())
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_role(&self, tx: &mut SqliteConnection, role_id: i64, role: &CreateRoleDb) -> Result<RetrieveRoleDb, AppError> {
+        sqlx::query_as!(
+            RetrieveRoleDb,
+            r#"
+            UPDATE Role
+            SET name = $1, user = $2, payroll = $3, company = $4
+            WHERE id = $5
+            RETURNING id as "id!: i64", name, user as "user: i16", payroll as "payroll: i16", company as "company: i16", built_in as "built_in: bool"
+            "#,
+            role.name,
+            role.user,
+            role.payroll,
+            role.company,
+            role_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn delete_role(&self, tx: &mut SqliteConnection, role_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM Role
+            WHERE id = $1
+            "#,
+            role_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn count_users_by_role_id(&self, tx: &mut SqliteConnection, role_id: i64) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM AppUser
+            WHERE role_id = $1
+            "#,
+            role_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+}
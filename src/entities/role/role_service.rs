@@ -0,0 +1,156 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{auth::{jwt::Claims, policy::permission_denied}, entities::permission::permission::Permission, error::error::{AppError, AppErrorType}, service};
+
+use super::{custom_dto::role_dto::CreateRoleDto, role::{CreateRoleDb, RetrieveRoleDb, RetrieveRoleDto}, role_repository::RoleRepository};
+
+pub struct RoleService {
+    db_pool: SqlitePool,
+    role_repository: RoleRepository
+}
+
+impl RoleService {
+    pub fn new(db_pool: SqlitePool, role_repository: RoleRepository) -> RoleService {
+        RoleService {
+            db_pool,
+            role_repository
+        }
+    }
+
+    /// Actors can only create roles with permissions included in their own
+    #[executor]
+    pub async fn create_role(&self, actor: &Claims, role: CreateRoleDto) -> Result<RetrieveRoleDto, AppError> {
+        let create_role_db = CreateRoleDb::from_create_role_dto(role)?;
+        Self::check_actor_holds(tx, actor, &create_role_db.permission(actor.sub)).await?;
+
+        if self.role_repository.role_exists_by_name(tx, &create_role_db.name).await? {
+            return Err(Self::role_name_taken(create_role_db.name));
+        }
+
+        let created = self.role_repository.create_role(tx, &create_role_db).await?;
+
+        Ok(created.to_retrieve_role_dto())
+    }
+
+    #[executor]
+    pub async fn get_roles(&self) -> Result<Vec<RetrieveRoleDto>, AppError> {
+        let roles = self.role_repository.get_roles(tx).await?;
+
+        Ok(roles.into_iter().map(|role| role.to_retrieve_role_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn get_role(&self, role_id: i64) -> Result<RetrieveRoleDb, AppError> {
+        match self.role_repository.get_role_by_id(tx, role_id).await? {
+            Some(role) => Ok(role),
+            None => Err(Self::role_not_found(role_id))
+        }
+    }
+
+    #[executor]
+    pub async fn find_role(&self, role_id: i64) -> Result<Option<RetrieveRoleDb>, AppError> {
+        self.role_repository.get_role_by_id(tx, role_id).await
+    }
+
+    #[executor]
+    pub async fn get_role_by_name(&self, name: &str) -> Result<Option<RetrieveRoleDb>, AppError> {
+        self.role_repository.get_role_by_name(tx, name).await
+    }
+
+    #[executor]
+    pub async fn get_user_role(&self, user_id: i64) -> Result<RetrieveRoleDb, AppError> {
+        match self.role_repository.get_role_by_user_id(tx, user_id).await? {
+            Some(role) => Ok(role),
+            None => Err(AppError::new(
+                String::from(r#"User with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![user_id.to_string()])
+            ))
+        }
+    }
+
+    #[executor]
+    pub async fn role_exists(&self, role_id: i64) -> Result<bool, AppError> {
+        Ok(self.role_repository.get_role_by_id(tx, role_id).await?.is_some())
+    }
+
+    /// Replaces the name and permissions of a custom role. Users with the role get the new permissions right away, so
+    /// actors can only change roles whose permissions, current and new, are included in their own.
+    #[executor]
+    pub async fn update_role(&self, actor: &Claims, role_id: i64, role: CreateRoleDto) -> Result<RetrieveRoleDto, AppError> {
+        let existing = self.get_custom_role(tx, role_id).await?;
+        Self::check_actor_holds(tx, actor, &existing.permission(actor.sub)).await?;
+
+        let create_role_db = CreateRoleDb::from_create_role_dto(role)?;
+        Self::check_actor_holds(tx, actor, &create_role_db.permission(actor.sub)).await?;
+
+        if create_role_db.name != existing.name && self.role_repository.role_exists_by_name(tx, &create_role_db.name).await? {
+            return Err(Self::role_name_taken(create_role_db.name));
+        }
+
+        let updated = self.role_repository.update_role(tx, role_id, &create_role_db).await?;
+
+        Ok(updated.to_retrieve_role_dto())
+    }
+
+    /// Roles assigned to any user, even inactive ones, cannot be deleted. Actors can only delete roles whose permissions
+    /// are included in their own.
+    #[executor]
+    pub async fn delete_role(&self, actor: &Claims, role_id: i64) -> Result<(), AppError> {
+        let existing = self.get_custom_role(tx, role_id).await?;
+        Self::check_actor_holds(tx, actor, &existing.permission(actor.sub)).await?;
+
+        if self.role_repository.count_users_by_role_id(tx, role_id).await? > 0 {
+            return Err(AppError::new(
+                String::from(r#"Role with id "$1" is assigned to users"#),
+                AppErrorType::Conflict,
+                Some(vec![role_id.to_string()])
+            ));
+        }
+
+        self.role_repository.delete_role(tx, role_id).await
+    }
+
+    /// Built-in roles are read-only
+    async fn get_custom_role(&self, tx: &mut SqliteConnection, role_id: i64) -> Result<RetrieveRoleDb, AppError> {
+        let role = match self.role_repository.get_role_by_id(tx, role_id).await? {
+            Some(role) => role,
+            None => return Err(Self::role_not_found(role_id))
+        };
+
+        if role.built_in {
+            return Err(AppError::new(
+                String::from(r#"Role "$1" is built-in and cannot be changed"#),
+                AppErrorType::Conflict,
+                Some(vec![role.name])
+            ));
+        }
+
+        Ok(role)
+    }
+
+    async fn check_actor_holds(tx: &mut SqliteConnection, actor: &Claims, permission: &Permission) -> Result<(), AppError> {
+        if !service::get().permission().can_give_executor(tx, actor, permission).await? {
+            return Err(permission_denied());
+        }
+
+        Ok(())
+    }
+
+    fn role_not_found(role_id: i64) -> AppError {
+        AppError::new(
+            String::from(r#"Role with id "$1" does not exist"#),
+            AppErrorType::NotFound,
+            Some(vec![role_id.to_string()])
+        )
+    }
+
+    fn role_name_taken(name: String) -> AppError {
+        AppError::new(
+            String::from(r#"Role with name "$1" already exists"#),
+            AppErrorType::Conflict,
+            Some(vec![name])
+        )
+    }
+}
@@ -0,0 +1,3 @@
+pub mod session;
+pub mod session_service;
+pub mod session_repository;
\ No newline at end of file
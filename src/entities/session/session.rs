@@ -0,0 +1,29 @@
+use macros::DeriveCustomModel;
+
+#[derive(DeriveCustomModel)]
+#[custom_model(model(
+    name = "CreateSessionDb",
+    fields(user_id, refresh_token_hash, created_at, expires_at)
+))]
+#[custom_model(model(
+    name = "RetrieveSessionDb",
+    fields(id, user_id, expires_at, revoked_at)
+))]
+#[allow(dead_code)]
+pub struct Session {
+    id: i64,
+    user_id: i64,
+    refresh_token_hash: String,
+    previous_refresh_token_hash: Option<String>,
+    created_at: String,
+    expires_at: String,
+    last_refreshed_at: Option<String>,
+    revoked_at: Option<String>
+}
+
+/// A freshly opened or rotated session. `refresh_token` is the raw token and must only be sent to the client.
+pub struct IssuedSessionDto {
+    pub id: i64,
+    pub user_id: i64,
+    pub refresh_token: String
+}
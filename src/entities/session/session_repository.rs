@@ -0,0 +1,144 @@
+use sqlx::SqliteConnection;
+
+use crate::{error::error::AppError, util::db::to_app_error};
+
+use super::session::{CreateSessionDb, RetrieveSessionDb};
+
+pub struct SessionRepository {}
+
+impl SessionRepository {
+    pub fn new() -> SessionRepository {
+        SessionRepository {
+
+        }
+    }
+
+    pub async fn create_session(&self, tx: &mut SqliteConnection, session: &CreateSessionDb) -> Result<RetrieveSessionDb, AppError> {
+        sqlx::query_as!(
+            RetrieveSessionDb,
+            r#"
+            INSERT INTO Session (user_id, refresh_token_hash, created_at, expires_at)
+            VALUES($1, $2, $3, $4)
+            RETURNING id as "id!: i64", user_id, expires_at, revoked_at
+            "#,
+            session.user_id,
+            session.refresh_token_hash,
+            session.created_at,
+            session.expires_at
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_session_by_refresh_token_hash(&self, tx: &mut SqliteConnection, refresh_token_hash: &str) -> Result<Option<RetrieveSessionDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveSessionDb,
+            r#"
+            SELECT id as "id!: i64", user_id, expires_at, revoked_at
+            FROM Session
+            WHERE refresh_token_hash = $1
+            LIMIT 1
+            "#,
+            refresh_token_hash
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_session_by_previous_refresh_token_hash(&self, tx: &mut SqliteConnection, refresh_token_hash: &str) -> Result<Option<RetrieveSessionDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveSessionDb,
+            r#"
+            SELECT id as "id!: i64", user_id, expires_at, revoked_at
+            FROM Session
+            WHERE previous_refresh_token_hash = $1
+            LIMIT 1
+            "#,
+            refresh_token_hash
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Replaces the refresh token hash of an active session. Returns `false` if the session was rotated or revoked
+    /// concurrently, so the caller must not hand out the new token.
+    pub async fn rotate_refresh_token(
+        &self,
+        tx: &mut SqliteConnection,
+        session_id: i64,
+        current_hash: &str,
+        new_hash: &str,
+        refreshed_at: &str
+    ) -> Result<bool, AppError>
+    {
+        sqlx::query!(
+            r#"
+            UPDATE Session
+            SET previous_refresh_token_hash = refresh_token_hash,
+                refresh_token_hash = $1,
+                last_refreshed_at = $2
+            WHERE id = $3 AND refresh_token_hash = $4 AND revoked_at IS NULL
+            "#,
+            new_hash,
+            refreshed_at,
+            session_id,
+            current_hash
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn is_session_active(&self, tx: &mut SqliteConnection, session_id: i64, now: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM Session
+            WHERE id = $1 AND revoked_at IS NULL AND expires_at > $2
+            LIMIT 1
+            "#,
+            session_id,
+            now
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|val| val.is_some())
+        .map_err(to_app_error)
+    }
+
+    pub async fn revoke_session(&self, tx: &mut SqliteConnection, session_id: i64, revoked_at: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Session
+            SET revoked_at = $1
+            WHERE id = $2 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            session_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn revoke_user_sessions(&self, tx: &mut SqliteConnection, user_id: i64, revoked_at: &str) -> Result<u64, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE Session
+            SET revoked_at = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(to_app_error)
+    }
+}
@@ -0,0 +1,116 @@
+use macros::executor;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{config, error::error::{AppError, AppErrorType}, util::crypto::{generate_secret_token, hash_secret_token}};
+
+use super::{session::{CreateSessionDb, IssuedSessionDto}, session_repository::SessionRepository};
+
+pub struct SessionService {
+    db_pool: SqlitePool,
+    session_repository: SessionRepository
+}
+
+impl SessionService {
+    pub fn new(db_pool: SqlitePool, session_repository: SessionRepository) -> SessionService {
+        SessionService {
+            db_pool,
+            session_repository
+        }
+    }
+
+    #[executor]
+    pub async fn create_session(&self, user_id: i64) -> Result<IssuedSessionDto, AppError> {
+        let refresh_token = generate_secret_token();
+
+        let now = chrono::Utc::now();
+        let expires_at = now
+            .checked_add_signed(chrono::Duration::seconds(config::get().auth.refresh_token_ttl))
+            .expect("valid timestamp");
+
+        let session = self.session_repository.create_session(tx, &CreateSessionDb {
+            user_id,
+            refresh_token_hash: hash_secret_token(&refresh_token),
+            created_at: now.naive_utc().to_string(),
+            expires_at: expires_at.naive_utc().to_string()
+        }).await?;
+
+        Ok(IssuedSessionDto {
+            id: session.id,
+            user_id: session.user_id,
+            refresh_token
+        })
+    }
+
+    /// Exchanges a refresh token for a new one, keeping the same session.
+    ///
+    /// Presenting a refresh token that was already rotated means it has been leaked (either the legitimate client or
+    /// an attacker is replaying it), so the whole session is revoked.
+    #[executor]
+    pub async fn refresh_session(&self, refresh_token: &str) -> Result<IssuedSessionDto, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+        let current_hash = hash_secret_token(refresh_token);
+
+        let session = match self.session_repository.get_session_by_refresh_token_hash(tx, &current_hash).await? {
+            Some(session) => session,
+            None => {
+                if let Some(reused) = self.session_repository.get_session_by_previous_refresh_token_hash(tx, &current_hash).await? {
+                    self.session_repository.revoke_session(tx, reused.id, &now).await?;
+                }
+
+                return Err(Self::invalid_refresh_token());
+            }
+        };
+
+        if session.revoked_at.is_some() || session.expires_at <= now {
+            return Err(Self::invalid_refresh_token());
+        }
+
+        let new_refresh_token = generate_secret_token();
+        let rotated = self.session_repository.rotate_refresh_token(
+            tx,
+            session.id,
+            &current_hash,
+            &hash_secret_token(&new_refresh_token),
+            &now
+        ).await?;
+
+        if !rotated {
+            return Err(Self::invalid_refresh_token());
+        }
+
+        Ok(IssuedSessionDto {
+            id: session.id,
+            user_id: session.user_id,
+            refresh_token: new_refresh_token
+        })
+    }
+
+    #[executor]
+    pub async fn is_session_active(&self, session_id: i64) -> Result<bool, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.session_repository.is_session_active(tx, session_id, &now).await
+    }
+
+    #[executor]
+    pub async fn revoke_session(&self, session_id: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.session_repository.revoke_session(tx, session_id, &now).await
+    }
+
+    #[executor]
+    pub async fn revoke_user_sessions(&self, user_id: i64) -> Result<u64, AppError> {
+        let now = chrono::Utc::now().naive_utc().to_string();
+
+        self.session_repository.revoke_user_sessions(tx, user_id, &now).await
+    }
+
+    fn invalid_refresh_token() -> AppError {
+        AppError::new(
+            String::from("Invalid refresh token"),
+            AppErrorType::Unauthorized,
+            None
+        )
+    }
+}
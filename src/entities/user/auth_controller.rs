@@ -1,29 +1,83 @@
-use actix_web::{web, Responder};
+use actix_web::{web, HttpRequest, Responder};
 
-use crate::{auth::jwt::Claims, check_permission, service, util::json_response::json_response};
+use crate::{auth::jwt::{ImpersonationClaims, SessionClaims}, entities::{invitation::custom_dto::invitation_dto::AcceptInvitationDto, mfa::mfa_controller, oidc::oidc_controller}, service, util::{json_response::json_response, request::client_ip}};
 
-use super::user::{CreateUserDto, SignInUserDto};
+use super::{custom_dto::{auth_dto::RefreshTokenDto, password_dto::{ForgotPasswordDto, TokenResetPasswordDto}}, user::SignInUserDto, user_policy::SignUpClaims};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
             .route("/signup", web::post().to(sign_up))
             .route("/signin", web::post().to(sign_in))
+            .route("/refresh", web::post().to(refresh))
+            .route("/signout", web::post().to(sign_out))
+            .route("/impersonation", web::delete().to(end_impersonation))
+            .route("/forgot-password", web::post().to(forgot_password))
+            .route("/reset-password", web::post().to(reset_password))
+            .route("/accept-invitation", web::post().to(accept_invitation))
+            .configure(mfa_controller::config)
+            .configure(oidc_controller::config)
     );
 }
 
-pub async fn sign_up(user: web::Json<CreateUserDto>, claims: Claims) -> impl Responder {
-    check_permission!(service::get().permission().create_user(claims.sub, &user).await);
+pub async fn sign_up(claims: SignUpClaims) -> impl Responder {
+    let SignUpClaims(claims, user) = claims;
 
     let auth_service = service::get().auth();
-    let created_user = auth_service.sign_up(user.into_inner()).await;
+    let created_user = auth_service.sign_up(&claims, user).await;
     
     json_response(&created_user)
 }
 
-pub async fn sign_in(credentials: web::Json<SignInUserDto>) -> impl Responder {
+pub async fn sign_in(req: HttpRequest, credentials: web::Json<SignInUserDto>) -> impl Responder {
     let auth_service = service::get().auth();
-    let logged_user = auth_service.sign_in(credentials.into_inner()).await;
+    let logged_user = auth_service.sign_in(credentials.into_inner(), &client_ip(&req)).await;
 
     json_response(&logged_user)
 }
+
+pub async fn refresh(refresh: web::Json<RefreshTokenDto>) -> impl Responder {
+    let auth_service = service::get().auth();
+    let refreshed = auth_service.refresh(refresh.into_inner()).await;
+
+    json_response(&refreshed)
+}
+
+pub async fn sign_out(claims: SessionClaims) -> impl Responder {
+    let SessionClaims(claims) = claims;
+
+    let auth_service = service::get().auth();
+    let signed_out = auth_service.sign_out(claims.sid).await;
+
+    json_response(&signed_out)
+}
+
+pub async fn end_impersonation(claims: ImpersonationClaims) -> impl Responder {
+    let ImpersonationClaims(claims) = claims;
+
+    let auth_service = service::get().auth();
+    let ended = auth_service.end_impersonation(claims.sid).await;
+
+    json_response(&ended)
+}
+
+pub async fn forgot_password(forgot: web::Json<ForgotPasswordDto>) -> impl Responder {
+    let auth_service = service::get().auth();
+    let result = auth_service.forgot_password(forgot.into_inner()).await;
+
+    json_response(&result)
+}
+
+pub async fn reset_password(reset: web::Json<TokenResetPasswordDto>) -> impl Responder {
+    let auth_service = service::get().auth();
+    let result = auth_service.reset_password_with_token(reset.into_inner()).await;
+
+    json_response(&result)
+}
+
+pub async fn accept_invitation(acceptance: web::Json<AcceptInvitationDto>) -> impl Responder {
+    let auth_service = service::get().auth();
+    let result = auth_service.accept_invitation(acceptance.into_inner()).await;
+
+    json_response(&result)
+}
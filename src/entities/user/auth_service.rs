@@ -1,33 +1,33 @@
+use std::sync::{Arc, OnceLock};
 
 use actix_web::web;
 use bcrypt::DEFAULT_COST;
 
-use crate::{auth::jwt::generate_token, error::error::{AppError, AppErrorType}, service};
+use crate::{auth::jwt::{generate_impersonation_token, generate_token, Claims, TokenScope}, config, entities::{impersonation::{custom_dto::impersonation_dto::StartImpersonationDto, impersonation::Impersonation}, invitation::{custom_dto::invitation_dto::{AcceptInvitationDto, InviteUserDto}, invitation::{RetrieveInvitationDb, RetrieveInvitationDto}}, login_attempt::login_attempt::LoginFailureReason, mfa::custom_dto::mfa_dto::{MfaCodeDto, MfaConfirmationDto, MfaEnrollmentDto, MfaVerificationDto}, oidc::{custom_dto::oidc_dto::OidcCallbackDto, oidc::OidcIdentity}}, error::error::{AppError, AppErrorType}, service, util::{crypto::{generate_secret_token, hash_secret_token}, mail::{Mail, MailService}}};
 
-use super::{custom_dto::auth_dto::AuthDto, user::{CreateUserDto, RetrieveUserDto, SignInUserDto, User}};
+use super::{custom_dto::{auth_dto::{AuthDto, RefreshTokenDto}, password_dto::{ChangePasswordDto, ForgotPasswordDto, ResetPasswordDto, TokenResetPasswordDto}}, user::{CreateUserDto, RetrieveAuthUserDto, RetrieveUserDto, SignInUserDto, User, UserStatus}};
 
-pub struct AuthService {
+/// Hash verified when the requested user does not exist, so the response time does not reveal it
+static DUMMY_PASSWORD_HASH: OnceLock<String> = OnceLock::new();
 
+pub struct AuthService {
+    mail_service: Arc<MailService>
 }
 
 impl AuthService {
-    pub fn new() -> AuthService {
+    pub fn new(mail_service: Arc<MailService>) -> AuthService {
         AuthService {
-
+            mail_service
         }
     }
 
-    pub async fn sign_up(&self, user: CreateUserDto) -> Result<RetrieveUserDto, AppError> {
+    /// Creates an active user on behalf of the actor, who can only give them a role included in their permissions
+    pub async fn sign_up(&self, actor: &Claims, user: CreateUserDto) -> Result<RetrieveUserDto, AppError> {
         if let Err(app_error) = User::check_raw_password(&user.password) {
             return Err(app_error);
         }
 
-        let hashed_pass = web::block(move || {
-            bcrypt::hash(&user.password, DEFAULT_COST)
-        })
-            .await
-            .map_err(AppError::internal_from_generic)?
-            .map_err(AppError::internal_from_generic)?;
+        let hashed_pass = Self::hash_password(user.password).await?;
 
         let hashed_user = CreateUserDto {
             username: user.username,
@@ -35,33 +35,205 @@ impl AuthService {
             name: user.name,
             password: hashed_pass,
             company_id: user.company_id,
-            role: user.role
+            role_id: user.role_id
         };
 
-        let created_user = service::get().user().create_user(hashed_user).await?;
+        let created_user = service::get().user().create_user(Some(actor), hashed_user, UserStatus::Active).await?;
 
         Ok(created_user)
     }
 
-    pub async fn sign_in(&self, user: SignInUserDto) -> Result<AuthDto, AppError> {
+    /// Creates a pending user and emails them an invitation to choose their password. Pending users cannot sign in
+    /// until they accept it. The role of the invitation must be included in the permissions of the actor.
+    pub async fn invite_user(&self, actor: &Claims, company_id: i64, invitation: InviteUserDto) -> Result<RetrieveInvitationDto, AppError> {
+        let email = invitation.email.trim().to_lowercase();
+        User::check_email(&Some(email.clone()))?;
+
+        // Nobody knows this password, it is replaced when the invitation is accepted
+        let password = Self::hash_password(generate_secret_token()).await?;
+
+        let (user, invitation, token) = service::get().user().invite(actor, CreateUserDto {
+            username: invitation.username,
+            email: Some(email.clone()),
+            name: invitation.name,
+            password,
+            company_id,
+            role_id: invitation.role_id
+        }, &email).await?;
+
+        self.send_invitation(&user.name, &invitation, &token);
+
+        Ok(invitation.to_retrieve_invitation_dto())
+    }
+
+    /// Sends the invitation again with a new token. The previous link stops working.
+    pub async fn resend_invitation(&self, company_id: i64, invitation_id: i64) -> Result<RetrieveInvitationDto, AppError> {
+        let (invitation, token) = service::get().invitation().renew_invitation(company_id, invitation_id).await?;
+
+        let user = self.get_existing_auth_user(invitation.user_id).await?;
+
+        self.send_invitation(&user.name, &invitation, &token);
+
+        Ok(invitation.to_retrieve_invitation_dto())
+    }
+
+    /// Sets the password chosen by the invited user and activates the account
+    pub async fn accept_invitation(&self, acceptance: AcceptInvitationDto) -> Result<(), AppError> {
+        User::check_raw_password(&acceptance.password)?;
+
+        let hashed_pass = Self::hash_password(acceptance.password).await?;
+
+        service::get().user().accept_invitation(&acceptance.token, &hashed_pass).await
+    }
+
+    /// Checks the credentials of the user. Failed attempts are throttled per username and per client ip, see
+    /// `LoginAttemptService`.
+    pub async fn sign_in(&self, user: SignInUserDto, client_ip: &str) -> Result<AuthDto, AppError> {
+        let login_attempt_service = service::get().login_attempt();
+        login_attempt_service.reserve_attempt(&user.username, client_ip).await?;
+
         let user_service = service::get().user();
         let existing_user = match user_service.get_auth_user_by_username(&user.username).await? {
+            Some(user) => user,
+            None => {
+                Self::verify_dummy_password(user.password).await?;
+                login_attempt_service.record_failure(&user.username, client_ip, LoginFailureReason::UnknownUser).await?;
+
+                return Err(Self::invalid_credentials());
+            }
+        };
+
+        let password_is_correct = Self::verify_password(user.password, existing_user.password.clone()).await?;
+
+        if !password_is_correct {
+            login_attempt_service.record_failure(&user.username, client_ip, LoginFailureReason::InvalidPassword).await?;
+
+            return Err(Self::invalid_credentials());
+        }
+
+        if !existing_user.is_active() {
+            login_attempt_service.record_failure(&user.username, client_ip, LoginFailureReason::InactiveAccount).await?;
+
+            return Err(Self::invalid_credentials());
+        }
+
+        login_attempt_service.record_success(&user.username, client_ip).await?;
+
+        self.issue_first_factor_auth(existing_user).await
+    }
+
+    /// Completes a single sign-on. The second factor of the identity provider is not trusted, so the same MFA step as
+    /// `sign_in` follows, and users that must change their password still have to do it.
+    pub async fn sign_in_with_oidc(&self, callback: OidcCallbackDto) -> Result<AuthDto, AppError> {
+        let identity = service::get().oidc().authenticate(callback).await?;
+
+        let user = self.find_or_provision_oidc_user(&identity).await?;
+
+        self.issue_first_factor_auth(user).await
+    }
+
+    pub async fn refresh(&self, refresh: RefreshTokenDto) -> Result<AuthDto, AppError> {
+        let session = service::get().session().refresh_session(&refresh.refresh_token).await?;
+
+        let user = match service::get().user().get_user_by_id(session.user_id).await? {
             Some(user) => user,
             None => {
                 return Err(AppError::new(
-                    String::from(r#"User with email "$1" does not exist"#),
-                    AppErrorType::NotFound,
-                    Some(vec![user.username])
+                    String::from("Invalid refresh token"),
+                    AppErrorType::Unauthorized,
+                    None
                 ));
             }
         };
 
-        let password_is_correct = web::block(move || {
-            bcrypt::verify(&user.password, &existing_user.password)
+        Ok(AuthDto {
+            token: generate_token(user.id, session.id, TokenScope::Full),
+            refresh_token: Some(session.refresh_token),
+            scope: TokenScope::Full,
+            user
         })
-            .await
-            .map_err(AppError::internal_from_generic)?
-            .map_err(AppError::internal_from_generic)?;
+    }
+
+    pub async fn sign_out(&self, session_id: i64) -> Result<(), AppError> {
+        service::get().session().revoke_session(session_id).await
+    }
+
+    /// Blocks the sign in of the user and closes their sessions. Payrolls and the rest of the history are kept.
+    pub async fn deactivate_user(&self, actor_user_id: i64, user_id: i64) -> Result<(), AppError> {
+        if actor_user_id == user_id {
+            return Err(AppError::new(
+                String::from("You cannot deactivate yourself"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let user = self.get_existing_auth_user(user_id).await?;
+
+        if user.status == UserStatus::Inactive.as_str() {
+            return Ok(());
+        }
+
+        service::get().user().deactivate(user_id).await
+    }
+
+    pub async fn reactivate_user(&self, user_id: i64) -> Result<(), AppError> {
+        let user = self.get_existing_auth_user(user_id).await?;
+
+        if user.status != UserStatus::Inactive.as_str() {
+            return Err(AppError::new(
+                String::from(r#"User with id "$1" is not deactivated"#),
+                AppErrorType::Conflict,
+                Some(vec![user_id.to_string()])
+            ));
+        }
+
+        service::get().user().update_status(user_id, UserStatus::Active).await
+    }
+
+    /// Opens a session as another user on behalf of the actor. The token expires after the impersonation lifetime
+    /// and every request made with it is recorded with the id of the actor.
+    pub async fn impersonate(&self, actor: &Claims, user_id: i64, impersonation: StartImpersonationDto) -> Result<AuthDto, AppError> {
+        Impersonation::check_reason(&impersonation.reason)?;
+
+        if actor.sub == user_id {
+            return Err(AppError::new(
+                String::from("You cannot impersonate yourself"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let user = self.get_existing_auth_user(user_id).await?;
+
+        let expires_at = chrono::Utc::now()
+            .checked_add_signed(chrono::Duration::seconds(config::get().auth.impersonation_ttl))
+            .expect("valid timestamp");
+
+        let session = service::get().session().create_session(user.id).await?;
+        service::get().impersonation().start(actor.sub, user.id, session.id, &impersonation.reason, &expires_at).await?;
+
+        Ok(AuthDto {
+            token: generate_impersonation_token(actor.sub, user.id, session.id, &expires_at),
+            refresh_token: None,
+            scope: TokenScope::Impersonation,
+            user: user.to_retrieve_user_dto()
+        })
+    }
+
+    pub async fn end_impersonation(&self, session_id: i64) -> Result<(), AppError> {
+        service::get().impersonation().end(session_id).await?;
+        service::get().session().revoke_session(session_id).await
+    }
+
+    /// Changes the password of the user after checking the current one.
+    ///
+    /// Every session of the user is revoked (including the one making the request) and a new full session is
+    /// returned, so this also lifts the restriction of tokens issued while `must_change_password` was set.
+    pub async fn change_password(&self, user_id: i64, passwords: ChangePasswordDto) -> Result<AuthDto, AppError> {
+        let existing_user = self.get_existing_auth_user(user_id).await?;
+
+        let password_is_correct = Self::verify_password(passwords.old_password.clone(), existing_user.password.clone()).await?;
 
         if !password_is_correct {
             return Err(AppError::new(
@@ -71,17 +243,366 @@ impl AuthService {
             ));
         }
 
-        let user = RetrieveUserDto {
-            id: existing_user.id,
-            username: existing_user.username,
-            email: existing_user.email,
-            name: existing_user.name,
-            company_id: existing_user.company_id
+        User::check_raw_password(&passwords.new_password)?;
+
+        if passwords.new_password == passwords.old_password {
+            return Err(AppError::new(
+                String::from("The new password must be different from the current one"),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        let hashed_pass = Self::hash_password(passwords.new_password).await?;
+
+        service::get().user().update_password(user_id, &hashed_pass, false).await?;
+        service::get().session().revoke_user_sessions(user_id).await?;
+
+        self.issue_auth(existing_user.to_retrieve_user_dto(), TokenScope::Full).await
+    }
+
+    /// Sets a temporary password chosen by an admin. The user will only be able to change it on the next sign in,
+    /// and any open session is revoked.
+    pub async fn reset_password(&self, user_id: i64, reset: ResetPasswordDto) -> Result<(), AppError> {
+        User::check_raw_password(&reset.temporary_password)?;
+
+        let hashed_pass = Self::hash_password(reset.temporary_password).await?;
+
+        service::get().user().update_password(user_id, &hashed_pass, true).await?;
+        service::get().session().revoke_user_sessions(user_id).await?;
+
+        Ok(())
+    }
+
+    /// Sends a password reset link to the email of the user, if the user exists and has one.
+    ///
+    /// The result is the same whether the user exists or not, and the email is sent in the background so the
+    /// response time does not reveal it either.
+    pub async fn forgot_password(&self, forgot: ForgotPasswordDto) -> Result<(), AppError> {
+        let existing_user = match service::get().user().get_auth_user_by_username(&forgot.username).await? {
+            Some(user) if user.is_active() => user,
+            _ => return Ok(())
+        };
+
+        let email = match existing_user.email {
+            Some(email) => email,
+            None => return Ok(())
+        };
+
+        let token = service::get().password_reset().create_token(existing_user.id).await?;
+
+        let mail = Mail {
+            to: email,
+            subject: String::from("Reset your password"),
+            body: format!(
+                "Hello {},\n\nWe received a request to reset your password. Use the following link to choose a new one:\n\n{}\n\nIf you did not request it, you can ignore this email.",
+                existing_user.name,
+                config::get().mail.password_reset_url.replace("{token}", &token)
+            )
         };
 
+        self.send_in_background(mail);
+
+        Ok(())
+    }
+
+    /// Sets a new password using a token sent by `forgot_password`. Every session of the user is revoked.
+    pub async fn reset_password_with_token(&self, reset: TokenResetPasswordDto) -> Result<(), AppError> {
+        User::check_raw_password(&reset.new_password)?;
+
+        let user_id = service::get().password_reset().consume_token(&reset.token).await?;
+
+        let hashed_pass = Self::hash_password(reset.new_password).await?;
+
+        service::get().user().update_password(user_id, &hashed_pass, false).await?;
+        service::get().session().revoke_user_sessions(user_id).await?;
+
+        Ok(())
+    }
+
+    pub async fn enroll_mfa(&self, user_id: i64) -> Result<MfaEnrollmentDto, AppError> {
+        let user = self.get_existing_auth_user(user_id).await?;
+
+        service::get().mfa().enroll(user_id, &user.username).await
+    }
+
+    /// Enables the second factor. If the enrollment was forced during sign in, the sign in is completed too.
+    pub async fn confirm_mfa(&self, claims: &Claims, code: MfaCodeDto) -> Result<MfaConfirmationDto, AppError> {
+        let recovery_codes = service::get().mfa().confirm(claims.sub, &code.code).await?;
+
+        let auth = if claims.scope == TokenScope::MfaEnrollment {
+            Some(self.complete_sign_in(claims).await?)
+        } else {
+            None
+        };
+
+        Ok(MfaConfirmationDto {
+            recovery_codes,
+            auth
+        })
+    }
+
+    /// Completes a sign in with the second factor. Wrong codes count as failed sign ins of the user.
+    pub async fn verify_mfa(&self, claims: &Claims, verification: MfaVerificationDto, client_ip: &str) -> Result<AuthDto, AppError> {
+        let user = self.get_existing_auth_user(claims.sub).await?;
+
+        let login_attempt_service = service::get().login_attempt();
+        login_attempt_service.reserve_attempt(&user.username, client_ip).await?;
+
+        if let Err(error) = service::get().mfa().verify(claims.sub, &verification).await {
+            if error.error_type() == AppErrorType::Unauthorized {
+                login_attempt_service.record_failure(&user.username, client_ip, LoginFailureReason::InvalidMfaCode).await?;
+            } else {
+                login_attempt_service.release_attempt(&user.username, client_ip).await?;
+            }
+
+            return Err(error);
+        }
+
+        login_attempt_service.release_attempt(&user.username, client_ip).await?;
+
+        self.complete_sign_in(claims).await
+    }
+
+    pub async fn disable_mfa(&self, user_id: i64, verification: MfaVerificationDto) -> Result<(), AppError> {
+        if self.is_mfa_required(user_id).await? {
+            return Err(AppError::new(
+                String::from("Two-factor authentication is required for your role"),
+                AppErrorType::Forbidden,
+                None
+            ));
+        }
+
+        service::get().mfa().disable(user_id, &verification).await
+    }
+
+    pub async fn unlock_user(&self, user_id: i64) -> Result<(), AppError> {
+        let user = self.get_existing_auth_user(user_id).await?;
+
+        service::get().login_attempt().unlock_username(&user.username).await
+    }
+
+    /// Users are matched by the subject they were linked to, then by their verified email, which links them.
+    /// Unknown users are created when provisioning is enabled. Invited users must accept their invitation first.
+    async fn find_or_provision_oidc_user(&self, identity: &OidcIdentity) -> Result<RetrieveAuthUserDto, AppError> {
+        let user_service = service::get().user();
+
+        if let Some(user) = user_service.get_auth_user_by_oidc_subject(&identity.subject).await? {
+            return Self::active_oidc_user(user);
+        }
+
+        if let Some(email) = &identity.email {
+            if let Some(user) = user_service.get_unlinked_auth_user_by_email(email).await? {
+                let user = Self::active_oidc_user(user)?;
+                user_service.link_oidc_subject(user.id, &identity.subject).await?;
+
+                return Ok(user);
+            }
+        }
+
+        let provisioning = match config::get().oidc.as_ref().and_then(|oidc| oidc.provisioning.as_ref()) {
+            Some(provisioning) => provisioning,
+            None => return Err(AppError::new(
+                String::from("There is no user linked to this identity"),
+                AppErrorType::Unauthorized,
+                None
+            ))
+        };
+
+        let username_candidate = identity.preferred_username.as_deref()
+            .or(identity.email.as_deref().and_then(|email| email.split('@').next()))
+            .unwrap_or(&identity.subject);
+
+        let mut username = User::username_from(username_candidate);
+        if user_service.username_exists(&username).await? {
+            username = format!("{}_{}", username, &hash_secret_token(&identity.subject)[..8]);
+        }
+
+        let email = identity.email.as_ref()
+            .map(|email| email.to_lowercase())
+            .filter(|email| User::check_email(&Some(email.clone())).is_ok());
+
+        let name: String = identity.name.as_deref()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&username)
+            .chars()
+            .take(50)
+            .collect();
+
+        let role = match service::get().role().get_role_by_name(&provisioning.role).await? {
+            Some(role) => role,
+            None => return Err(AppError::new(
+                String::from(r#"The role "$1" given to provisioned users does not exist"#),
+                AppErrorType::InternalServerError,
+                Some(vec![provisioning.role.clone()])
+            ))
+        };
+
+        // Provisioned users sign in through the identity provider, so they get a password nobody knows
+        let password = Self::hash_password(generate_secret_token()).await?;
+
+        let created_user = user_service.create_user(None, CreateUserDto {
+            username,
+            email,
+            name,
+            password,
+            company_id: provisioning.company_id,
+            role_id: role.id
+        }, UserStatus::Active).await?;
+
+        user_service.link_oidc_subject(created_user.id, &identity.subject).await?;
+
+        self.get_existing_auth_user(created_user.id).await
+    }
+
+    fn active_oidc_user(user: RetrieveAuthUserDto) -> Result<RetrieveAuthUserDto, AppError> {
+        if !user.is_active() {
+            return Err(AppError::new(
+                String::from("The user linked to this identity is not active"),
+                AppErrorType::Unauthorized,
+                None
+            ));
+        }
+
+        Ok(user)
+    }
+
+    fn send_invitation(&self, name: &str, invitation: &RetrieveInvitationDb, token: &str) {
+        let mail = Mail {
+            to: invitation.email.clone(),
+            subject: String::from("You have been invited to Payroll Manager"),
+            body: format!(
+                "Hello {},\n\nAn account has been created for you. Use the following link to choose your password:\n\n{}\n\nThe link expires on {} (UTC).",
+                name,
+                config::get().mail.invitation_url.replace("{token}", token),
+                invitation.expires_at
+            )
+        };
+
+        self.send_in_background(mail);
+    }
+
+    /// Emails are sent in the background so the response time does not depend on the mail server
+    fn send_in_background(&self, mail: Mail) {
+        let mail_service = Arc::clone(&self.mail_service);
+        actix_web::rt::spawn(async move {
+            // Failures are already logged when the error is created
+            let _ = mail_service.send(mail).await;
+        });
+    }
+
+    /// Asks for the second factor once the user is identified, or for its enrollment when their role requires one
+    async fn issue_first_factor_auth(&self, user: RetrieveAuthUserDto) -> Result<AuthDto, AppError> {
+        if service::get().mfa().is_mfa_enabled(user.id).await? {
+            return self.issue_auth(user.to_retrieve_user_dto(), TokenScope::MfaPending).await;
+        }
+
+        if self.is_mfa_required(user.id).await? {
+            return self.issue_auth(user.to_retrieve_user_dto(), TokenScope::MfaEnrollment).await;
+        }
+
+        let scope = Self::scope_after_authentication(&user);
+
+        self.issue_auth(user.to_retrieve_user_dto(), scope).await
+    }
+
+    /// Replaces the restricted session used for the second factor step with the one the user is entitled to.
+    async fn complete_sign_in(&self, claims: &Claims) -> Result<AuthDto, AppError> {
+        service::get().session().revoke_session(claims.sid).await?;
+
+        let user = self.get_existing_auth_user(claims.sub).await?;
+        let scope = Self::scope_after_authentication(&user);
+
+        self.issue_auth(user.to_retrieve_user_dto(), scope).await
+    }
+
+    /// A second factor is required when the user holds any permission of the roles that require it, so that a custom
+    /// role with the same powers cannot skip it
+    async fn is_mfa_required(&self, user_id: i64) -> Result<bool, AppError> {
+        let role_service = service::get().role();
+        let permission = role_service.get_user_role(user_id).await?.permission(user_id);
+
+        for name in &config::get().auth.mfa_required_roles {
+            if let Some(required) = role_service.get_role_by_name(name).await? {
+                if permission.overlaps(&required.permission(user_id)) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn get_existing_auth_user(&self, user_id: i64) -> Result<RetrieveAuthUserDto, AppError> {
+        match service::get().user().get_auth_user_by_id(user_id).await? {
+            Some(user) => Ok(user),
+            None => Err(AppError::new(
+                String::from(r#"User with id "$1" does not exist"#),
+                AppErrorType::NotFound,
+                Some(vec![user_id.to_string()])
+            ))
+        }
+    }
+
+    /// Scope of the token given once the user is fully authenticated
+    fn scope_after_authentication(user: &RetrieveAuthUserDto) -> TokenScope {
+        if user.must_change_password {
+            TokenScope::PasswordChange
+        } else {
+            TokenScope::Full
+        }
+    }
+
+    /// Opens a new session for the user. Restricted tokens do not get a refresh token, so they cannot outlive the
+    /// access token lifetime.
+    async fn issue_auth(&self, user: RetrieveUserDto, scope: TokenScope) -> Result<AuthDto, AppError> {
+        let session = service::get().session().create_session(user.id).await?;
+
         Ok(AuthDto {
-            token: generate_token(user.id),
+            token: generate_token(user.id, session.id, scope),
+            refresh_token: if scope == TokenScope::Full { Some(session.refresh_token) } else { None },
+            scope,
             user
         })
     }
-}
\ No newline at end of file
+
+    async fn hash_password(password: String) -> Result<String, AppError> {
+        web::block(move || {
+            bcrypt::hash(&password, DEFAULT_COST)
+        })
+            .await
+            .map_err(AppError::internal_from_generic)?
+            .map_err(AppError::internal_from_generic)
+    }
+
+    async fn verify_password(password: String, hash: String) -> Result<bool, AppError> {
+        web::block(move || {
+            bcrypt::verify(&password, &hash)
+        })
+            .await
+            .map_err(AppError::internal_from_generic)?
+            .map_err(AppError::internal_from_generic)
+    }
+
+    async fn verify_dummy_password(password: String) -> Result<(), AppError> {
+        web::block(move || {
+            let hash = DUMMY_PASSWORD_HASH.get_or_init(|| {
+                bcrypt::hash("dummy-password", DEFAULT_COST).expect("Failed to hash dummy password")
+            });
+
+            bcrypt::verify(&password, hash)
+        })
+            .await
+            .map_err(AppError::internal_from_generic)?
+            .map_err(AppError::internal_from_generic)
+            .map(|_| ())
+    }
+
+    fn invalid_credentials() -> AppError {
+        AppError::new(
+            String::from("Invalid username or password"),
+            AppErrorType::Unauthorized,
+            None
+        )
+    }
+}
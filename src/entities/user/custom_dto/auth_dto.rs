@@ -1,9 +1,17 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::user::user::RetrieveUserDto;
+use crate::{auth::jwt::TokenScope, user::user::RetrieveUserDto};
 
 #[derive(Serialize)]
 pub struct AuthDto {
     pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub scope: TokenScope,
     pub user: RetrieveUserDto
 }
+
+#[derive(Deserialize)]
+pub struct RefreshTokenDto {
+    pub refresh_token: String
+}
@@ -1 +1,3 @@
-pub mod auth_dto;
\ No newline at end of file
+pub mod auth_dto;
+pub mod password_dto;
+pub mod user_dto;
\ No newline at end of file
@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ChangePasswordDto {
+    pub old_password: String,
+    pub new_password: String
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordDto {
+    pub temporary_password: String
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordDto {
+    pub username: String
+}
+
+#[derive(Deserialize)]
+pub struct TokenResetPasswordDto {
+    pub token: String,
+    pub new_password: String
+}
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+/// Only the given fields are updated
+#[derive(Deserialize)]
+pub struct UpdateUserDto {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub name: Option<String>
+}
+
+#[derive(Deserialize)]
+pub struct ChangeRoleDto {
+    pub role_id: i64
+}
@@ -0,0 +1 @@
+pub mod user_filter;
\ No newline at end of file
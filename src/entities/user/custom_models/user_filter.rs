@@ -0,0 +1,84 @@
+use serde::Deserialize;
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::{entities::{department::department::Department, user::user::UserStatus}, error::error::AppError, util::{db::contains_pattern, pagination::Pagination}};
+
+pub struct UserFilterDb {
+    pub company_id: Option<i64>,
+    pub department_id: Option<i64>,
+    pub search: Option<String>,
+    pub role_id: Option<i64>,
+    pub status: Option<UserStatus>,
+    pub pagination: Pagination
+}
+
+impl UserFilterDb {
+    pub const MAX_LIMIT: i64 = 100;
+
+    pub fn from_user_filter_dto(filter: UserFilterDto) -> Result<UserFilterDb, AppError> {
+        Ok(UserFilterDb {
+            company_id: filter.company_id,
+            department_id: filter.department_id,
+            search: filter.search.map(|search| search.trim().to_lowercase()).filter(|search| !search.is_empty()),
+            role_id: filter.role_id,
+            status: filter.status,
+            pagination: Pagination::new(filter.limit, filter.offset, Self::MAX_LIMIT)?
+        })
+    }
+
+    /// Conditions of the filter, shared by the listing and its count
+    pub fn fill_conditions(&self, query: &mut QueryBuilder<Sqlite>) {
+        query.push(" WHERE 1 = 1");
+
+        if let Some(company_id) = self.company_id {
+            query.push(" AND company_id = ");
+            query.push_bind(company_id);
+        }
+
+        if let Some(department_id) = self.department_id {
+            query.push(" AND department_id IN ");
+            Department::push_descendant_ids(query, department_id);
+        }
+
+        if let Some(search) = &self.search {
+            let pattern = contains_pattern(search);
+
+            query.push(" AND (lower(name) LIKE ");
+            query.push_bind(pattern.clone());
+            query.push(" ESCAPE '\\' OR lower(username) LIKE ");
+            query.push_bind(pattern);
+            query.push(" ESCAPE '\\')");
+        }
+
+        if let Some(role_id) = self.role_id {
+            query.push(" AND role_id = ");
+            query.push_bind(role_id);
+        }
+
+        if let Some(status) = self.status {
+            query.push(" AND status = ");
+            query.push_bind(status.as_str());
+        }
+    }
+
+    pub fn fill_query(&self, query: &mut QueryBuilder<Sqlite>) {
+        self.fill_conditions(query);
+
+        query.push(" ORDER BY id");
+
+        self.pagination.fill_query(query);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UserFilterDto {
+    pub company_id: Option<i64>,
+    /// Users of the department and of its subdepartments
+    pub department_id: Option<i64>,
+    /// Part of the name or the username, case insensitive
+    pub search: Option<String>,
+    pub role_id: Option<i64>,
+    pub status: Option<UserStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>
+}
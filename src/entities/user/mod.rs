@@ -3,5 +3,7 @@ pub mod user_service;
 pub mod auth_service;
 pub mod user_repository;
 pub mod user_controller;
+pub mod user_policy;
 pub mod auth_controller;
-pub mod custom_dto;
\ No newline at end of file
+pub mod custom_dto;
+pub mod custom_models;
\ No newline at end of file
@@ -1,34 +1,51 @@
 use macros::DeriveCustomModel;
 use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
 
-use crate::{entities::permission::permission::Role, error::error::{AppError, AppErrorType}};
+use crate::{error::error::{AppError, AppErrorType}, util::tax_id::{is_valid_spanish_national_id, normalize_tax_id}};
 
 #[derive(DeriveCustomModel)]
 #[custom_model(model(
     name = "RetrieveUserDb",
-    fields(id, username, email, name, company_id)
+    fields(id, username, email, name, company_id, role_id, department_id, status),
+    extra_derives(FromRow)
 ))]
 #[custom_model(model(
     name = "RetrieveAuthUserDb",
-    fields(id, username, email, name, password, company_id)
+    fields(id, username, email, name, password, company_id, role_id, department_id, must_change_password, status)
 ))]
 #[custom_model(model(
     name = "CreateUserDb",
-    fields(email, username, name, password, company_id)
+    fields(email, username, name, password, company_id, role_id, status)
 ))]
 #[custom_model(model(
     name = "RetrieveUserDto",
-    fields(id, username, email, name, company_id),
+    fields(id, username, email, name, company_id, role_id, department_id, status),
     extra_derives(Serialize)
 ))]
 #[custom_model(model(
     name = "RetrieveAuthUserDto",
-    fields(id, username, email, name, password, company_id),
+    fields(id, username, email, name, password, company_id, role_id, department_id, must_change_password, status),
     extra_derives(Serialize)
 ))]
 #[custom_model(model(
     name = "CreateUserDto",
-    fields(username, email, name, password, company_id, role),
+    fields(username, email, name, password, company_id, role_id),
+    extra_derives(Deserialize)
+))]
+#[custom_model(model(
+    name = "UserIdentifiersDb",
+    fields(id, national_id, employee_number),
+    extra_derives(FromRow)
+))]
+#[custom_model(model(
+    name = "UserIdentifiersDto",
+    fields(id, national_id, employee_number),
+    extra_derives(Serialize)
+))]
+#[custom_model(model(
+    name = "UpdateUserIdentifiersDto",
+    fields(national_id, employee_number),
     extra_derives(Deserialize)
 ))]
 #[custom_model(model(
@@ -45,11 +62,18 @@ pub struct User {
     name: String,
     password: String,
     company_id: i64,
-    role: Role
+    role_id: i64,
+    department_id: Option<i64>,
+    must_change_password: bool,
+    status: String,
+    /// DNI or NIE of the employee, unique in the company
+    national_id: Option<String>,
+    /// Number of the employee in the company, as used by the payroll provider
+    employee_number: Option<String>
 }
 
 impl User {
-    pub fn new(id: i64, username: String, email: Option<String>, name: String, password: String, company_id: i64, role: Role) -> User {
+    pub fn new(id: i64, username: String, email: Option<String>, name: String, password: String, company_id: i64, role_id: i64, department_id: Option<i64>, must_change_password: bool, status: String, national_id: Option<String>, employee_number: Option<String>) -> User {
         User {
             id,
             username,
@@ -57,7 +81,12 @@ impl User {
             name,
             password,
             company_id,
-            role
+            role_id,
+            department_id,
+            must_change_password,
+            status,
+            national_id,
+            employee_number
         }
     }
 
@@ -109,6 +138,62 @@ impl User {
         Ok(())
     }
 
+    pub fn check_national_id(national_id: &Option<String>) -> Result<(), AppError> {
+        if let Some(national_id) = national_id {
+            if !is_valid_spanish_national_id(national_id) {
+                return Err(AppError::new(
+                    String::from("Invalid national id: $1. It must be a valid DNI or NIE"),
+                    AppErrorType::BadRequest,
+                    Some(vec![national_id.to_string()])
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn check_employee_number(employee_number: &Option<String>) -> Result<(), AppError> {
+        if let Some(employee_number) = employee_number {
+            if employee_number.is_empty() || employee_number.len() > 20 || !employee_number.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(AppError::new(
+                    String::from("Invalid employee number: $1. It must have between 1 and 20 letters or digits"),
+                    AppErrorType::BadRequest,
+                    Some(vec![employee_number.to_string()])
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds a valid username from a name picked somewhere else, like an identity provider
+    pub fn username_from(candidate: &str) -> String {
+        let mut username = String::new();
+        let mut pending_separator = false;
+
+        for c in candidate.chars() {
+            if c.is_ascii_alphanumeric() {
+                if pending_separator && !username.is_empty() {
+                    username.push('.');
+                }
+                pending_separator = false;
+                username.push(c.to_ascii_lowercase());
+            } else {
+                pending_separator = true;
+            }
+
+            if username.len() >= 40 {
+                break;
+            }
+        }
+
+        if username.is_empty() {
+            String::from("user")
+        } else {
+            username
+        }
+    }
+
     pub fn check_raw_password(password: &str) -> Result<(), AppError> {
         if password.len() < 8 {
             return Err(AppError::new(
@@ -122,6 +207,27 @@ impl User {
     }
 }
 
+/// Whether the user can sign in
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserStatus {
+    /// Invited, waiting for the user to choose a password
+    Pending,
+    Active,
+    /// Deactivated by an admin. The user cannot sign in, but their payrolls are kept
+    Inactive
+}
+
+impl UserStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserStatus::Pending => "pending",
+            UserStatus::Active => "active",
+            UserStatus::Inactive => "inactive"
+        }
+    }
+}
+
 impl RetrieveUserDb {
     pub fn to_retrieve_user_dto(self) -> Result<RetrieveUserDto, AppError> {
         // User::check_name(&self.name)?;
@@ -131,7 +237,10 @@ impl RetrieveUserDb {
             username: self.username,
             email: self.email,
             name: self.name,
-            company_id: self.company_id
+            company_id: self.company_id,
+            role_id: self.role_id,
+            department_id: self.department_id,
+            status: self.status
         })
     }
 }
@@ -146,11 +255,34 @@ impl RetrieveAuthUserDb {
             email: self.email,
             name: self.name,
             password: self.password,
-            company_id: self.company_id
+            company_id: self.company_id,
+            role_id: self.role_id,
+            department_id: self.department_id,
+            must_change_password: self.must_change_password,
+            status: self.status
         })
     }
 }
 
+impl RetrieveAuthUserDto {
+    pub fn is_active(&self) -> bool {
+        self.status == UserStatus::Active.as_str()
+    }
+
+    pub fn to_retrieve_user_dto(self) -> RetrieveUserDto {
+        RetrieveUserDto {
+            id: self.id,
+            username: self.username,
+            email: self.email,
+            name: self.name,
+            company_id: self.company_id,
+            role_id: self.role_id,
+            department_id: self.department_id,
+            status: self.status
+        }
+    }
+}
+
 impl CreateUserDb {
     // pub fn to_create_user_dto(self) -> Result<CreateUserDto, AppError> {
     //     // User::check_email(&self.email)?;
@@ -165,7 +297,7 @@ impl CreateUserDb {
     //     })
     // }
 
-    pub fn from_create_user_dto(user: CreateUserDto) -> Result<CreateUserDb, AppError> {
+    pub fn from_create_user_dto(user: CreateUserDto, status: UserStatus) -> Result<CreateUserDb, AppError> {
         User::check_username(&user.username)?;
         User::check_email(&user.email)?;
         User::check_name(&user.name)?;
@@ -175,7 +307,35 @@ impl CreateUserDb {
             email: user.email,
             name: user.name,
             password: user.password,
-            company_id: user.company_id
+            company_id: user.company_id,
+            role_id: user.role_id,
+            status: status.as_str().to_string()
         })
     }
 }
+
+impl UpdateUserIdentifiersDto {
+    /// Identifiers are stored in uppercase and without separators, the way they are searched in documents
+    pub fn normalized(self) -> Result<UpdateUserIdentifiersDto, AppError> {
+        let national_id = self.national_id.map(|national_id| normalize_tax_id(&national_id));
+        let employee_number = self.employee_number.map(|employee_number| employee_number.trim().to_uppercase());
+
+        User::check_national_id(&national_id)?;
+        User::check_employee_number(&employee_number)?;
+
+        Ok(UpdateUserIdentifiersDto {
+            national_id,
+            employee_number
+        })
+    }
+}
+
+impl UserIdentifiersDb {
+    pub fn to_user_identifiers_dto(self) -> UserIdentifiersDto {
+        UserIdentifiersDto {
+            id: self.id,
+            national_id: self.national_id,
+            employee_number: self.employee_number
+        }
+    }
+}
@@ -1,19 +1,37 @@
 use actix_web::{web, Responder};
 
-use crate::{auth::jwt::Claims, check_permission, service, util::json_response::json_response};
+use crate::{auth::jwt::PasswordChangeClaims, entities::{department::custom_dto::department_dto::ChangeDepartmentDto, impersonation::custom_dto::impersonation_dto::StartImpersonationDto}, service, util::json_response::json_response};
 
+use super::{custom_dto::{password_dto::{ChangePasswordDto, ResetPasswordDto}, user_dto::{ChangeRoleDto, UpdateUserDto}}, custom_models::user_filter::UserFilterDto, user::UpdateUserIdentifiersDto, user_policy::{AdministerUserClaims, ChangeUserRoleClaims, DeleteUserClaims, ImpersonateUserClaims, ListUsersClaims, ReadUserClaims, ResetUserPasswordClaims, UpdateUserClaims}};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/users")
+            .route("", web::get().to(get_users))
+            .route("/me/password", web::put().to(change_password))
             .route("/{requested_user_id}", web::get().to(get_profile))
+            .route("/{requested_user_id}", web::patch().to(update_user))
+            .route("/{requested_user_id}/role", web::put().to(change_role))
+            .route("/{requested_user_id}/department", web::put().to(change_department))
+            .route("/{requested_user_id}/identifiers", web::get().to(get_identifiers))
+            .route("/{requested_user_id}/identifiers", web::put().to(update_identifiers))
+            .route("/{requested_user_id}/deactivate", web::post().to(deactivate))
+            .route("/{requested_user_id}/reactivate", web::post().to(reactivate))
+            .route("/{requested_user_id}/password", web::put().to(reset_password))
+            .route("/{requested_user_id}/sessions", web::delete().to(revoke_sessions))
+            .route("/{requested_user_id}/unlock", web::post().to(unlock))
+            .route("/{requested_user_id}/impersonate", web::post().to(impersonate))
     );
 }
 
-pub async fn get_profile(requested_user_id: web::Path<i64>, claims: Claims) -> impl Responder {
-    let requested_user_id = requested_user_id.into_inner();
+pub async fn get_users(filters: web::Query<UserFilterDto>, _claims: ListUsersClaims) -> impl Responder {
+    let users = service::get().user().get_filtered_users(filters.into_inner()).await;
+
+    json_response(&users)
+}
 
-    check_permission!(service::get().permission().retrieve_user(claims.sub, requested_user_id).await);
+pub async fn get_profile(requested_user_id: web::Path<i64>, _claims: ReadUserClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
 
     let user_service = service::get().user();
 
@@ -21,3 +39,108 @@ pub async fn get_profile(requested_user_id: web::Path<i64>, claims: Claims) -> i
 
     json_response(&user)
 }
+
+pub async fn update_user(requested_user_id: web::Path<i64>, update: web::Json<UpdateUserDto>, _claims: UpdateUserClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
+
+    let user = service::get().user().update_user(requested_user_id, update.into_inner()).await;
+
+    json_response(&user)
+}
+
+/// Replaces the permissions of the user with the ones of the role
+pub async fn change_role(requested_user_id: web::Path<i64>, role: web::Json<ChangeRoleDto>, claims: ChangeUserRoleClaims) -> impl Responder {
+    let ChangeUserRoleClaims(claims) = claims;
+    let requested_user_id = requested_user_id.into_inner();
+    let role_id = role.into_inner().role_id;
+
+    let user = service::get().user().change_role(&claims, requested_user_id, role_id).await;
+
+    json_response(&user)
+}
+
+pub async fn change_department(requested_user_id: web::Path<i64>, department: web::Json<ChangeDepartmentDto>, _claims: AdministerUserClaims) -> impl Responder {
+    let user = service::get().user().change_department(requested_user_id.into_inner(), department.into_inner().department_id).await;
+
+    json_response(&user)
+}
+
+/// National ids are personal data, so only admins can see the identifiers, like they are the only ones that can set them
+pub async fn get_identifiers(requested_user_id: web::Path<i64>, _claims: AdministerUserClaims) -> impl Responder {
+    let identifiers = service::get().user().get_user_identifiers(requested_user_id.into_inner()).await;
+
+    json_response(&identifiers)
+}
+
+pub async fn update_identifiers(requested_user_id: web::Path<i64>, identifiers: web::Json<UpdateUserIdentifiersDto>, _claims: AdministerUserClaims) -> impl Responder {
+    let identifiers = service::get().user().update_user_identifiers(requested_user_id.into_inner(), identifiers.into_inner()).await;
+
+    json_response(&identifiers)
+}
+
+/// Blocks the sign in of the user without deleting their payrolls
+pub async fn deactivate(requested_user_id: web::Path<i64>, claims: DeleteUserClaims) -> impl Responder {
+    let DeleteUserClaims(claims) = claims;
+    let requested_user_id = requested_user_id.into_inner();
+
+    let auth_service = service::get().auth();
+    let result = auth_service.deactivate_user(claims.sub, requested_user_id).await;
+
+    json_response(&result)
+}
+
+pub async fn reactivate(requested_user_id: web::Path<i64>, _claims: DeleteUserClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
+
+    let auth_service = service::get().auth();
+    let result = auth_service.reactivate_user(requested_user_id).await;
+
+    json_response(&result)
+}
+
+pub async fn revoke_sessions(requested_user_id: web::Path<i64>, _claims: UpdateUserClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
+
+    let revoked = service::get().session().revoke_user_sessions(requested_user_id).await;
+
+    json_response(&revoked)
+}
+
+pub async fn change_password(passwords: web::Json<ChangePasswordDto>, claims: PasswordChangeClaims) -> impl Responder {
+    let PasswordChangeClaims(claims) = claims;
+
+    let auth_service = service::get().auth();
+    let auth = auth_service.change_password(claims.sub, passwords.into_inner()).await;
+
+    json_response(&auth)
+}
+
+pub async fn reset_password(requested_user_id: web::Path<i64>, reset: web::Json<ResetPasswordDto>, _claims: ResetUserPasswordClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
+
+    let auth_service = service::get().auth();
+    let result = auth_service.reset_password(requested_user_id, reset.into_inner()).await;
+
+    json_response(&result)
+}
+
+/// Lifts the sign in lockout of the user
+pub async fn unlock(requested_user_id: web::Path<i64>, _claims: AdministerUserClaims) -> impl Responder {
+    let requested_user_id = requested_user_id.into_inner();
+
+    let auth_service = service::get().auth();
+    let result = auth_service.unlock_user(requested_user_id).await;
+
+    json_response(&result)
+}
+
+/// Issues a token to view the application as the user. Impersonation tokens cannot start another impersonation
+pub async fn impersonate(requested_user_id: web::Path<i64>, impersonation: web::Json<StartImpersonationDto>, claims: ImpersonateUserClaims) -> impl Responder {
+    let ImpersonateUserClaims(claims) = claims;
+    let requested_user_id = requested_user_id.into_inner();
+
+    let auth_service = service::get().auth();
+    let auth = auth_service.impersonate(&claims, requested_user_id, impersonation.into_inner()).await;
+
+    json_response(&auth)
+}
@@ -0,0 +1,75 @@
+use crate::{auth::{jwt::TokenScope::{Full, Impersonation}, policy::{path_id, query_id, Action, Policy, Resource}}, authorized_claims, entities::permission::permission::{Operation, ScopeKind::{Any, Owned, SelfCompany}}};
+
+use super::user::CreateUserDto;
+
+/// Users can read and update their own profile. Creating and deactivating users is left to admins.
+pub const USER_POLICY: Policy = Policy {
+    create: &[Any, SelfCompany],
+    read: &[Any, SelfCompany, Owned],
+    update: &[Any, SelfCompany, Owned],
+    delete: &[Any, SelfCompany]
+};
+
+authorized_claims!(
+    /// Claims of an actor allowed to list the users of the `company_id` query parameter, or every user without it
+    ListUsersClaims, Operation::Read, |req| Some(Resource::Users(query_id(req, "company_id")))
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to create users in the `company_id` of the path
+    CreateCompanyUserClaims, Operation::Create, |req| path_id(req, "company_id").map(|company_id| Resource::Users(Some(company_id)))
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to create the user of the body, in its company
+    SignUpClaims, action: Action::new(Operation::Create), json |user: CreateUserDto| Some(Resource::Users(Some(user.company_id)))
+);
+
+authorized_claims!(
+    ReadUserClaims, Operation::Read, |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Actors can update themselves, but other users only when their permissions are included in the ones of the
+    /// actor, so the account of a user above them cannot be taken over. API keys cannot update users.
+    UpdateUserClaims,
+    action: Action::new(Operation::Update).tokens(&[Full, Impersonation]).outranking_owner(),
+    |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Claims of an actor allowed to update the user as an admin of their company. Owning the user is not enough, but
+    /// admins can still administer themselves.
+    AdministerUserClaims, Operation::Update, &[Any, SelfCompany], |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Users are deactivated instead of deleted, so it requires the delete permission. Users above the actor cannot be
+    /// deactivated or reactivated by them, nor by API keys.
+    DeleteUserClaims,
+    action: Action::new(Operation::Delete).tokens(&[Full, Impersonation]).outranking_owner(),
+    |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Actors can only change the role of users whose permissions are included in theirs. The role given is checked
+    /// the same way when it is changed.
+    ChangeUserRoleClaims,
+    action: Action::new(Operation::Update).scopes(&[Any, SelfCompany]).tokens(&[Full]).outranking_owner(),
+    |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Passwords are never reset while impersonating or with an API key, nor for users whose permissions are not
+    /// included in the ones of the actor
+    ResetUserPasswordClaims,
+    action: Action::new(Operation::Update).scopes(&[Any, SelfCompany]).tokens(&[Full]).outranking_owner(),
+    |req| path_id(req, "requested_user_id").map(Resource::User)
+);
+
+authorized_claims!(
+    /// Only users that can update any user may impersonate, with their own session and never someone above them
+    ImpersonateUserClaims,
+    action: Action::new(Operation::Update).scopes(&[Any]).tokens(&[Full]).outranking_owner(),
+    |req| path_id(req, "requested_user_id").map(Resource::User)
+);
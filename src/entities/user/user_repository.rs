@@ -1,8 +1,8 @@
-use sqlx::SqliteConnection;
+use sqlx::{QueryBuilder, SqliteConnection};
 
 use crate::{error::error::AppError, util::db::to_app_error};
 
-use super::user::{CreateUserDb, RetrieveAuthUserDb, RetrieveUserDb};
+use super::{custom_models::user_filter::UserFilterDb, user::{CreateUserDb, RetrieveAuthUserDb, RetrieveUserDb, UserIdentifiersDb}};
 
 pub struct UserRepository {}
 
@@ -18,15 +18,17 @@ impl UserRepository {
         sqlx::query_as!(
             RetrieveUserDb,
             r#"
-            INSERT INTO AppUser (username, email, name, password, company_id)
-            VALUES($1, $2, $3, $4, $5)
-            RETURNING id as "id!: i64", username, email, name, company_id
+            INSERT INTO AppUser (username, email, name, password, company_id, role_id, status)
+            VALUES($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id as "id!: i64", username, email, name, company_id, role_id, department_id, status
             "#,
             user.username,
             user.email,
             user.name,
             user.password,
-            user.company_id
+            user.company_id,
+            user.role_id,
+            user.status
         )
         .fetch_one(tx)
         .await
@@ -53,7 +55,7 @@ impl UserRepository {
         sqlx::query_as!(
             RetrieveAuthUserDb,
             r#"
-            SELECT id as "id!: i64", username, email, name, password, company_id
+            SELECT id as "id!: i64", username, email, name, password, company_id, role_id, department_id, must_change_password as "must_change_password: bool", status
             FROM AppUser
             WHERE username = $1
             LIMIT 1
@@ -65,11 +67,94 @@ impl UserRepository {
         .map_err(to_app_error)
     }
 
+    pub async fn get_auth_user_by_id(&self, tx: &mut SqliteConnection, id: i64) -> Result<Option<RetrieveAuthUserDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveAuthUserDb,
+            r#"
+            SELECT id as "id!: i64", username, email, name, password, company_id, role_id, department_id, must_change_password as "must_change_password: bool", status
+            FROM AppUser
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_password(&self, tx: &mut SqliteConnection, user_id: i64, password: &str, must_change_password: bool) -> Result<bool, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET password = $1, must_change_password = $2
+            WHERE id = $3
+            "#,
+            password,
+            must_change_password,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_auth_user_by_oidc_subject(&self, tx: &mut SqliteConnection, oidc_subject: &str) -> Result<Option<RetrieveAuthUserDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveAuthUserDb,
+            r#"
+            SELECT id as "id!: i64", username, email, name, password, company_id, role_id, department_id, must_change_password as "must_change_password: bool", status
+            FROM AppUser
+            WHERE oidc_subject = $1
+            LIMIT 1
+            "#,
+            oidc_subject
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Users with the given email that are not linked to an identity yet. At most two are returned, which is enough
+    /// to tell if the email is ambiguous.
+    pub async fn get_unlinked_auth_users_by_email(&self, tx: &mut SqliteConnection, email: &str) -> Result<Vec<RetrieveAuthUserDb>, AppError> {
+        sqlx::query_as!(
+            RetrieveAuthUserDb,
+            r#"
+            SELECT id as "id!: i64", username, email, name, password, company_id, role_id, department_id, must_change_password as "must_change_password: bool", status
+            FROM AppUser
+            WHERE lower(email) = lower($1) AND oidc_subject IS NULL
+            LIMIT 2
+            "#,
+            email
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_oidc_subject(&self, tx: &mut SqliteConnection, user_id: i64, oidc_subject: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET oidc_subject = $1
+            WHERE id = $2
+            "#,
+            oidc_subject,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
     pub async fn get_user_by_id(&self, tx: &mut SqliteConnection, id: i64) -> Result<Option<RetrieveUserDb>, AppError> {
         sqlx::query_as!(
             RetrieveUserDb,
             r#"
-            SELECT id as "id!: i64", username, email, name, company_id
+            SELECT id as "id!: i64", username, email, name, company_id, role_id, department_id, status
             FROM AppUser
             WHERE id = $1
             LIMIT 1
@@ -81,6 +166,104 @@ impl UserRepository {
         .map_err(to_app_error)
     }
 
+    pub async fn get_filtered_users(&self, tx: &mut SqliteConnection, filter: &UserFilterDb) -> Result<Vec<RetrieveUserDb>, AppError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT id, username, email, name, company_id, role_id, department_id, status
+            FROM AppUser
+            "#
+        );
+
+        filter.fill_query(&mut query);
+
+        query.build_query_as()
+            .fetch_all(tx)
+            .await
+            .map_err(to_app_error)
+    }
+
+    pub async fn count_filtered_users(&self, tx: &mut SqliteConnection, filter: &UserFilterDb) -> Result<i64, AppError> {
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT COUNT(*)
+            FROM AppUser
+            "#
+        );
+
+        filter.fill_conditions(&mut query);
+
+        query.build_query_scalar()
+            .fetch_one(tx)
+            .await
+            .map_err(to_app_error)
+    }
+
+    pub async fn update_user(&self, tx: &mut SqliteConnection, user_id: i64, username: &str, email: &Option<String>, name: &str) -> Result<RetrieveUserDb, AppError> {
+        sqlx::query_as!(
+            RetrieveUserDb,
+            r#"
+            UPDATE AppUser
+            SET username = $1, email = $2, name = $3
+            WHERE id = $4
+            RETURNING id as "id!: i64", username, email, name, company_id, role_id, department_id, status
+            "#,
+            username,
+            email,
+            name,
+            user_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_role(&self, tx: &mut SqliteConnection, user_id: i64, role_id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET role_id = $1
+            WHERE id = $2
+            "#,
+            role_id,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
+    pub async fn count_users_by_role_and_status(&self, tx: &mut SqliteConnection, role_id: i64, status: &str) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM AppUser
+            WHERE role_id = $1 AND status = $2
+            "#,
+            role_id,
+            status
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_status(&self, tx: &mut SqliteConnection, user_id: i64, status: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE AppUser
+            SET status = $1
+            WHERE id = $2
+            "#,
+            status,
+            user_id
+        )
+        .execute(tx)
+        .await
+        .map(|_| ())
+        .map_err(to_app_error)
+    }
+
     pub async fn get_company_id_by_user_id(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Option<i64>, AppError> {
         sqlx::query_scalar!(
             r#"
@@ -95,4 +278,155 @@ impl UserRepository {
         .await
         .map_err(to_app_error)
     }
-}
+
+    pub async fn get_user_id_by_username_and_company_id(&self, tx: &mut SqliteConnection, username: &str, company_id: i64) -> Result<Option<i64>, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT id as "id!: i64"
+            FROM AppUser
+            WHERE username = $1 AND company_id = $2
+            LIMIT 1
+            "#,
+            username,
+            company_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn count_users_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<i64, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM AppUser
+            WHERE company_id = $1
+            "#,
+            company_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_department(&self, tx: &mut SqliteConnection, user_id: i64, department_id: Option<i64>) -> Result<RetrieveUserDb, AppError> {
+        sqlx::query_as!(
+            RetrieveUserDb,
+            r#"
+            UPDATE AppUser
+            SET department_id = $1
+            WHERE id = $2
+            RETURNING id as "id!: i64", username, email, name, company_id, role_id, department_id, status
+            "#,
+            department_id,
+            user_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_department_id_by_user_id(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Option<i64>, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT department_id as "department_id: i64"
+            FROM AppUser
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(Option::flatten)
+        .map_err(to_app_error)
+    }
+
+    pub async fn get_user_identifiers(&self, tx: &mut SqliteConnection, user_id: i64) -> Result<Option<UserIdentifiersDb>, AppError> {
+        sqlx::query_as!(
+            UserIdentifiersDb,
+            r#"
+            SELECT id as "id!: i64", national_id, employee_number
+            FROM AppUser
+            WHERE id = $1
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Users of the company that have any identifier
+    pub async fn get_user_identifiers_by_company_id(&self, tx: &mut SqliteConnection, company_id: i64) -> Result<Vec<UserIdentifiersDb>, AppError> {
+        sqlx::query_as!(
+            UserIdentifiersDb,
+            r#"
+            SELECT id as "id!: i64", national_id, employee_number
+            FROM AppUser
+            WHERE company_id = $1 AND (national_id IS NOT NULL OR employee_number IS NOT NULL)
+            "#,
+            company_id
+        )
+        .fetch_all(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    pub async fn update_user_identifiers(&self, tx: &mut SqliteConnection, user_id: i64, national_id: &Option<String>, employee_number: &Option<String>) -> Result<UserIdentifiersDb, AppError> {
+        sqlx::query_as!(
+            UserIdentifiersDb,
+            r#"
+            UPDATE AppUser
+            SET national_id = $1, employee_number = $2
+            WHERE id = $3
+            RETURNING id as "id!: i64", national_id, employee_number
+            "#,
+            national_id,
+            employee_number,
+            user_id
+        )
+        .fetch_one(tx)
+        .await
+        .map_err(to_app_error)
+    }
+
+    /// Whether another user of the company has the national id
+    pub async fn national_id_exists(&self, tx: &mut SqliteConnection, company_id: i64, national_id: &str, user_id: i64) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM AppUser
+            WHERE company_id = $1 AND national_id = $2 AND id != $3
+            LIMIT 1
+            "#,
+            company_id,
+            national_id,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|val| val.is_some())
+        .map_err(to_app_error)
+    }
+
+    /// Whether another user of the company has the employee number
+    pub async fn employee_number_exists(&self, tx: &mut SqliteConnection, company_id: i64, employee_number: &str, user_id: i64) -> Result<bool, AppError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT 1 as "exists!: i64"
+            FROM AppUser
+            WHERE company_id = $1 AND employee_number = $2 AND id != $3
+            LIMIT 1
+            "#,
+            company_id,
+            employee_number,
+            user_id
+        )
+        .fetch_optional(tx)
+        .await
+        .map(|val| val.is_some())
+        .map_err(to_app_error)
+    }
+}
\ No newline at end of file
@@ -1,9 +1,9 @@
 use macros::executor;
 use sqlx::{SqliteConnection, SqlitePool};
 
-use crate::{error::error::{AppError, AppErrorType}, service};
+use crate::{auth::{jwt::Claims, policy::permission_denied}, entities::{invitation::invitation::RetrieveInvitationDb, role::role::Role}, error::error::{AppError, AppErrorType}, service, util::pagination::Page};
 
-use super::{user::{CreateUserDb, CreateUserDto, RetrieveAuthUserDto, RetrieveUserDto}, user_repository::UserRepository};
+use super::{custom_dto::user_dto::UpdateUserDto, custom_models::user_filter::{UserFilterDb, UserFilterDto}, user::{CreateUserDb, CreateUserDto, RetrieveAuthUserDto, RetrieveUserDb, RetrieveUserDto, UpdateUserIdentifiersDto, User, UserIdentifiersDto, UserStatus}, user_repository::UserRepository};
 
 pub struct UserService {
     db_pool: SqlitePool,
@@ -18,8 +18,10 @@ impl UserService {
         }
     }
 
+    /// Users created by an actor can only get a role included in the permissions of the actor. Users provisioned by
+    /// the application itself have no actor.
     #[executor] 
-    pub async fn create_user(&self, create_user_dto: CreateUserDto) -> Result<RetrieveUserDto, AppError> {
+    pub async fn create_user(&self, actor: Option<&Claims>, create_user_dto: CreateUserDto, status: UserStatus) -> Result<RetrieveUserDto, AppError> {
         if self.user_repository.user_exists_by_username(tx, &create_user_dto.username).await? {
             return Err(AppError::new(
                 String::from(r#"User with username "$1" already exists"#),
@@ -28,36 +30,205 @@ impl UserService {
             ));
         }
 
-        if !service::get().company().company_exists_by_id(create_user_dto.company_id).await? {
-            return Err(AppError::new(
+        match service::get().company().find_company_executor(tx, create_user_dto.company_id).await? {
+            Some(company) if company.archived_at.is_some() => return Err(AppError::new(
+                String::from(r#"Company with id "$1" is archived"#),
+                AppErrorType::Conflict,
+                Some(vec![create_user_dto.company_id.to_string()])
+            )),
+            Some(_) => (),
+            None => return Err(AppError::new(
                 String::from(r#"Company with id "$1" does not exist"#),
                 AppErrorType::BadRequest,
                 Some(vec![create_user_dto.company_id.to_string()])
             ))
         }
 
-        let role = create_user_dto.role;
-        let create_user_db = CreateUserDb::from_create_user_dto(create_user_dto)?;
+        match actor {
+            Some(actor) => Self::check_role_assignable(tx, actor, create_user_dto.role_id).await?,
+            None => if !service::get().role().role_exists_executor(tx, create_user_dto.role_id).await? {
+                return Err(Self::role_not_found(create_user_dto.role_id));
+            }
+        }
 
-        let created_user = self.user_repository.create_user(tx, &create_user_db).await?;
+        let create_user_db = CreateUserDb::from_create_user_dto(create_user_dto, status)?;
 
-        service::get().permission().create_permission_from_role_executor(tx, created_user.id, role).await?;
+        let created_user = self.user_repository.create_user(tx, &create_user_db).await?;
 
         Ok(created_user.to_retrieve_user_dto()?)
     }
 
     #[executor]
     pub async fn get_auth_user_by_username(&self, username: &str) -> Result<Option<RetrieveAuthUserDto>, AppError> {
+        // Not finding the user is not an error here, callers must not reveal whether the username exists
         match self.user_repository.get_auth_user_by_username(tx, username).await? {
+            Some(user) => Ok(Some(user.to_retrieve_auth_user_dto()?)),
+            None => Ok(None)
+        }
+    }
+
+    #[executor]
+    pub async fn get_auth_user_by_id(&self, id: i64) -> Result<Option<RetrieveAuthUserDto>, AppError> {
+        match self.user_repository.get_auth_user_by_id(tx, id).await? {
             Some(user) => Ok(Some(user.to_retrieve_auth_user_dto()?)),
             None => Err(AppError::new(
-                String::from(r#"User with username "$1" does not exist"#),
+                String::from(r#"User with id "$1" does not exist"#),
                 AppErrorType::NotFound,
-                Some(vec![username.to_string()])
+                Some(vec![id.to_string()])
             ))
         }
     }
 
+    /// Stores an already hashed password. Setting `must_change_password` restricts the user to the change-password
+    /// route until they pick a new one.
+    #[executor]
+    pub async fn update_password(&self, user_id: i64, hashed_password: &str, must_change_password: bool) -> Result<(), AppError> {
+        if !self.user_repository.update_password(tx, user_id, hashed_password, must_change_password).await? {
+            return Err(Self::user_not_found(user_id));
+        }
+
+        Ok(())
+    }
+
+    #[executor]
+    pub async fn get_filtered_users(&self, filter: UserFilterDto) -> Result<Page<RetrieveUserDto>, AppError> {
+        let filter = UserFilterDb::from_user_filter_dto(filter)?;
+
+        let users = self.user_repository.get_filtered_users(tx, &filter).await?;
+        let total = self.user_repository.count_filtered_users(tx, &filter).await?;
+
+        Page::new(users, total, &filter.pagination).try_map(|user| user.to_retrieve_user_dto())
+    }
+
+    #[executor]
+    pub async fn update_user(&self, user_id: i64, update: UpdateUserDto) -> Result<RetrieveUserDto, AppError> {
+        let user = match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user,
+            None => return Err(Self::user_not_found(user_id))
+        };
+
+        let username = update.username.unwrap_or(user.username.clone());
+        let email = match update.email {
+            Some(email) => Some(email.trim().to_lowercase()),
+            None => user.email
+        };
+        let name = update.name.unwrap_or(user.name);
+
+        User::check_username(&username)?;
+        User::check_email(&email)?;
+        User::check_name(&name)?;
+
+        if username != user.username && self.user_repository.user_exists_by_username(tx, &username).await? {
+            return Err(AppError::new(
+                String::from(r#"User with username "$1" already exists"#),
+                AppErrorType::Conflict,
+                Some(vec![username])
+            ));
+        }
+
+        self.user_repository.update_user(tx, user_id, &username, &email, &name).await?.to_retrieve_user_dto()
+    }
+
+    /// The permissions of the user are the ones of the role. The last active super admin keeps their role, so the
+    /// application can still be administered. The check and the change are made in one transaction, so two super
+    /// admins cannot demote each other at the same time.
+    #[executor(transaction)]
+    pub async fn change_role(&self, actor: &Claims, user_id: i64, role_id: i64) -> Result<RetrieveUserDto, AppError> {
+        let user = match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user,
+            None => return Err(Self::user_not_found(user_id))
+        };
+
+        Self::check_role_assignable(tx, actor, role_id).await?;
+
+        if role_id != Role::SUPER_ADMIN_ID && self.is_last_super_admin(tx, &user).await? {
+            return Err(Self::last_super_admin());
+        }
+
+        self.user_repository.update_role(tx, user_id, role_id).await?;
+
+        match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user.to_retrieve_user_dto(),
+            None => Err(Self::user_not_found(user_id))
+        }
+    }
+
+    /// Blocks the sign in of the user and revokes their invitations and sessions at once. The last active super admin
+    /// cannot be deactivated.
+    #[executor(transaction)]
+    pub async fn deactivate(&self, user_id: i64) -> Result<(), AppError> {
+        let user = match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user,
+            None => return Err(Self::user_not_found(user_id))
+        };
+
+        if self.is_last_super_admin(tx, &user).await? {
+            return Err(Self::last_super_admin());
+        }
+
+        self.user_repository.update_status(tx, user_id, UserStatus::Inactive.as_str()).await?;
+        service::get().invitation().revoke_user_invitations_executor(tx, user_id).await?;
+        service::get().session().revoke_user_sessions_executor(tx, user_id).await?;
+
+        Ok(())
+    }
+
+    /// Creates a pending user and their invitation at once, so the username is never taken by a user that cannot be
+    /// invited. Returns the invitation with the raw token.
+    #[executor(transaction)]
+    pub async fn invite(&self, actor: &Claims, create_user_dto: CreateUserDto, email: &str) -> Result<(RetrieveUserDto, RetrieveInvitationDb, String), AppError> {
+        let company_id = create_user_dto.company_id;
+
+        let user = self.create_user_executor(tx, Some(actor), create_user_dto, UserStatus::Pending).await?;
+        let (invitation, token) = service::get().invitation().create_invitation_executor(tx, user.id, company_id, actor.sub, email).await?;
+
+        Ok((user, invitation, token))
+    }
+
+    /// Consumes the invitation token and activates the user with the chosen password at once, so the token is only
+    /// used up when the user is activated.
+    #[executor(transaction)]
+    pub async fn accept_invitation(&self, token: &str, hashed_password: &str) -> Result<(), AppError> {
+        let user_id = service::get().invitation().consume_token_executor(tx, token).await?;
+
+        self.update_password_executor(tx, user_id, hashed_password, false).await?;
+        self.user_repository.update_status(tx, user_id, UserStatus::Active.as_str()).await
+    }
+
+    #[executor]
+    pub async fn update_status(&self, user_id: i64, status: UserStatus) -> Result<(), AppError> {
+        self.user_repository.update_status(tx, user_id, status.as_str()).await
+    }
+
+    #[executor]
+    pub async fn get_auth_user_by_oidc_subject(&self, oidc_subject: &str) -> Result<Option<RetrieveAuthUserDto>, AppError> {
+        match self.user_repository.get_auth_user_by_oidc_subject(tx, oidc_subject).await? {
+            Some(user) => Ok(Some(user.to_retrieve_auth_user_dto()?)),
+            None => Ok(None)
+        }
+    }
+
+    /// The only user with the given email that is not linked to an identity yet, if there is exactly one
+    #[executor]
+    pub async fn get_unlinked_auth_user_by_email(&self, email: &str) -> Result<Option<RetrieveAuthUserDto>, AppError> {
+        let mut users = self.user_repository.get_unlinked_auth_users_by_email(tx, email).await?;
+
+        match users.len() {
+            1 => Ok(Some(users.remove(0).to_retrieve_auth_user_dto()?)),
+            _ => Ok(None)
+        }
+    }
+
+    #[executor]
+    pub async fn link_oidc_subject(&self, user_id: i64, oidc_subject: &str) -> Result<(), AppError> {
+        self.user_repository.update_oidc_subject(tx, user_id, oidc_subject).await
+    }
+
+    #[executor]
+    pub async fn username_exists(&self, username: &str) -> Result<bool, AppError> {
+        self.user_repository.user_exists_by_username(tx, username).await
+    }
+
     #[executor]
     pub async fn get_user_by_id(&self, id: i64) -> Result<Option<RetrieveUserDto>, AppError> {
         match self.user_repository.get_user_by_id(tx, id).await? {
@@ -74,4 +245,140 @@ impl UserService {
     pub async fn get_company_by_user_id(&self, user_id: i64) -> Result<Option<i64>, AppError> {
         self.user_repository.get_company_id_by_user_id(tx, user_id).await
     }
-}
+
+    /// Id of the user of the company with the username, if there is one
+    #[executor]
+    pub async fn get_company_user_id_by_username(&self, company_id: i64, username: &str) -> Result<Option<i64>, AppError> {
+        self.user_repository.get_user_id_by_username_and_company_id(tx, username, company_id).await
+    }
+
+    #[executor]
+    pub async fn get_department_by_user_id(&self, user_id: i64) -> Result<Option<i64>, AppError> {
+        self.user_repository.get_department_id_by_user_id(tx, user_id).await
+    }
+
+    /// Moves the user to a department of their company, or out of any department
+    #[executor]
+    pub async fn change_department(&self, user_id: i64, department_id: Option<i64>) -> Result<RetrieveUserDto, AppError> {
+        let user = match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user,
+            None => return Err(Self::user_not_found(user_id))
+        };
+
+        if let Some(department_id) = department_id {
+            if service::get().department().get_company_by_department_id_executor(tx, department_id).await? != Some(user.company_id) {
+                return Err(AppError::new(
+                    String::from(r#"Department with id "$1" does not exist in the company of the user"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![department_id.to_string()])
+                ));
+            }
+        }
+
+        self.user_repository.update_department(tx, user_id, department_id).await?.to_retrieve_user_dto()
+    }
+
+    #[executor]
+    pub async fn get_user_identifiers(&self, user_id: i64) -> Result<UserIdentifiersDto, AppError> {
+        match self.user_repository.get_user_identifiers(tx, user_id).await? {
+            Some(identifiers) => Ok(identifiers.to_user_identifiers_dto()),
+            None => Err(Self::user_not_found(user_id))
+        }
+    }
+
+    /// Sets the identifiers of the employee, which must be unique in their company. Missing ones are cleared.
+    #[executor]
+    pub async fn update_user_identifiers(&self, user_id: i64, identifiers: UpdateUserIdentifiersDto) -> Result<UserIdentifiersDto, AppError> {
+        let user = match self.user_repository.get_user_by_id(tx, user_id).await? {
+            Some(user) => user,
+            None => return Err(Self::user_not_found(user_id))
+        };
+
+        let identifiers = identifiers.normalized()?;
+
+        if let Some(national_id) = &identifiers.national_id {
+            if self.user_repository.national_id_exists(tx, user.company_id, national_id, user_id).await? {
+                return Err(AppError::new(
+                    String::from(r#"User with national id "$1" already exists in the company"#),
+                    AppErrorType::Conflict,
+                    Some(vec![national_id.clone()])
+                ));
+            }
+        }
+
+        if let Some(employee_number) = &identifiers.employee_number {
+            if self.user_repository.employee_number_exists(tx, user.company_id, employee_number, user_id).await? {
+                return Err(AppError::new(
+                    String::from(r#"User with employee number "$1" already exists in the company"#),
+                    AppErrorType::Conflict,
+                    Some(vec![employee_number.clone()])
+                ));
+            }
+        }
+
+        let updated = self.user_repository.update_user_identifiers(tx, user_id, &identifiers.national_id, &identifiers.employee_number).await?;
+
+        Ok(updated.to_user_identifiers_dto())
+    }
+
+    /// Identifiers of the users of the company, to find them in the documents of the company
+    #[executor]
+    pub async fn get_user_identifiers_by_company_id(&self, company_id: i64) -> Result<Vec<UserIdentifiersDto>, AppError> {
+        let identifiers = self.user_repository.get_user_identifiers_by_company_id(tx, company_id).await?;
+
+        Ok(identifiers.into_iter().map(|identifiers| identifiers.to_user_identifiers_dto()).collect())
+    }
+
+    #[executor]
+    pub async fn count_users_by_company_id(&self, company_id: i64) -> Result<i64, AppError> {
+        self.user_repository.count_users_by_company_id(tx, company_id).await
+    }
+
+    async fn is_last_super_admin(&self, tx: &mut SqliteConnection, user: &RetrieveUserDb) -> Result<bool, AppError> {
+        if user.role_id != Role::SUPER_ADMIN_ID || user.status != UserStatus::Active.as_str() {
+            return Ok(false);
+        }
+
+        let super_admins = self.user_repository.count_users_by_role_and_status(tx, Role::SUPER_ADMIN_ID, UserStatus::Active.as_str()).await?;
+
+        Ok(super_admins <= 1)
+    }
+
+    fn user_not_found(user_id: i64) -> AppError {
+        AppError::new(
+            String::from(r#"User with id "$1" does not exist"#),
+            AppErrorType::NotFound,
+            Some(vec![user_id.to_string()])
+        )
+    }
+
+    /// Actors can only assign roles included in their own permissions
+    async fn check_role_assignable(tx: &mut SqliteConnection, actor: &Claims, role_id: i64) -> Result<(), AppError> {
+        let role = match service::get().role().find_role_executor(tx, role_id).await? {
+            Some(role) => role,
+            None => return Err(Self::role_not_found(role_id))
+        };
+
+        if !service::get().permission().can_give_executor(tx, actor, &role.permission(actor.sub)).await? {
+            return Err(permission_denied());
+        }
+
+        Ok(())
+    }
+
+    fn role_not_found(role_id: i64) -> AppError {
+        AppError::new(
+            String::from(r#"Role with id "$1" does not exist"#),
+            AppErrorType::BadRequest,
+            Some(vec![role_id.to_string()])
+        )
+    }
+
+    fn last_super_admin() -> AppError {
+        AppError::new(
+            String::from("The last super admin cannot lose their role"),
+            AppErrorType::Conflict,
+            None
+        )
+    }
+}
\ No newline at end of file
@@ -1,5 +1,8 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use serde::{ser::SerializeStruct, Serialize};
 
+use super::http_error_code::http_error_code;
+
 #[derive(Debug)]
 pub struct AppError {
     message: String,
@@ -26,6 +29,10 @@ impl AppError {
         &self.message
     }
 
+    pub fn error_type(&self) -> AppErrorType {
+        self.r#type
+    }
+
     /// Converts any error that implements the `Display` trait into an `AppError`
     /// with the `InternalServerError` type.
     ///
@@ -61,7 +68,8 @@ impl AppError {
             AppErrorType::Forbidden |
             AppErrorType::NotFound |
             AppErrorType::Conflict |
-            AppErrorType::UnsupportedMediaType => AppErrorScope::Public,
+            AppErrorType::UnsupportedMediaType |
+            AppErrorType::TooManyRequests => AppErrorScope::Public,
 
             AppErrorType::InternalServerError |
             AppErrorType::NotImplemented => AppErrorScope::Internal
@@ -88,7 +96,24 @@ impl Serialize for AppError {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Lets extractors fail with the same response that `json_response` builds for the error
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.code(http_error_code)).unwrap()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppErrorType {
     BadRequest,
     Unauthorized,
@@ -96,6 +121,7 @@ pub enum AppErrorType {
     NotFound,
     Conflict,
     UnsupportedMediaType,
+    TooManyRequests,
     InternalServerError,
     NotImplemented
 }
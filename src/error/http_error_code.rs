@@ -8,6 +8,7 @@ pub fn http_error_code(error_type: AppErrorType) -> u16 {
         AppErrorType::NotFound => 404,
         AppErrorType::Conflict => 409,
         AppErrorType::UnsupportedMediaType => 415,
+        AppErrorType::TooManyRequests => 429,
         AppErrorType::InternalServerError => 500,
         AppErrorType::NotImplemented => 501
     }
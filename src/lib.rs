@@ -15,6 +15,20 @@ pub fn initialize_config() {
     const DATABASE_URL: &str = "DATABASE_URL";
     //AUTH
     const AUTH_SECRET: &str = "AUTH_SECRET";
+    const AUTH_SIGNING_KEYS: &str = "AUTH_SIGNING_KEYS";
+    const AUTH_ACTIVE_KEY_ID: &str = "AUTH_ACTIVE_KEY_ID";
+    const AUTH_ACCESS_TOKEN_TTL: &str = "AUTH_ACCESS_TOKEN_TTL";
+    const AUTH_REFRESH_TOKEN_TTL: &str = "AUTH_REFRESH_TOKEN_TTL";
+    const AUTH_PASSWORD_RESET_TOKEN_TTL: &str = "AUTH_PASSWORD_RESET_TOKEN_TTL";
+    const AUTH_INVITATION_TOKEN_TTL: &str = "AUTH_INVITATION_TOKEN_TTL";
+    const AUTH_IMPERSONATION_TTL: &str = "AUTH_IMPERSONATION_TTL";
+    const AUTH_MFA_ISSUER: &str = "AUTH_MFA_ISSUER";
+    const AUTH_MFA_REQUIRED_ROLES: &str = "AUTH_MFA_REQUIRED_ROLES";
+    const MFA_ENCRYPTION_KEY: &str = "MFA_ENCRYPTION_KEY";
+    const AUTH_LOGIN_MAX_FAILURES_PER_USERNAME: &str = "AUTH_LOGIN_MAX_FAILURES_PER_USERNAME";
+    const AUTH_LOGIN_MAX_FAILURES_PER_IP: &str = "AUTH_LOGIN_MAX_FAILURES_PER_IP";
+    const AUTH_LOGIN_LOCKOUT_DURATION: &str = "AUTH_LOGIN_LOCKOUT_DURATION";
+    const AUTH_TRUST_FORWARDED_HEADERS: &str = "AUTH_TRUST_FORWARDED_HEADERS";
     //FILE
     const FILE_TEMP_DIR: &str = "FILE_TEMP_DIR";
     const FILE_MAX_SIZE: &str = "FILE_MAX_SIZE";
@@ -24,6 +38,25 @@ pub fn initialize_config() {
     const BUCKET_ACCESS_KEY: &str = "BUCKET_ACCESS_KEY";
     const BUCKET_SECRET_KEY: &str = "BUCKET_SECRET_KEY";
     const BUCKET_PAYROLL_BASE_BUCKET_NAME: &str = "BUCKET_PAYROLL_BASE_BUCKET_NAME";
+    //MAIL
+    const MAIL_FROM: &str = "MAIL_FROM";
+    const MAIL_TRANSPORT: &str = "MAIL_TRANSPORT";
+    const MAIL_OUTBOX_DIR: &str = "MAIL_OUTBOX_DIR";
+    const MAIL_SMTP_HOST: &str = "MAIL_SMTP_HOST";
+    const MAIL_SMTP_PORT: &str = "MAIL_SMTP_PORT";
+    const MAIL_SMTP_USERNAME: &str = "MAIL_SMTP_USERNAME";
+    const MAIL_SMTP_PASSWORD: &str = "MAIL_SMTP_PASSWORD";
+    const MAIL_SMTP_TLS: &str = "MAIL_SMTP_TLS";
+    const MAIL_PASSWORD_RESET_URL: &str = "MAIL_PASSWORD_RESET_URL";
+    const MAIL_INVITATION_URL: &str = "MAIL_INVITATION_URL";
+    //OIDC
+    const OIDC_ISSUER: &str = "OIDC_ISSUER";
+    const OIDC_CLIENT_ID: &str = "OIDC_CLIENT_ID";
+    const OIDC_CLIENT_SECRET: &str = "OIDC_CLIENT_SECRET";
+    const OIDC_REDIRECT_URL: &str = "OIDC_REDIRECT_URL";
+    const OIDC_SCOPES: &str = "OIDC_SCOPES";
+    const OIDC_PROVISIONING_COMPANY_ID: &str = "OIDC_PROVISIONING_COMPANY_ID";
+    const OIDC_PROVISIONING_ROLE: &str = "OIDC_PROVISIONING_ROLE";
 
 
     let database_config = config::DatabaseConfig {
@@ -31,14 +64,81 @@ pub fn initialize_config() {
     };
 
     let auth_config = {
-        let auth_secret = config::AuthConfig::secret_from_hex_string(
-            &env::var(AUTH_SECRET)
-            .expect(format!("{} must be a valid hex string", AUTH_SECRET).as_str())
-        )
-        .unwrap_or_else(|e| panic!("Invalid {}: {}", AUTH_SECRET, e));
+        let auth_secret = env::var(AUTH_SECRET)
+            .expect(format!("{} must be a valid hex string", AUTH_SECRET).as_str());
+        let auth_secret = match auth_secret.is_empty() {
+            true => None,
+            false => Some(
+                config::AuthConfig::secret_from_hex_string(&auth_secret)
+                    .unwrap_or_else(|e| panic!("Invalid {}: {}", AUTH_SECRET, e))
+            )
+        };
+
+        let mfa_encryption_key = config::AuthConfig::secret_from_hex_string(
+            &env::var(MFA_ENCRYPTION_KEY).expect(format!("{} must be a valid hex string", MFA_ENCRYPTION_KEY).as_str())
+        ).unwrap_or_else(|e| panic!("Invalid {}: {}", MFA_ENCRYPTION_KEY, e));
+        if mfa_encryption_key.len() < 32 {
+            panic!("{} must be at least 32 bytes long", MFA_ENCRYPTION_KEY);
+        }
+
+        // Both are optional so configurations from before asymmetric keys keep signing with the HS256 secret
+        let active_key_id = env::var(AUTH_ACTIVE_KEY_ID).unwrap_or_default();
 
         config::AuthConfig {
-            secret: auth_secret
+            secret: auth_secret,
+            signing_keys: env::var(AUTH_SIGNING_KEYS)
+                .unwrap_or_default()
+                .split(',')
+                .filter(|key| !key.trim().is_empty())
+                .map(|key| key.parse::<config::SigningKeyConfig>()
+                    .unwrap_or_else(|e| panic!("Invalid {}: {}", AUTH_SIGNING_KEYS, e)))
+                .collect(),
+            active_key_id: if active_key_id.is_empty() { None } else { Some(active_key_id) },
+            access_token_ttl: env::var(AUTH_ACCESS_TOKEN_TTL)
+                .expect(format!("{} must be a valid number", AUTH_ACCESS_TOKEN_TTL).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_ACCESS_TOKEN_TTL).as_str()),
+            refresh_token_ttl: env::var(AUTH_REFRESH_TOKEN_TTL)
+                .expect(format!("{} must be a valid number", AUTH_REFRESH_TOKEN_TTL).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_REFRESH_TOKEN_TTL).as_str()),
+            password_reset_token_ttl: env::var(AUTH_PASSWORD_RESET_TOKEN_TTL)
+                .expect(format!("{} must be a valid number", AUTH_PASSWORD_RESET_TOKEN_TTL).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_PASSWORD_RESET_TOKEN_TTL).as_str()),
+            invitation_token_ttl: env::var(AUTH_INVITATION_TOKEN_TTL)
+                .expect(format!("{} must be a valid number", AUTH_INVITATION_TOKEN_TTL).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_INVITATION_TOKEN_TTL).as_str()),
+            impersonation_ttl: env::var(AUTH_IMPERSONATION_TTL)
+                .expect(format!("{} must be a valid number", AUTH_IMPERSONATION_TTL).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_IMPERSONATION_TTL).as_str()),
+            mfa_issuer: env::var(AUTH_MFA_ISSUER)
+                .expect(format!("{} must be a valid name", AUTH_MFA_ISSUER).as_str()),
+            mfa_required_roles: env::var(AUTH_MFA_REQUIRED_ROLES)
+                .expect(format!("{} must be a comma separated list of roles", AUTH_MFA_REQUIRED_ROLES).as_str())
+                .split(',')
+                .map(|role| role.trim().to_string())
+                .filter(|role| !role.is_empty())
+                .collect(),
+            mfa_encryption_key,
+            login_max_failures_per_username: env::var(AUTH_LOGIN_MAX_FAILURES_PER_USERNAME)
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_MAX_FAILURES_PER_USERNAME).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_MAX_FAILURES_PER_USERNAME).as_str()),
+            login_max_failures_per_ip: env::var(AUTH_LOGIN_MAX_FAILURES_PER_IP)
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_MAX_FAILURES_PER_IP).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_MAX_FAILURES_PER_IP).as_str()),
+            login_lockout_duration: env::var(AUTH_LOGIN_LOCKOUT_DURATION)
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_LOCKOUT_DURATION).as_str())
+                .parse()
+                .expect(format!("{} must be a valid number", AUTH_LOGIN_LOCKOUT_DURATION).as_str()),
+            trust_forwarded_headers: env::var(AUTH_TRUST_FORWARDED_HEADERS)
+                .expect(format!("{} must be true or false", AUTH_TRUST_FORWARDED_HEADERS).as_str())
+                .parse()
+                .expect(format!("{} must be true or false", AUTH_TRUST_FORWARDED_HEADERS).as_str())
         }
     };
 
@@ -66,5 +166,74 @@ pub fn initialize_config() {
             expect(format!("{} must be a valid bucket name", BUCKET_PAYROLL_BASE_BUCKET_NAME).as_str())
     };
 
-    config::initialize(database_config, auth_config, file_config, bucket_config);
+    let mail_config = config::MailConfig {
+        from: env::var(MAIL_FROM)
+            .expect(format!("{} must be a valid mailbox", MAIL_FROM).as_str()),
+        transport: match env::var(MAIL_TRANSPORT)
+            .expect(format!("{} must be either smtp or outbox", MAIL_TRANSPORT).as_str())
+            .as_str()
+        {
+            "smtp" => config::MailTransportConfig::Smtp(config::SmtpConfig {
+                host: env::var(MAIL_SMTP_HOST)
+                    .expect(format!("{} must be a valid host", MAIL_SMTP_HOST).as_str()),
+                port: env::var(MAIL_SMTP_PORT)
+                    .expect(format!("{} must be a valid port", MAIL_SMTP_PORT).as_str())
+                    .parse()
+                    .expect(format!("{} must be a valid port", MAIL_SMTP_PORT).as_str()),
+                username: env::var(MAIL_SMTP_USERNAME)
+                    .expect(format!("{} must be a valid username", MAIL_SMTP_USERNAME).as_str()),
+                password: env::var(MAIL_SMTP_PASSWORD)
+                    .expect(format!("{} must be a valid password", MAIL_SMTP_PASSWORD).as_str()),
+                tls: env::var(MAIL_SMTP_TLS)
+                    .expect(format!("{} must be none, starttls or tls", MAIL_SMTP_TLS).as_str())
+                    .parse::<config::SmtpTls>()
+                    .unwrap_or_else(|e| panic!("Invalid {}: {}", MAIL_SMTP_TLS, e))
+            }),
+            "outbox" => config::MailTransportConfig::Outbox(
+                env::var(MAIL_OUTBOX_DIR)
+                    .expect(format!("{} must be a valid directory", MAIL_OUTBOX_DIR).as_str())
+            ),
+            other => panic!("Invalid {}: {}", MAIL_TRANSPORT, other)
+        },
+        password_reset_url: env::var(MAIL_PASSWORD_RESET_URL)
+            .expect(format!("{} must be a valid url", MAIL_PASSWORD_RESET_URL).as_str()),
+        invitation_url: env::var(MAIL_INVITATION_URL)
+            .expect(format!("{} must be a valid url", MAIL_INVITATION_URL).as_str())
+    };
+
+    let oidc_issuer = env::var(OIDC_ISSUER)
+        .expect(format!("{} must be a valid url", OIDC_ISSUER).as_str());
+
+    let oidc_config = if oidc_issuer.is_empty() {
+        None
+    } else {
+        let client_secret = env::var(OIDC_CLIENT_SECRET)
+            .expect(format!("{} must be a valid secret", OIDC_CLIENT_SECRET).as_str());
+        let provisioning_company_id = env::var(OIDC_PROVISIONING_COMPANY_ID)
+            .expect(format!("{} must be a valid company id", OIDC_PROVISIONING_COMPANY_ID).as_str());
+
+        Some(config::OidcConfig {
+            issuer: oidc_issuer.trim_end_matches('/').to_string(),
+            client_id: env::var(OIDC_CLIENT_ID)
+                .expect(format!("{} must be a valid client id", OIDC_CLIENT_ID).as_str()),
+            client_secret: if client_secret.is_empty() { None } else { Some(client_secret) },
+            redirect_url: env::var(OIDC_REDIRECT_URL)
+                .expect(format!("{} must be a valid url", OIDC_REDIRECT_URL).as_str()),
+            scopes: env::var(OIDC_SCOPES)
+                .expect(format!("{} must be a space separated list of scopes", OIDC_SCOPES).as_str()),
+            provisioning: if provisioning_company_id.is_empty() {
+                None
+            } else {
+                Some(config::OidcProvisioningConfig {
+                    company_id: provisioning_company_id
+                        .parse()
+                        .expect(format!("{} must be a valid company id", OIDC_PROVISIONING_COMPANY_ID).as_str()),
+                    role: env::var(OIDC_PROVISIONING_ROLE)
+                        .expect(format!("{} must be a valid role name", OIDC_PROVISIONING_ROLE).as_str())
+                })
+            }
+        })
+    };
+
+    config::initialize(database_config, auth_config, file_config, bucket_config, mail_config, oidc_config);
 }
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use actix_web::{web, App, HttpServer};
 use dotenv::dotenv;
-use payroll_manager::{config::{self}, entities::{company::{self, company_repository::CompanyRepository, company_service::CompanyService}, payroll::{self, payroll_repository::PayrollRepository, payroll_service::PayrollService}, permission::{permission_repository::PermissionRepository, permission_service::PermissionService}}, initialize_config, service::{self, ServiceHub}, user::{self, auth_service::AuthService, user_repository::UserRepository, user_service::UserService}, util::{db::{get_db_pool, run_migrations}, minio::MinioService}};
+use payroll_manager::{auth::{self, keys::{self, KeyStore}}, config::{self}, entities::{api_key::{api_key_repository::ApiKeyRepository, api_key_service::ApiKeyService}, company::{self, company_repository::CompanyRepository, company_service::CompanyService}, company_grant::{company_grant_repository::CompanyGrantRepository, company_grant_service::CompanyGrantService}, department::{department_repository::DepartmentRepository, department_service::DepartmentService}, impersonation::{impersonation_repository::ImpersonationRepository, impersonation_service::ImpersonationService}, invitation::{invitation_repository::InvitationRepository, invitation_service::InvitationService}, login_attempt::{login_attempt_repository::LoginAttemptRepository, login_attempt_service::LoginAttemptService}, mfa::{mfa_repository::MfaRepository, mfa_service::MfaService}, oidc::{oidc_repository::OidcRepository, oidc_service::OidcService}, payroll::{self, payroll_repository::PayrollRepository, payroll_service::PayrollService}, permission::{self, permission_repository::PermissionRepository, permission_service::PermissionService}, password_reset::{password_reset_repository::PasswordResetRepository, password_reset_service::PasswordResetService}, role::{self, role_repository::RoleRepository, role_service::RoleService}, session::{session_repository::SessionRepository, session_service::SessionService}}, initialize_config, service::{self, ServiceHub}, user::{self, auth_service::AuthService, user_repository::UserRepository, user_service::UserService}, util::{db::{get_db_pool, run_migrations}, mail::MailService, minio::MinioService}};
 
 
 #[actix_web::main]
@@ -13,8 +13,11 @@ async fn main() -> std::io::Result<()> {
 
     let config = config::get();
 
+    keys::init(KeyStore::from_config(&config.auth).unwrap_or_else(|e| panic!("Failed to load signing keys: {}", e)));
+
     let db_pool = get_db_pool(&config.database.url).await;
     let minio_service = Arc::new(MinioService::new(&config.bucket.host, &config.bucket.access_key, &config.bucket.secret_key));
+    let mail_service = Arc::new(MailService::from_config(&config.mail));
 
     match run_migrations(&db_pool).await {
         Ok(_) => (),
@@ -29,30 +32,79 @@ async fn main() -> std::io::Result<()> {
     let user_repository = UserRepository::new();
     let user_service = UserService::new(db_pool.clone(), user_repository);
 
-    let auth_service = AuthService::new();
+    let auth_service = AuthService::new(Arc::clone(&mail_service));
 
     let company_repository = CompanyRepository::new();
-    let company_service = CompanyService::new(db_pool.clone(), company_repository);
+    let company_service = CompanyService::new(db_pool.clone(), company_repository, Arc::clone(&minio_service));
 
     let payroll_repository = PayrollRepository::new();
     let payroll_service = PayrollService::new(db_pool.clone(), payroll_repository, Arc::clone(&minio_service));
 
+    let session_repository = SessionRepository::new();
+    let session_service = SessionService::new(db_pool.clone(), session_repository);
+
+    let password_reset_repository = PasswordResetRepository::new();
+    let password_reset_service = PasswordResetService::new(db_pool.clone(), password_reset_repository);
+
+    let mfa_repository = MfaRepository::new();
+    let mfa_service = MfaService::new(db_pool.clone(), mfa_repository);
+
+    let login_attempt_repository = LoginAttemptRepository::new();
+    let login_attempt_service = LoginAttemptService::new(db_pool.clone(), login_attempt_repository);
+
+    let api_key_repository = ApiKeyRepository::new();
+    let api_key_service = ApiKeyService::new(db_pool.clone(), api_key_repository);
+
+    let oidc_repository = OidcRepository::new();
+    let oidc_service = OidcService::new(db_pool.clone(), oidc_repository);
+
+    let impersonation_repository = ImpersonationRepository::new();
+    let impersonation_service = ImpersonationService::new(db_pool.clone(), impersonation_repository);
+
+    let invitation_repository = InvitationRepository::new();
+    let invitation_service = InvitationService::new(db_pool.clone(), invitation_repository);
+
+    let role_repository = RoleRepository::new();
+    let role_service = RoleService::new(db_pool.clone(), role_repository);
+
+    let company_grant_repository = CompanyGrantRepository::new();
+    let company_grant_service = CompanyGrantService::new(db_pool.clone(), company_grant_repository);
+
+    let department_repository = DepartmentRepository::new();
+    let department_service = DepartmentService::new(db_pool.clone(), department_repository);
+
     service::init(ServiceHub {
         permission_service,
         auth_service,
         user_service,
         company_service,
-        payroll_service
+        payroll_service,
+        session_service,
+        password_reset_service,
+        mfa_service,
+        login_attempt_service,
+        api_key_service,
+        oidc_service,
+        impersonation_service,
+        invitation_service,
+        role_service,
+        company_grant_service,
+        department_service
     });
 
+    service::get().mfa().encrypt_plaintext_totp_secrets().await.expect("Failed to encrypt TOTP secrets");
+
     HttpServer::new(move || {
         App::new()
+            .configure(auth::jwks_controller::config)
             .service(
                 web::scope("/api/v1")
                     .configure(user::auth_controller::config)
                     .configure(user::user_controller::config)
                     .configure(company::company_controller::config)
                     .configure(payroll::payroll_controller::config)
+                    .configure(role::role_controller::config)
+                    .configure(permission::permission_controller::config)
             )
     })
     .bind(("127.0.0.1", 8080))?
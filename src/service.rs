@@ -1,13 +1,24 @@
 use std::sync::OnceLock;
 
-use crate::{entities::{company::company_service::CompanyService, payroll::payroll_service::PayrollService, permission::permission_service::PermissionService}, user::{auth_service::AuthService, user_service::UserService}};
+use crate::{entities::{api_key::api_key_service::ApiKeyService, company::company_service::CompanyService, company_grant::company_grant_service::CompanyGrantService, department::department_service::DepartmentService, login_attempt::login_attempt_service::LoginAttemptService, impersonation::impersonation_service::ImpersonationService, invitation::invitation_service::InvitationService, mfa::mfa_service::MfaService, oidc::oidc_service::OidcService, password_reset::password_reset_service::PasswordResetService, payroll::payroll_service::PayrollService, permission::permission_service::PermissionService, role::role_service::RoleService, session::session_service::SessionService}, user::{auth_service::AuthService, user_service::UserService}};
 
 pub struct ServiceHub {
     pub permission_service: PermissionService,
     pub auth_service: AuthService,
     pub user_service: UserService,
     pub company_service: CompanyService,
-    pub payroll_service: PayrollService
+    pub payroll_service: PayrollService,
+    pub session_service: SessionService,
+    pub password_reset_service: PasswordResetService,
+    pub mfa_service: MfaService,
+    pub login_attempt_service: LoginAttemptService,
+    pub api_key_service: ApiKeyService,
+    pub oidc_service: OidcService,
+    pub impersonation_service: ImpersonationService,
+    pub invitation_service: InvitationService,
+    pub role_service: RoleService,
+    pub company_grant_service: CompanyGrantService,
+    pub department_service: DepartmentService
 }
 
 impl ServiceHub {
@@ -30,6 +41,50 @@ impl ServiceHub {
     pub fn payroll(&self) -> &PayrollService {
         &self.payroll_service
     }
+
+    pub fn session(&self) -> &SessionService {
+        &self.session_service
+    }
+
+    pub fn password_reset(&self) -> &PasswordResetService {
+        &self.password_reset_service
+    }
+
+    pub fn mfa(&self) -> &MfaService {
+        &self.mfa_service
+    }
+
+    pub fn login_attempt(&self) -> &LoginAttemptService {
+        &self.login_attempt_service
+    }
+
+    pub fn api_key(&self) -> &ApiKeyService {
+        &self.api_key_service
+    }
+
+    pub fn oidc(&self) -> &OidcService {
+        &self.oidc_service
+    }
+
+    pub fn impersonation(&self) -> &ImpersonationService {
+        &self.impersonation_service
+    }
+
+    pub fn invitation(&self) -> &InvitationService {
+        &self.invitation_service
+    }
+
+    pub fn role(&self) -> &RoleService {
+        &self.role_service
+    }
+
+    pub fn company_grant(&self) -> &CompanyGrantService {
+        &self.company_grant_service
+    }
+
+    pub fn department(&self) -> &DepartmentService {
+        &self.department_service
+    }
 }
 
 static INSTANCE: OnceLock<ServiceHub> = OnceLock::new();
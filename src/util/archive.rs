@@ -0,0 +1,95 @@
+use std::{collections::HashMap, fs::File, io::{self, Read}};
+
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use crate::{error::error::{AppError, AppErrorType}, util::file::ExtractedFile};
+
+/// Extracts the files of the ZIP archive into `dest_dir`, indexed by their name without directories. Hidden files and
+/// the resource forks added by macOS are skipped.
+///
+/// The sizes declared by the archive cannot be trusted, so the limits are checked against the data actually written,
+/// which stops zip bombs as soon as they exceed them. The caller is responsible for removing `dest_dir`, even when the
+/// extraction fails.
+pub fn extract_zip(zip_path: &str, dest_dir: &str, max_file_size: u64, max_total_size: u64) -> Result<HashMap<String, ExtractedFile>, AppError> {
+    let zip_file = File::open(zip_path).map_err(AppError::internal_from_generic)?;
+    let mut archive = ZipArchive::new(zip_file).map_err(invalid_archive)?;
+
+    let mut files = HashMap::new();
+    let mut total_size = 0u64;
+
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(invalid_archive)?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let path = entry.enclosed_name().ok_or_else(|| AppError::new(
+            String::from(r#"The archive contains an invalid path: "$1""#),
+            AppErrorType::BadRequest,
+            Some(vec![entry.name().to_string()])
+        ))?;
+
+        if path.starts_with("__MACOSX") {
+            continue;
+        }
+
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if !name.starts_with('.') => name.to_string(),
+            _ => continue
+        };
+
+        if files.contains_key(&file_name) {
+            return Err(AppError::new(
+                String::from(r#"The archive contains more than one file named "$1""#),
+                AppErrorType::BadRequest,
+                Some(vec![file_name])
+            ));
+        }
+
+        let limit = max_file_size.min(max_total_size - total_size);
+
+        let unique_file_name = Uuid::now_v7().to_string();
+        let file_path = format!("{}/{}", dest_dir, unique_file_name);
+
+        let mut output = File::create(&file_path).map_err(AppError::internal_from_generic)?;
+        let written = io::copy(&mut entry.take(limit + 1), &mut output).map_err(invalid_archive)?;
+
+        if written > limit {
+            return Err(if limit == max_file_size {
+                AppError::new(
+                    String::from(r#"File "$1" of the archive cannot exceed $2 bytes"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![file_name, max_file_size.to_string()])
+                )
+            } else {
+                AppError::new(
+                    String::from(r#"The uncompressed archive cannot exceed $1 bytes"#),
+                    AppErrorType::BadRequest,
+                    Some(vec![max_total_size.to_string()])
+                )
+            });
+        }
+
+        total_size += written;
+
+        files.insert(file_name, ExtractedFile {
+            file_path,
+            unique_file_name,
+            file_size: written as i64
+        });
+    }
+
+    Ok(files)
+}
+
+fn invalid_archive<E>(err: E) -> AppError
+where E: std::fmt::Display
+{
+    AppError::new(
+        format!("Invalid ZIP archive: {}", err),
+        AppErrorType::BadRequest,
+        None
+    )
+}
@@ -0,0 +1,95 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use sha2::{Digest, Sha256};
+
+/// Generates a random opaque token (32 bytes, hex encoded) suitable for refresh tokens and similar secrets.
+///
+/// The raw token must only be handed to the client. Store the result of `hash_secret_token` instead.
+pub fn generate_secret_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    hex::encode(bytes)
+}
+
+/// Hashes an opaque token with SHA-256 so it can be stored and looked up without keeping the raw value.
+///
+/// Unlike passwords, these tokens have enough entropy to not need a slow hash like bcrypt.
+pub fn hash_secret_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// SHA-256 of the file, hex encoded. The file is read in chunks, so it is never loaded whole into memory.
+pub fn hash_file(file_path: &str) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(file_path)?;
+    let mut hasher = Sha256::new();
+
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Derives a 256-bit key for `purpose` from the secret with HMAC-SHA256, so one secret can key unrelated uses.
+pub fn derive_key(secret: &[u8], purpose: &str) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(purpose.as_bytes());
+
+    mac.finalize().into_bytes().into()
+}
+
+/// Encrypts the value with AES-256-GCM and returns the random nonce followed by the ciphertext, base64 encoded.
+///
+/// The `context` is authenticated along with the value, so it can only be decrypted with the same context.
+pub fn encrypt(key: &[u8; 32], value: &[u8], context: &[u8]) -> String {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("valid AES-256 key"));
+
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut ciphertext = value.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(context), &mut ciphertext)
+        .expect("value within the AES-GCM limits");
+
+    STANDARD.encode([nonce.as_slice(), ciphertext.as_slice()].concat())
+}
+
+/// Decrypts a value encrypted by `encrypt`. Fails if it was encrypted with another key or context, or was tampered with.
+pub fn decrypt(key: &[u8; 32], encrypted: &str, context: &[u8]) -> Option<Vec<u8>> {
+    let encrypted = STANDARD.decode(encrypted).ok()?;
+
+    if encrypted.len() < NONCE_LEN {
+        return None;
+    }
+
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("valid AES-256 key"));
+
+    let mut value = ciphertext.to_vec();
+    let value = key.open_in_place(Nonce::try_assume_unique_for_key(nonce).ok()?, Aad::from(context), &mut value).ok()?;
+
+    Some(value.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypts_with_the_same_key_and_context() {
+        let key = derive_key(b"secret", "purpose");
+        let encrypted = encrypt(&key, b"value", b"context");
+
+        assert_eq!(decrypt(&key, &encrypted, b"context"), Some(b"value".to_vec()));
+        assert_eq!(decrypt(&key, &encrypted, b"other context"), None);
+        assert_eq!(decrypt(&derive_key(b"secret", "other purpose"), &encrypted, b"context"), None);
+    }
+
+    #[test]
+    fn encrypts_with_a_new_nonce_every_time() {
+        let key = derive_key(b"secret", "purpose");
+
+        assert_ne!(encrypt(&key, b"value", b"context"), encrypt(&key, b"value", b"context"));
+    }
+}
@@ -16,6 +16,11 @@ pub fn to_app_error(error: sqlx::Error) -> AppError {
     )
 }
 
+/// Pattern for `LIKE ... ESCAPE '\'` that matches the values containing `search`
+pub fn contains_pattern(search: &str) -> String {
+    format!("%{}%", search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+}
+
 pub async fn run_migrations(pool: &sqlx::Pool<sqlx::Sqlite>) -> Result<(), AppError> {
     sqlx::migrate!()
         .run(pool)
@@ -0,0 +1,195 @@
+use actix_web::HttpRequest;
+
+/// Conditional and range headers of a file download
+pub struct DownloadRequest {
+    range: Option<String>,
+    if_none_match: Option<String>,
+    if_range: Option<String>
+}
+
+/// Bytes from `start` to `end` of a file, both included
+#[derive(Clone, Copy)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64
+}
+
+pub enum RangeRequest {
+    /// There is no range to honor, so the whole file is sent
+    Full,
+    Partial(ByteRange),
+    /// The range starts past the end of the file
+    NotSatisfiable
+}
+
+impl DownloadRequest {
+    pub fn from_request(req: &HttpRequest) -> DownloadRequest {
+        let header = |name: &str| req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        DownloadRequest {
+            range: header("Range"),
+            if_none_match: header("If-None-Match"),
+            if_range: header("If-Range")
+        }
+    }
+
+    /// Whether the client already has the file with the `etag`. ETags are compared weakly, as `If-None-Match` requires.
+    pub fn is_not_modified(&self, etag: &str) -> bool {
+        let etag = etag.trim_start_matches("W/");
+
+        match &self.if_none_match {
+            Some(if_none_match) => if_none_match.split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag),
+            None => false
+        }
+    }
+
+    /// Range of the file of `file_size` bytes that was requested. Only single byte ranges are honored, any other one is
+    /// ignored and the whole file is sent. So is the range when `If-Range` does not match the `etag`, since the client
+    /// has parts of another file.
+    pub fn range(&self, file_size: u64, etag: Option<&str>) -> RangeRequest {
+        let range = match &self.range {
+            Some(range) => range,
+            None => return RangeRequest::Full
+        };
+
+        if let Some(if_range) = &self.if_range {
+            // Dates are not supported, and weak ETags never match
+            if if_range.starts_with("W/") || etag != Some(if_range.trim()) {
+                return RangeRequest::Full;
+            }
+        }
+
+        parse_range(range, file_size)
+    }
+}
+
+impl ByteRange {
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Value of the `Content-Range` header of the range
+    pub fn content_range(&self, file_size: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, file_size)
+    }
+}
+
+fn parse_range(range: &str, file_size: u64) -> RangeRequest {
+    let spec = match range.trim().strip_prefix("bytes=") {
+        Some(spec) => spec.trim(),
+        None => return RangeRequest::Full
+    };
+
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => return RangeRequest::Full
+    };
+
+    // Suffix range with the last bytes of the file
+    if start.is_empty() {
+        return match end.parse::<u64>() {
+            Ok(0) => RangeRequest::NotSatisfiable,
+            Ok(_) if file_size == 0 => RangeRequest::NotSatisfiable,
+            Ok(suffix) => RangeRequest::Partial(ByteRange {
+                start: file_size - suffix.min(file_size),
+                end: file_size - 1
+            }),
+            Err(_) => RangeRequest::Full
+        };
+    }
+
+    let start = match start.parse::<u64>() {
+        Ok(start) => start,
+        Err(_) => return RangeRequest::Full
+    };
+
+    let end = if end.is_empty() {
+        None
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => Some(end),
+            _ => return RangeRequest::Full
+        }
+    };
+
+    if start >= file_size {
+        return RangeRequest::NotSatisfiable;
+    }
+
+    RangeRequest::Partial(ByteRange {
+        start,
+        end: end.map_or(file_size - 1, |end| end.min(file_size - 1))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(range: &str, file_size: u64) -> Option<(u64, u64)> {
+        match parse_range(range, file_size) {
+            RangeRequest::Partial(range) => Some((range.start, range.end)),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn parses_bounded_ranges() {
+        assert_eq!(partial("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(partial("bytes= 10 - 19 ", 1000), Some((10, 19)));
+        // The end is clamped to the last byte of the file
+        assert_eq!(partial("bytes=900-2000", 1000), Some((900, 999)));
+    }
+
+    #[test]
+    fn parses_open_ended_ranges() {
+        assert_eq!(partial("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(partial("bytes=999-", 1000), Some((999, 999)));
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(partial("bytes=-100", 1000), Some((900, 999)));
+        // A suffix longer than the file is the whole file
+        assert_eq!(partial("bytes=-5000", 1000), Some((0, 999)));
+        assert!(matches!(parse_range("bytes=-0", 1000), RangeRequest::NotSatisfiable));
+        assert!(matches!(parse_range("bytes=-10", 0), RangeRequest::NotSatisfiable));
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_not_satisfiable() {
+        assert!(matches!(parse_range("bytes=1000-", 1000), RangeRequest::NotSatisfiable));
+        assert!(matches!(parse_range("bytes=1500-1600", 1000), RangeRequest::NotSatisfiable));
+        assert!(matches!(parse_range("bytes=0-", 0), RangeRequest::NotSatisfiable));
+    }
+
+    #[test]
+    fn ignores_multiple_and_invalid_ranges() {
+        assert!(matches!(parse_range("bytes=0-9,20-29", 1000), RangeRequest::Full));
+        assert!(matches!(parse_range("bytes=20-10", 1000), RangeRequest::Full));
+        assert!(matches!(parse_range("bytes=a-10", 1000), RangeRequest::Full));
+        assert!(matches!(parse_range("bytes=10", 1000), RangeRequest::Full));
+        assert!(matches!(parse_range("items=0-9", 1000), RangeRequest::Full));
+    }
+
+    #[test]
+    fn ignores_ranges_of_another_file() {
+        let request = DownloadRequest {
+            range: Some(String::from("bytes=0-9")),
+            if_none_match: None,
+            if_range: Some(String::from("\"other\""))
+        };
+
+        assert!(matches!(request.range(1000, Some("\"current\"")), RangeRequest::Full));
+        assert!(matches!(request.range(1000, Some("\"other\"")), RangeRequest::Partial(_)));
+    }
+}
@@ -10,12 +10,10 @@ pub async fn remove_file(file_path: &str) -> Result<(), AppError> {
         ))
 }
 
-pub fn check_pdf(file_path: &str) -> Result<(), AppError> {
-    lopdf::Document::load(file_path)
-        .map(|_| ())
-        .map_err(|err| AppError::new(
-            format!("Failed to load pdf file: {}", err),
-            AppErrorType::BadRequest,
-            None
-        ))
+/// File extracted from an upload, such as an archive or a combined PDF, stored with a unique name like the uploaded
+/// ones
+pub struct ExtractedFile {
+    pub file_path: String,
+    pub unique_file_name: String,
+    pub file_size: i64
 }
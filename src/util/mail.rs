@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use actix_web::web;
+use lettre::{message::{header::ContentType, Mailbox}, transport::smtp::authentication::Credentials, FileTransport, Message, SmtpTransport, Transport};
+
+use crate::{config::{MailConfig, MailTransportConfig, SmtpTls}, error::error::{AppError, AppErrorType}};
+
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String
+}
+
+/// Delivers emails. Implementations are blocking, `MailService` takes care of running them outside the async runtime.
+pub trait Mailer: Send + Sync {
+    fn send(&self, mail: &Mail) -> Result<(), AppError>;
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: SmtpTransport
+}
+
+impl SmtpMailer {
+    pub fn new(from: Mailbox, host: &str, port: u16, username: &str, password: &str, tls: &SmtpTls) -> Result<SmtpMailer, AppError> {
+        let builder = match tls {
+            SmtpTls::None => SmtpTransport::builder_dangerous(host),
+            SmtpTls::StartTls => SmtpTransport::starttls_relay(host).map_err(AppError::internal_from_generic)?,
+            SmtpTls::Tls => SmtpTransport::relay(host).map_err(AppError::internal_from_generic)?
+        };
+
+        let transport = builder
+            .port(port)
+            .credentials(Credentials::new(username.to_string(), password.to_string()))
+            .build();
+
+        Ok(SmtpMailer {
+            from,
+            transport
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        let message = build_message(&self.from, mail)?;
+
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .map_err(|err| AppError::new(
+                format!("Failed to send email: {}", err),
+                AppErrorType::InternalServerError,
+                None
+            ))
+    }
+}
+
+/// Writes every email as an .eml file in a directory instead of sending it. Meant for local development and tests.
+pub struct OutboxMailer {
+    from: Mailbox,
+    transport: FileTransport
+}
+
+impl OutboxMailer {
+    pub fn new(from: Mailbox, dir: &str) -> OutboxMailer {
+        OutboxMailer {
+            from,
+            transport: FileTransport::new(dir)
+        }
+    }
+}
+
+impl Mailer for OutboxMailer {
+    fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        let message = build_message(&self.from, mail)?;
+
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .map_err(|err| AppError::new(
+                format!("Failed to write email to outbox: {}", err),
+                AppErrorType::InternalServerError,
+                None
+            ))
+    }
+}
+
+fn build_message(from: &Mailbox, mail: &Mail) -> Result<Message, AppError> {
+    let to = mail.to.parse::<Mailbox>()
+        .map_err(|err| AppError::new(
+            format!("Invalid email recipient: {}", err),
+            AppErrorType::InternalServerError,
+            None
+        ))?;
+
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(&mail.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body.clone())
+        .map_err(AppError::internal_from_generic)
+}
+
+pub struct MailService {
+    mailer: Arc<dyn Mailer>
+}
+
+impl MailService {
+    pub fn new(mailer: Arc<dyn Mailer>) -> MailService {
+        MailService {
+            mailer
+        }
+    }
+
+    pub fn from_config(config: &MailConfig) -> MailService {
+        let from = config.from.parse::<Mailbox>().expect("Invalid mail sender");
+
+        let mailer: Arc<dyn Mailer> = match &config.transport {
+            MailTransportConfig::Smtp(smtp) => Arc::new(
+                SmtpMailer::new(from, &smtp.host, smtp.port, &smtp.username, &smtp.password, &smtp.tls)
+                    .expect("Failed to create smtp mailer")
+            ),
+            MailTransportConfig::Outbox(dir) => Arc::new(OutboxMailer::new(from, dir))
+        };
+
+        MailService::new(mailer)
+    }
+
+    pub async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        let mailer = Arc::clone(&self.mailer);
+
+        web::block(move || {
+            mailer.send(&mail)
+        })
+        .await
+        .map_err(AppError::internal_from_generic)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn outbox_dir() -> String {
+        let dir = format!("{}/{}", std::env::temp_dir().display(), Uuid::now_v7());
+        std::fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn outbox_mailer(dir: &str) -> OutboxMailer {
+        OutboxMailer::new("Payroll Manager <payroll@example.com>".parse().unwrap(), dir)
+    }
+
+    #[actix_web::test]
+    async fn writes_emails_to_the_outbox() {
+        let dir = outbox_dir();
+        let mail_service = MailService::new(Arc::new(outbox_mailer(&dir)));
+
+        let result = mail_service.send(Mail {
+            to: String::from("employee@example.com"),
+            subject: String::from("Reset your password"),
+            body: String::from("Your reset token is abc123")
+        }).await;
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        let content = files.first().map(|file| std::fs::read_to_string(file).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(files.len(), 1);
+        assert!(files[0].extension().is_some_and(|extension| extension == "eml"));
+
+        let content = content.unwrap();
+        assert!(content.contains("From: \"Payroll Manager\" <payroll@example.com>"));
+        assert!(content.contains("To: employee@example.com"));
+        assert!(content.contains("Subject: Reset your password"));
+        assert!(content.contains("Your reset token is abc123"));
+    }
+
+    #[test]
+    fn rejects_invalid_recipients() {
+        let dir = outbox_dir();
+
+        let result = outbox_mailer(&dir).send(&Mail {
+            to: String::from("not an email"),
+            subject: String::from("Reset your password"),
+            body: String::new()
+        });
+
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(files, 0);
+    }
+}
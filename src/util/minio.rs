@@ -1,9 +1,11 @@
 use std::{path::Path, pin::Pin};
 
 use actix_web::web;
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 use minio::s3::{args::{BucketExistsArgs, MakeBucketArgs}, builders::ObjectContent, client::{Client, ClientBuilder}, creds::StaticProvider, http::BaseUrl, types::S3Api};
 
+use sha2::{Digest, Sha256};
+
 use crate::error::error::{AppError, AppErrorType};
 
 pub struct MinioService {
@@ -91,9 +93,29 @@ impl MinioService {
         Ok(())
     }
 
+    /// SHA-256 of the object, hex encoded. The object is streamed, so it is never loaded whole into memory.
+    pub async fn hash_file(&self, bucket_name: &str, object_name: &str) -> Result<String, AppError> {
+        let mut stream = self.get_file_stream(bucket_name, object_name).await?.stream;
+        let mut hasher = Sha256::new();
+
+        while let Some(chunk) = stream.next().await {
+            hasher.update(chunk.map_err(AppError::internal_from_generic)?);
+        }
+
+        Ok(hex::encode(hasher.finalize()))
+    }
+
     pub async fn get_file_stream(&self, bucket_name: &str, object_name: &str) -> Result<MinioStreamInfo, AppError> {
+        self.get_file_range_stream(bucket_name, object_name, None, None).await
+    }
+
+    /// Streams `length` bytes of the object starting at `offset`, or up to its end when there is no length. The size
+    /// of the stream is the one of the range that was read.
+    pub async fn get_file_range_stream(&self, bucket_name: &str, object_name: &str, offset: Option<u64>, length: Option<u64>) -> Result<MinioStreamInfo, AppError> {
         let (stream, size) = self.client
             .get_object(bucket_name, object_name)
+            .offset(offset)
+            .length(length)
             .send()
             .await
             .map_err(
@@ -3,6 +3,11 @@ pub mod db;
 pub mod minio;
 pub mod multipart;
 pub mod file;
-
-#[macro_use]
-pub mod permission;
\ No newline at end of file
+pub mod crypto;
+pub mod mail;
+pub mod request;
+pub mod tax_id;
+pub mod pagination;
+pub mod archive;
+pub mod pdf;
+pub mod download;
@@ -193,6 +193,52 @@ pub async fn extract_file(payload: &mut Multipart) -> Result<FileInfo, AppError>
     ))
 }
 
+/// Extracts a field with the given name from an Actix multipart payload into memory, along with the name of the file
+/// it was sent as, if any.
+///
+/// Like the other extractors, it expects the field to be the next one of the payload. It is meant for small fields,
+/// such as manifests, so it fails as soon as the data exceeds `max_size` bytes.
+pub async fn extract_field(payload: &mut Multipart, name: &str, max_size: u64) -> Result<FieldData, AppError> {
+    if let Some(Ok(mut field)) = payload.next().await {
+        let content_type = field.content_disposition().unwrap();
+
+        if content_type.get_name() == Some(name) {
+            let file_name = content_type.get_filename().map(|name| name.to_string());
+
+            let mut content = Vec::new();
+
+            while let Some(chunk) = field.try_next().await
+                .map_err(|err| AppError::new(
+                    format!("Failed reading field data: {}", err),
+                    AppErrorType::InternalServerError,
+                    None
+                ))?
+            {
+                if (content.len() + chunk.len()) as u64 > max_size {
+                    return Err(AppError::new(
+                        format!(r#""{}" field cannot exceed {} bytes"#, name, max_size),
+                        AppErrorType::BadRequest,
+                        None
+                    ));
+                }
+
+                content.extend_from_slice(&chunk);
+            }
+
+            return Ok(FieldData {
+                file_name,
+                content
+            });
+        }
+    }
+
+    Err(AppError::new(
+        format!("Expected {}", name),
+        AppErrorType::BadRequest,
+        None
+    ))
+}
+
 async fn process_file_receiving(field: &mut Field, file_path: &str) -> Result<i64, AppError> {
     let max_size = config::get().file.max_size;
 
@@ -415,3 +461,8 @@ pub struct FileInfo {
     pub unique_file_name: String,
     pub file_size: i64
 }
+
+pub struct FieldData {
+    pub file_name: Option<String>,
+    pub content: Vec<u8>
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::error::error::{AppError, AppErrorType};
+
+/// Page of a listing, with the total of items that match its filters
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, pagination: &Pagination) -> Page<T> {
+        Page {
+            items,
+            total,
+            limit: pagination.limit,
+            offset: pagination.offset
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset
+        }
+    }
+
+    pub fn try_map<U, E>(self, f: impl FnMut(T) -> Result<U, E>) -> Result<Page<U>, E> {
+        Ok(Page {
+            items: self.items.into_iter().map(f).collect::<Result<_, _>>()?,
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset
+        })
+    }
+}
+
+/// Limit and offset of a listing, already checked
+pub struct Pagination {
+    pub limit: i64,
+    pub offset: i64
+}
+
+impl Pagination {
+    pub const DEFAULT_LIMIT: i64 = 25;
+
+    /// Missing values default to the first page. Every listing sets its own maximum limit.
+    pub fn new(limit: Option<i64>, offset: Option<i64>, max_limit: i64) -> Result<Pagination, AppError> {
+        let limit = limit.unwrap_or(Self::DEFAULT_LIMIT.min(max_limit));
+        let offset = offset.unwrap_or(0);
+
+        if limit < 1 || limit > max_limit {
+            return Err(AppError::new(
+                String::from(r#"Limit must be between 1 and $1"#),
+                AppErrorType::BadRequest,
+                Some(vec![max_limit.to_string()])
+            ));
+        }
+
+        if offset < 0 {
+            return Err(AppError::new(
+                String::from(r#"Offset must be greater than or equal to 0"#),
+                AppErrorType::BadRequest,
+                None
+            ));
+        }
+
+        Ok(Pagination {
+            limit,
+            offset
+        })
+    }
+
+    pub fn fill_query(&self, query: &mut QueryBuilder<Sqlite>) {
+        query.push(" LIMIT ");
+        query.push_bind(self.limit);
+        query.push(" OFFSET ");
+        query.push_bind(self.offset);
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC"
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,404 @@
+use std::{borrow::Cow, collections::{BTreeMap, BTreeSet}, io::Read};
+
+use flate2::read::ZlibDecoder;
+use lopdf::{Dictionary, Document, Object, ObjectId, ObjectStream, Stream};
+use serde::Serialize;
+use uuid::Uuid;
+use weezl::{decode::Decoder, BitOrder, LzwStatus};
+
+use crate::{error::error::{AppError, AppErrorType}, util::{crypto::hash_file, file::ExtractedFile}};
+
+/// Active content that is stripped from the uploaded PDFs
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovedContent {
+    JavaScript,
+    /// Action run when the document is opened
+    OpenAction,
+    /// Actions run on events of the document, its pages or its annotations
+    AdditionalActions,
+    /// Actions that run an application or open a file
+    Launch,
+    EmbeddedFiles
+}
+
+pub struct SanitizedPdf {
+    /// Size of the file once sanitized
+    pub file_size: i64,
+    /// SHA-256 of the file once sanitized, hex encoded
+    pub content_hash: String,
+    pub removed: Vec<RemovedContent>
+}
+
+/// Checks that the file is a valid PDF whose streams do not decompress to more than `max_uncompressed_size` bytes,
+/// and strips its active content. The file is only rewritten when something was removed.
+pub fn sanitize_pdf(file_path: &str, max_uncompressed_size: u64) -> Result<SanitizedPdf, AppError> {
+    // lopdf decompresses the object streams while loading, so they are only expanded once their size is checked
+    let mut document = Document::load_filtered(file_path, defer_object_streams).map_err(load_error)?;
+
+    check_uncompressed_size(&document, max_uncompressed_size)?;
+    expand_object_streams(&mut document);
+
+    let mut removed = BTreeSet::new();
+
+    for object in document.objects.values_mut() {
+        sanitize_object(object, &mut removed);
+    }
+
+    if !removed.is_empty() {
+        document.prune_objects();
+        document.save(file_path).map_err(AppError::internal_from_generic)?;
+    }
+
+    let file_size = std::fs::metadata(file_path).map_err(AppError::internal_from_generic)?.len();
+    let content_hash = hash_file(file_path).map_err(AppError::internal_from_generic)?;
+
+    Ok(SanitizedPdf {
+        file_size: file_size as i64,
+        content_hash,
+        removed: removed.into_iter().collect()
+    })
+}
+
+/// Text of every page of the PDF, by page number. The file must have gone through `sanitize_pdf`. Pages without text, such as scanned ones, have an empty text.
+pub fn extract_page_texts(file_path: &str) -> Result<Vec<(u32, String)>, AppError> {
+    let document = load_pdf(file_path)?;
+
+    Ok(document.get_pages()
+        .keys()
+        .map(|page_number| (*page_number, document.extract_text(&[*page_number]).unwrap_or_default()))
+        .collect())
+}
+
+/// Writes a PDF into `dest_dir` for every group of pages of the document, keeping only the pages of the group. The file
+/// must have gone through `sanitize_pdf` and the caller is responsible for removing `dest_dir`.
+pub fn split_pdf(file_path: &str, page_groups: &[Vec<u32>], dest_dir: &str) -> Result<Vec<ExtractedFile>, AppError> {
+    let document = load_pdf(file_path)?;
+    let page_numbers: Vec<u32> = document.get_pages().into_keys().collect();
+
+    page_groups.iter().map(|pages| {
+        let excluded: Vec<u32> = page_numbers.iter()
+            .filter(|page_number| !pages.contains(page_number))
+            .copied()
+            .collect();
+
+        let mut part = document.clone();
+        part.delete_pages(&excluded);
+        part.prune_objects();
+        part.compress();
+
+        let unique_file_name = Uuid::now_v7().to_string();
+        let file_path = format!("{}/{}", dest_dir, unique_file_name);
+
+        part.save(&file_path).map_err(AppError::internal_from_generic)?;
+        let file_size = std::fs::metadata(&file_path).map_err(AppError::internal_from_generic)?.len();
+
+        Ok(ExtractedFile {
+            file_path,
+            unique_file_name,
+            file_size: file_size as i64
+        })
+    })
+    .collect()
+}
+
+fn load_pdf(file_path: &str) -> Result<Document, AppError> {
+    Document::load(file_path).map_err(load_error)
+}
+
+fn load_error(err: lopdf::Error) -> AppError {
+    AppError::new(
+        format!("Failed to load pdf file: {}", err),
+        AppErrorType::BadRequest,
+        None
+    )
+}
+
+/// Type the object streams are given while loading, so lopdf keeps them as they are
+const DEFERRED_OBJECT_STREAM: &[u8] = b"DeferredObjStm";
+
+/// The returned object is only used by lopdf for the objects inside object streams, and none is expanded while loading
+fn defer_object_streams(object_id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    if let Object::Stream(stream) = object {
+        if stream.dict.has_type(b"ObjStm") {
+            stream.dict.set("Type", Object::Name(DEFERRED_OBJECT_STREAM.to_vec()));
+        }
+    }
+
+    Some((object_id, Object::Null))
+}
+
+/// Adds the objects of the deferred object streams to the document, without replacing the ones it already has, the same
+/// way lopdf does while loading
+fn expand_object_streams(document: &mut Document) {
+    let mut objects = BTreeMap::new();
+
+    for object in document.objects.values_mut() {
+        if let Object::Stream(stream) = object {
+            if stream.dict.has_type(DEFERRED_OBJECT_STREAM) {
+                stream.dict.set("Type", "ObjStm");
+
+                if let Ok(object_stream) = ObjectStream::new(stream) {
+                    objects.extend(object_stream.objects);
+                }
+            }
+        }
+    }
+
+    for (object_id, object) in objects {
+        document.objects.entry(object_id).or_insert(object);
+    }
+}
+
+/// The streams are decompressed with a limit on the output, so a compression bomb is stopped as soon as it exceeds it
+fn check_uncompressed_size(document: &Document, max_uncompressed_size: u64) -> Result<(), AppError> {
+    let mut total_size = 0u64;
+
+    for object in document.objects.values() {
+        if let Object::Stream(stream) = object {
+            total_size += uncompressed_size(stream, max_uncompressed_size - total_size)?;
+
+            if total_size > max_uncompressed_size {
+                return Err(AppError::new(
+                    String::from("The decompressed content of the pdf cannot exceed $1 bytes"),
+                    AppErrorType::BadRequest,
+                    Some(vec![max_uncompressed_size.to_string()])
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Size of the stream once every filter of its chain is applied, up to one byte over `limit`. Image codecs are only
+/// accepted as the last filter and counted as they are, since their content is never decoded. Filters whose output
+/// cannot be measured are rejected. Corrupted data is counted up to where it can be read.
+fn uncompressed_size(stream: &Stream, limit: u64) -> Result<u64, AppError> {
+    if !stream.dict.has(b"Filter") {
+        return Ok(stream.content.len() as u64);
+    }
+
+    let filters = stream.filters().map_err(|_| AppError::new(
+        String::from("The pdf has a stream with an invalid filter"),
+        AppErrorType::BadRequest,
+        None
+    ))?;
+
+    let early_change = stream.dict.get(b"DecodeParms")
+        .and_then(Object::as_dict)
+        .and_then(|params| params.get(b"EarlyChange"))
+        .and_then(Object::as_i64)
+        .map(|early_change| early_change != 0)
+        .unwrap_or(true);
+
+    let mut content = Cow::Borrowed(stream.content.as_slice());
+
+    for (index, filter) in filters.iter().enumerate() {
+        if content.len() as u64 > limit {
+            break;
+        }
+
+        content = Cow::Owned(match *filter {
+            b"FlateDecode" => inflate(&content, limit),
+            b"LZWDecode" => decode_lzw(&content, early_change, limit),
+            b"DCTDecode" | b"JPXDecode" | b"CCITTFaxDecode" | b"JBIG2Decode" if index == filters.len() - 1 => break,
+            _ => return Err(AppError::new(
+                String::from("The pdf uses the $1 filter, whose decompressed size cannot be checked"),
+                AppErrorType::BadRequest,
+                Some(vec![String::from_utf8_lossy(filter).into_owned()])
+            ))
+        });
+    }
+
+    Ok(content.len() as u64)
+}
+
+fn inflate(input: &[u8], limit: u64) -> Vec<u8> {
+    let mut output = Vec::new();
+    // On corrupted data the output read until the error is kept
+    let _ = ZlibDecoder::new(input).take(limit + 1).read_to_end(&mut output);
+
+    output
+}
+
+fn decode_lzw(mut input: &[u8], early_change: bool, limit: u64) -> Vec<u8> {
+    // The same settings lopdf decodes with
+    let mut decoder = if early_change {
+        Decoder::with_tiff_size_switch(BitOrder::Msb, 8)
+    } else {
+        Decoder::new(BitOrder::Msb, 8)
+    };
+
+    let mut output = Vec::new();
+    let mut buffer = [0u8; 8192];
+
+    while output.len() as u64 <= limit {
+        let result = decoder.decode_bytes(input, &mut buffer);
+        output.extend_from_slice(&buffer[..result.consumed_out]);
+        input = &input[result.consumed_in..];
+
+        if !matches!(result.status, Ok(LzwStatus::Ok)) {
+            break;
+        }
+    }
+
+    output.truncate(limit as usize + 1);
+    output
+}
+
+fn sanitize_object(object: &mut Object, removed: &mut BTreeSet<RemovedContent>) {
+    match object {
+        Object::Dictionary(dictionary) => sanitize_dictionary(dictionary, removed),
+        Object::Stream(stream) => sanitize_dictionary(&mut stream.dict, removed),
+        Object::Array(items) => items.iter_mut().for_each(|item| sanitize_object(item, removed)),
+        _ => ()
+    }
+}
+
+/// Removes the keys that trigger or hold active content. JavaScript and Launch actions are emptied, since they can be
+/// referenced from anywhere, and the objects that are no longer referenced are pruned afterwards.
+fn sanitize_dictionary(dictionary: &mut Dictionary, removed: &mut BTreeSet<RemovedContent>) {
+    let action = dictionary.get(b"S").and_then(Object::as_name).ok().map(|action| action.to_vec());
+
+    match action.as_deref() {
+        Some(b"JavaScript") => {
+            *dictionary = Dictionary::new();
+            removed.insert(RemovedContent::JavaScript);
+            return;
+        },
+        Some(b"Launch") => {
+            *dictionary = Dictionary::new();
+            removed.insert(RemovedContent::Launch);
+            return;
+        },
+        _ => ()
+    }
+
+    let removed_keys: [(&[u8], RemovedContent); 6] = [
+        (b"OpenAction", RemovedContent::OpenAction),
+        (b"AA", RemovedContent::AdditionalActions),
+        (b"JS", RemovedContent::JavaScript),
+        // Name trees of the catalog
+        (b"JavaScript", RemovedContent::JavaScript),
+        (b"EmbeddedFiles", RemovedContent::EmbeddedFiles),
+        // Embedded streams of a file specification
+        (b"EF", RemovedContent::EmbeddedFiles)
+    ];
+
+    for (key, content) in removed_keys {
+        if dictionary.remove(key).is_some() {
+            removed.insert(content);
+        }
+    }
+
+    for (_, value) in dictionary.iter_mut() {
+        sanitize_object(value, removed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::dictionary;
+
+    use super::*;
+
+    const MAX_UNCOMPRESSED_SIZE: u64 = 64 * 1024;
+
+    fn deflate(content: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Writes a one page PDF with the given content stream and action run on opening, and returns its path
+    fn write_pdf(stream: Stream, open_action: Option<Dictionary>) -> String {
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let content_id = document.add_object(stream);
+        let page_id = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Contents" => content_id
+        });
+        document.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1
+        }));
+
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+
+        if let Some(open_action) = open_action {
+            catalog.set("OpenAction", document.add_object(open_action));
+        }
+
+        let catalog_id = document.add_object(catalog);
+        document.trailer.set("Root", catalog_id);
+
+        let file_path = format!("{}/{}.pdf", std::env::temp_dir().display(), Uuid::now_v7());
+        document.save(&file_path).unwrap();
+        file_path
+    }
+
+    fn text_stream() -> Stream {
+        Stream::new(Dictionary::new(), b"BT /F1 12 Tf 72 712 Td (Payroll) Tj ET".to_vec())
+    }
+
+    #[test]
+    fn rejects_nested_deflate_bombs() {
+        let content = deflate(&deflate(&vec![0u8; 16 * MAX_UNCOMPRESSED_SIZE as usize]));
+        let stream = Stream::new(dictionary! { "Filter" => vec!["FlateDecode".into(), "FlateDecode".into()] }, content);
+        let file_path = write_pdf(stream, None);
+
+        let result = sanitize_pdf(&file_path, MAX_UNCOMPRESSED_SIZE);
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn accepts_nested_deflate_under_the_limit() {
+        let content = deflate(&deflate(&text_stream().content));
+        let stream = Stream::new(dictionary! { "Filter" => vec!["FlateDecode".into(), "FlateDecode".into()] }, content);
+        let file_path = write_pdf(stream, None);
+
+        let result = sanitize_pdf(&file_path, MAX_UNCOMPRESSED_SIZE);
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert!(result.is_ok_and(|sanitized| sanitized.removed.is_empty()));
+    }
+
+    #[test]
+    fn rejects_filters_that_cannot_be_measured() {
+        let stream = Stream::new(dictionary! { "Filter" => "RunLengthDecode" }, vec![0x81, 0, 0x80]);
+        let file_path = write_pdf(stream, None);
+
+        let result = sanitize_pdf(&file_path, MAX_UNCOMPRESSED_SIZE);
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn removes_javascript_open_actions() {
+        let open_action = dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") };
+        let file_path = write_pdf(text_stream(), Some(open_action));
+
+        let result = sanitize_pdf(&file_path, MAX_UNCOMPRESSED_SIZE);
+        let document = Document::load(&file_path);
+        std::fs::remove_file(&file_path).unwrap();
+
+        let sanitized = result.unwrap_or_else(|_| panic!("the pdf should be sanitized"));
+        assert!(sanitized.removed == vec![RemovedContent::JavaScript, RemovedContent::OpenAction]);
+
+        let document = document.unwrap();
+        let catalog = document.catalog().unwrap();
+        assert!(!catalog.has(b"OpenAction"));
+        assert!(!document.objects.values().any(|object| object.as_dict().is_ok_and(|dictionary| dictionary.has(b"JS"))));
+    }
+}
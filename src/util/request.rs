@@ -0,0 +1,17 @@
+use actix_web::HttpRequest;
+
+use crate::config;
+
+/// Returns the ip address of the client. Proxy headers are only honored when configured, since clients can set
+/// them to anything.
+pub fn client_ip(req: &HttpRequest) -> String {
+    let connection_info = req.connection_info();
+
+    let ip = if config::get().auth.trust_forwarded_headers {
+        connection_info.realip_remote_addr()
+    } else {
+        connection_info.peer_addr()
+    };
+
+    ip.unwrap_or("unknown").to_string()
+}
@@ -0,0 +1,151 @@
+const NIF_LETTERS: &[u8] = b"TRWAGMYFPDXBNJZSQVHLCKE";
+const CIF_CONTROL_LETTERS: &[u8] = b"JABCDEFGHI";
+
+/// Whether the value is a valid Spanish tax identification number: a NIF of a person (DNI or NIE) or a CIF of an
+/// organization. It must be in uppercase and without separators.
+pub fn is_valid_spanish_tax_id(tax_id: &str) -> bool {
+    let bytes = tax_id.as_bytes();
+
+    if bytes.len() != 9 || !bytes.iter().all(|b| b.is_ascii_digit() || b.is_ascii_uppercase()) {
+        return false;
+    }
+
+    match bytes[0] {
+        b'0'..=b'9' => is_valid_dni(bytes),
+        b'X' | b'Y' | b'Z' => is_valid_nie(bytes),
+        _ => is_valid_cif(bytes)
+    }
+}
+
+/// Whether the value is a valid NIF of a person, either a DNI or a NIE. It must be in uppercase and without separators.
+pub fn is_valid_spanish_national_id(national_id: &str) -> bool {
+    let bytes = national_id.as_bytes();
+
+    if bytes.len() != 9 || !bytes.iter().all(|b| b.is_ascii_digit() || b.is_ascii_uppercase()) {
+        return false;
+    }
+
+    match bytes[0] {
+        b'0'..=b'9' => is_valid_dni(bytes),
+        b'X' | b'Y' | b'Z' => is_valid_nie(bytes),
+        _ => false
+    }
+}
+
+/// Removes the separators that tax ids are usually written with, and converts them to uppercase
+pub fn normalize_tax_id(tax_id: &str) -> String {
+    tax_id.replace(['-', ' ', '.'], "").to_uppercase()
+}
+
+/// 8 digits followed by the letter of the number modulo 23
+fn is_valid_dni(bytes: &[u8]) -> bool {
+    match parse_digits(&bytes[..8]) {
+        Some(number) => NIF_LETTERS[(number % 23) as usize] == bytes[8],
+        None => false
+    }
+}
+
+/// Same as a DNI, with the first digit replaced by X (0), Y (1) or Z (2)
+fn is_valid_nie(bytes: &[u8]) -> bool {
+    let prefix = match bytes[0] {
+        b'X' => b'0',
+        b'Y' => b'1',
+        _ => b'2'
+    };
+
+    let mut dni = [0u8; 9];
+    dni[0] = prefix;
+    dni[1..].copy_from_slice(&bytes[1..]);
+
+    is_valid_dni(&dni)
+}
+
+/// Letter of the kind of organization, 7 digits and a control character. Depending on the kind, the control
+/// character must be a digit, a letter, or can be either.
+fn is_valid_cif(bytes: &[u8]) -> bool {
+    let kind = bytes[0];
+
+    if !b"ABCDEFGHJKLMNPQRSUVW".contains(&kind) || parse_digits(&bytes[1..8]).is_none() {
+        return false;
+    }
+
+    let sum: u32 = bytes[1..8].iter().enumerate().map(|(i, b)| {
+        let digit = (b - b'0') as u32;
+
+        if i % 2 == 0 {
+            let doubled = digit * 2;
+            doubled / 10 + doubled % 10
+        } else {
+            digit
+        }
+    }).sum();
+
+    let control_digit = (10 - sum % 10) % 10;
+    let control_letter = CIF_CONTROL_LETTERS[control_digit as usize];
+    let control = bytes[8];
+
+    match kind {
+        b'A' | b'B' | b'E' | b'H' => control == b'0' + control_digit as u8,
+        b'K' | b'L' | b'M' | b'N' | b'P' | b'Q' | b'R' | b'S' | b'W' => control == control_letter,
+        _ => control == b'0' + control_digit as u8 || control == control_letter
+    }
+}
+
+fn parse_digits(bytes: &[u8]) -> Option<u32> {
+    if !bytes.iter().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_dni_letters() {
+        assert!(is_valid_spanish_tax_id("12345678Z"));
+        assert!(is_valid_spanish_tax_id("00000000T"));
+        assert!(!is_valid_spanish_tax_id("12345678A"));
+        assert!(!is_valid_spanish_tax_id("1234567Z"));
+    }
+
+    #[test]
+    fn validates_nie_letters() {
+        assert!(is_valid_spanish_tax_id("X1234567L"));
+        assert!(is_valid_spanish_tax_id("Y1234567X"));
+        assert!(is_valid_spanish_tax_id("Z1234567R"));
+        assert!(!is_valid_spanish_tax_id("X1234567A"));
+    }
+
+    #[test]
+    fn validates_cif_control_characters() {
+        // Digit control
+        assert!(is_valid_spanish_tax_id("A58818501"));
+        assert!(!is_valid_spanish_tax_id("A5881850A"));
+        // Letter control
+        assert!(is_valid_spanish_tax_id("Q2826000H"));
+        assert!(!is_valid_spanish_tax_id("Q28260008"));
+        // Either
+        assert!(is_valid_spanish_tax_id("G28260008"));
+        assert!(is_valid_spanish_tax_id("G2826000H"));
+        assert!(!is_valid_spanish_tax_id("G2826000A"));
+        // Unknown kind of organization
+        assert!(!is_valid_spanish_tax_id("I28260008"));
+    }
+
+    #[test]
+    fn national_ids_exclude_cifs() {
+        assert!(is_valid_spanish_national_id("12345678Z"));
+        assert!(is_valid_spanish_national_id("X1234567L"));
+        assert!(!is_valid_spanish_national_id("A58818501"));
+    }
+
+    #[test]
+    fn requires_normalized_values() {
+        assert!(!is_valid_spanish_tax_id("12345678z"));
+        assert!(!is_valid_spanish_tax_id("12345678-Z"));
+        assert_eq!(normalize_tax_id("12.345.678-z"), "12345678Z");
+        assert!(is_valid_spanish_tax_id(&normalize_tax_id(" x-1234567-l ")));
+    }
+}